@@ -1,6 +1,7 @@
 use std::path::Path;
 use std::rc::Rc;
 use std::sync::{Arc, OnceLock};
+use std::time::Duration;
 
 use cursive::theme::{BaseColor, BorderStyle, Palette, PaletteColor, Theme};
 use cursive::traits::Nameable;
@@ -16,12 +17,19 @@ use signal_hook_tokio::Signals;
 
 use crate::command::Command;
 use crate::commands::{CommandManager};
+use crate::config::ConfigValues;
+use crate::cover::Covers;
 use crate::events::{Event, EventManager};
+use crate::ext_traits::CursiveExt;
+use crate::hooks::{HookEvent, Hooks};
 use crate::library::Library;
+#[cfg(feature = "notify")]
+use crate::notification::Notifier;
 use crate::queue::Queue;
+use crate::scrobbler::Scrobbler;
 use crate::spotify::{PlayerEvent, Spotify};
 use crate::ui::create_cursive;
-use crate::{authentication, ui};
+use crate::{authentication, cache, ui};
 use crate::{queue, spotify};
 
 /// Set up the global logger to log to `filename`.
@@ -94,6 +102,13 @@ pub struct Application {
     event_manager: EventManager,
     /// The object to render to the terminal.
     cursive: CursiveRunner<Cursive>,
+    /// The external programs to run on player events.
+    hooks: Hooks,
+    /// Submits the played tracks to ListenBrainz.
+    scrobbler: Scrobbler,
+    /// Shows desktop notifications when the track changes.
+    #[cfg(feature = "notify")]
+    notifier: Notifier,
 }
 
 pub fn default_theme() -> Theme {
@@ -143,6 +158,7 @@ impl Application {
             )
             .unwrap();
 
+        let config = ConfigValues::load()?;
         let credentials = authentication::get_credentials()?;
 
         // DON'T USE STDOUT AFTER THIS CALL!
@@ -157,9 +173,17 @@ impl Application {
         });
 
         let event_manager = EventManager::new(cursive.cb_sink().clone());
-        let spotify = spotify::Spotify::new(event_manager.clone(), credentials);
+        Covers::init(event_manager.clone());
+        ui::cover::init(config.cover);
+        let spotify = spotify::Spotify::new(
+            event_manager.clone(),
+            credentials,
+            config.pcm,
+            config.playback.unwrap_or_default(),
+            cache::open(config.cache.unwrap_or_default().audio_limit_bytes()),
+        );
         let library = Arc::new(Library::new(event_manager.clone(), spotify.clone()));
-        let queue = Arc::new(queue::Queue::new(spotify.clone()));
+        let queue = Arc::new(queue::Queue::new(spotify.clone(), event_manager.clone()));
 
         let search = ui::search::SearchView::new(event_manager.clone(), queue.clone(), library.clone());
         let libraryview = ui::library::LibraryView::new(queue.clone(), library.clone());
@@ -184,11 +208,18 @@ impl Application {
             handle_signals(cursive_callback_sink).await;
         });
 
+        #[cfg(feature = "notify")]
+        let notifier = Notifier::new(cursive.cb_sink().clone(), library);
+
         Ok(Self {
             queue,
             spotify,
             event_manager,
             cursive,
+            hooks: Hooks::new(config.hooks.unwrap_or_default()),
+            scrobbler: Scrobbler::new(config.listenbrainz),
+            #[cfg(feature = "notify")]
+            notifier,
         })
     }
 
@@ -197,22 +228,40 @@ impl Application {
         // cursive event loop
         while self.cursive.is_running() {
             self.cursive.step();
+            ui::cover::flush(&mut self.cursive);
             for event in self.event_manager.msg_iter() {
                 match event {
                     Event::Player(state) => {
                         trace!("event received: {:?}", state);
                         self.spotify.update_status(state.clone());
 
-                        if state == PlayerEvent::FinishedTrack {
-                            self.queue.next(false);
+                        let current = self.queue.get_current();
+                        let position = self.spotify.get_current_progress();
+                        self.hooks.player_event(&state, current.as_ref(), position);
+                        self.scrobbler.player_event(&state, current.as_ref(), position);
+                        #[cfg(feature = "notify")]
+                        self.notifier.player_event(&state, current.as_ref());
+
+                        if state == PlayerEvent::FinishedTrack && !self.queue.next(false) {
+                            self.hooks.run(HookEvent::QueueEnd, current.as_ref(), position);
                         }
                     }
                     Event::Queue(event) => {
-                        self.queue.handle_event(event);
+                        if let Err(e) = self.queue.handle_event(event) {
+                            self.cursive
+                                .on_layout(|_, mut layout| layout.set_result(Err(e)));
+                        }
                     }
                     Event::SessionDied => self.spotify.start_worker(None),
+                    Event::LibraryUpdated => {
+                        self.hooks.run(HookEvent::LibraryUpdated, None, Duration::ZERO)
+                    }
                 }
             }
+
+            if let PlayerEvent::Playing(_) = self.spotify.get_current_status() {
+                self.scrobbler.update(self.spotify.get_current_progress());
+            }
         }
     }
 }
@@ -0,0 +1,190 @@
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use librespot_core::cache::Cache;
+use librespot_core::spotify_id::FileId;
+use log::{debug, info, warn};
+
+use crate::command::CachePart;
+use crate::fs::cache_path;
+use crate::library::CACHE_FILES;
+
+/// The directory in the cache directory librespot keeps its files in.
+const LIBRESPOT_DIRECTORY: &str = "librespot";
+
+/// The directory in the librespot directory that keeps the audio files.
+const AUDIO_DIRECTORY: &str = "files";
+
+/// The file in the librespot directory that keeps the credentials.
+const CREDENTIALS_FILE: &str = "credentials.json";
+
+/// The `[cache]` section of the configuration file.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct CacheConfig {
+    /// The most disk space cached audio may take up, in megabytes. The least
+    /// recently played files are removed first. Unlimited if not set.
+    pub audio_limit: Option<u64>,
+}
+
+impl CacheConfig {
+    /// The audio limit in bytes.
+    pub fn audio_limit_bytes(&self) -> Option<u64> {
+        self.audio_limit.map(|megabytes| megabytes * 1024 * 1024)
+    }
+}
+
+/// Open the librespot cache of credentials and audio files, removing the
+/// least recently used audio files if they exceed `audio_limit` bytes.
+pub fn open(audio_limit: Option<u64>) -> Cache {
+    let librespot_cache_path = cache_path(LIBRESPOT_DIRECTORY);
+    let audio_cache_path = librespot_cache_path.join(AUDIO_DIRECTORY);
+    Cache::new(
+        Some(librespot_cache_path),
+        None,
+        Some(audio_cache_path),
+        audio_limit,
+    )
+    .expect("Could not create cache")
+}
+
+fn audio_directory() -> PathBuf {
+    cache_path(LIBRESPOT_DIRECTORY).join(AUDIO_DIRECTORY)
+}
+
+fn credentials_file() -> PathBuf {
+    cache_path(LIBRESPOT_DIRECTORY).join(CREDENTIALS_FILE)
+}
+
+/// The files in `directory` and its subdirectories.
+fn files(directory: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(directory) else {
+        return Vec::new();
+    };
+    let mut found = Vec::new();
+    for path in entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+        if path.is_dir() {
+            found.extend(files(&path));
+        } else {
+            found.push(path);
+        }
+    }
+    found
+}
+
+fn size(path: &Path) -> u64 {
+    fs::metadata(path).map(|m| m.len()).unwrap_or_default()
+}
+
+/// The id of the audio file at `path`, which librespot names after the hex
+/// id with the first two digits as the directory.
+fn file_id(path: &Path) -> Option<FileId> {
+    let directory = path.parent()?.file_name()?.to_str()?;
+    let name = path.file_name()?.to_str()?;
+    let hex = format!("{directory}{name}");
+    if hex.len() != 40 {
+        return None;
+    }
+    let mut id = [0; 20];
+    for (i, byte) in id.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(hex.get(i * 2..i * 2 + 2)?, 16).ok()?;
+    }
+    Some(FileId(id))
+}
+
+/// A size in bytes in a readable unit.
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["kB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+/// How much disk space the parts of the cache take up.
+pub struct CacheUsage {
+    pub audio: u64,
+    pub audio_files: usize,
+    pub library: u64,
+    pub credentials: u64,
+}
+
+impl CacheUsage {
+    pub fn measure() -> Self {
+        let audio_files = files(&audio_directory());
+        Self {
+            audio: audio_files.iter().map(|path| size(path)).sum(),
+            audio_files: audio_files.len(),
+            library: CACHE_FILES.iter().map(|file| size(&cache_path(file))).sum(),
+            credentials: size(&credentials_file()),
+        }
+    }
+
+    pub fn total(&self) -> u64 {
+        self.audio + self.library + self.credentials
+    }
+}
+
+impl fmt::Display for CacheUsage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "audio {} in {} files, library {}, credentials {}",
+            format_size(self.audio),
+            self.audio_files,
+            format_size(self.library),
+            format_size(self.credentials)
+        )
+    }
+}
+
+/// Remove the files of `part` from the cache, returning how many bytes were
+/// freed. Audio files are removed through `cache` if the player is running,
+/// so its record of the cache size stays right. Files that are in use can be
+/// removed safely, they are downloaded or written again when they're needed.
+pub fn clear(part: CachePart, cache: Option<&Cache>) -> u64 {
+    let mut freed = 0;
+    if matches!(part, CachePart::Audio | CachePart::All) {
+        let directory = audio_directory();
+        for path in files(&directory) {
+            let size = size(&path);
+            let removed = match (cache, file_id(&path)) {
+                (Some(cache), Some(id)) => cache.remove_file(id).is_ok(),
+                _ => fs::remove_file(&path)
+                    .map_err(|e| warn!("could not remove {}: {}", path.display(), e))
+                    .is_ok(),
+            };
+            if removed {
+                freed += size;
+            }
+        }
+        // librespot creates the directories again when it needs them
+        if let Ok(entries) = fs::read_dir(&directory) {
+            for entry in entries.flatten() {
+                let _ = fs::remove_dir(entry.path());
+            }
+        }
+        info!("removed {} of cached audio", format_size(freed));
+    }
+
+    let mut remove = |path: PathBuf| {
+        let size = size(&path);
+        match fs::remove_file(&path) {
+            Ok(()) => freed += size,
+            Err(e) => debug!("could not remove {}: {}", path.display(), e),
+        }
+    };
+    if matches!(part, CachePart::Library | CachePart::All) {
+        CACHE_FILES.iter().for_each(|file| remove(cache_path(file)));
+    }
+    if matches!(part, CachePart::Credentials | CachePart::All) {
+        remove(credentials_file());
+    }
+    freed
+}
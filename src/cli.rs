@@ -1,6 +1,18 @@
-use crate::fs::{user_cache_directory, user_configuration_directory};
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+
+use crate::cache::{self, format_size, CacheUsage};
+use crate::command::{CachePart, ExportFormat};
+use crate::config::ConfigValues;
+use crate::export;
+use crate::fs::{cache_path, user_cache_directory, user_configuration_directory};
 #[cfg(unix)]
 use crate::fs::user_runtime_directory;
+use crate::library::{CACHE_PLAYLISTS, CACHE_TRACKS};
+use crate::model::playable::Playable;
+use crate::model::playlist::Playlist;
+use crate::model::track::Track;
 
 /// Print platform info like which platform directories will be used.
 pub fn info() {
@@ -13,4 +25,107 @@ pub fn info() {
     println!("USER_CACHE_PATH {}", user_cache_directory);
     #[cfg(unix)]
     println!("USER_RUNTIME_PATH {}", user_runtime_directory);
+
+    match ConfigValues::load() {
+        Ok(config) => {
+            match config.pcm {
+                Some(pcm) => println!("PCM_OUTPUT {}", pcm),
+                None => println!("PCM_OUTPUT disabled"),
+            }
+            println!("PLAYBACK {}", config.playback.unwrap_or_default());
+        }
+        Err(e) => eprintln!("{e}"),
+    }
+}
+
+/// Print how much disk space the cache takes up, or clear the `clear` part of
+/// it. Clearing is safe while ncspot is running, it writes the files again
+/// when it needs them.
+pub fn cache(clear: Option<&str>) {
+    if let Some(part) = clear.and_then(CachePart::from_name) {
+        let freed = cache::clear(part, None);
+        println!("Cleared {} of the cache, freeing {}", part, format_size(freed));
+        return;
+    }
+
+    let usage = CacheUsage::measure();
+    let limit = ConfigValues::load()
+        .ok()
+        .and_then(|config| config.cache)
+        .and_then(|cache| cache.audio_limit_bytes());
+    println!("CACHE_PATH {}", user_cache_directory().display());
+    println!(
+        "AUDIO {} in {} files, limit {}",
+        format_size(usage.audio),
+        usage.audio_files,
+        limit.map_or("none".to_string(), format_size)
+    );
+    println!("LIBRARY {}", format_size(usage.library));
+    println!("CREDENTIALS {}", format_size(usage.credentials));
+    println!("TOTAL {}", format_size(usage.total()));
+}
+
+/// Read a JSON cache of the library, as written by the TUI.
+fn read_cache<T: DeserializeOwned>(file: &str) -> Result<Vec<T>, String> {
+    let path = cache_path(file);
+    let contents = std::fs::read_to_string(&path).map_err(|e| {
+        format!(
+            "Could not read {}, start ncspot once to cache the library: {}",
+            path.display(),
+            e
+        )
+    })?;
+    serde_json::from_str(&contents)
+        .map_err(|e| format!("Could not parse {}: {}", path.display(), e))
+}
+
+/// Replace characters that aren't allowed in file names.
+fn file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+/// Export the cached saved tracks and playlists to `directory`, one file each.
+/// This doesn't connect to Spotify, so it exports the library as it was last
+/// cached by the TUI.
+pub fn export(directory: &Path, format: &str) -> Result<(), String> {
+    let format = match format {
+        "csv" => ExportFormat::Csv,
+        "json" => ExportFormat::Json,
+        _ => ExportFormat::M3u,
+    };
+    std::fs::create_dir_all(directory)
+        .map_err(|e| format!("Could not create {}: {}", directory.display(), e))?;
+
+    let tracks: Vec<Track> = read_cache(CACHE_TRACKS)?;
+    let playlists: Vec<Playlist> = read_cache(CACHE_PLAYLISTS)?;
+
+    let mut lists = vec![(
+        "Saved tracks".to_string(),
+        tracks.into_iter().map(Playable::Track).collect::<Vec<Playable>>(),
+    )];
+    for playlist in playlists {
+        let name = file_name(&playlist.name);
+        // playlists can share a name, keep them apart by their id
+        let name = if lists.iter().any(|(n, _)| n == &name) {
+            format!("{} ({})", name, playlist.id)
+        } else {
+            name
+        };
+        lists.push((name, playlist.tracks.unwrap_or_default()));
+    }
+
+    for (name, items) in lists {
+        let path = directory.join(format!("{}.{}", name, format.extension()));
+        export::export(&path, format, &items)?;
+        println!("Exported {} items to {}", items.len(), path.display());
+    }
+    Ok(())
 }
@@ -1,5 +1,9 @@
 use crate::queue::RepeatSetting;
+use std::collections::BTreeMap;
 use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::RwLock;
 
 #[derive(Clone, Debug)]
 pub enum TargetMode {
@@ -37,6 +41,146 @@ pub enum SortKey {
     Artist,
     Album,
     Added,
+    Tempo,
+    Key,
+    Energy,
+    Danceability,
+}
+
+/// Steps of importing a list of tracks from a file.
+#[derive(Clone, Debug)]
+pub enum ImportAction {
+    File(String),
+    Queue,
+    Playlist(String),
+}
+
+/// Actions on the queues that are saved on disk.
+#[derive(Clone, Debug)]
+pub enum SnapshotAction {
+    Save(String),
+    Load(String),
+    List,
+}
+
+/// What to do with the key bindings.
+#[derive(Clone, Debug)]
+pub enum KeymapAction {
+    Show,
+    Save,
+}
+
+/// Changes to the equalizer.
+#[derive(Clone, Debug)]
+pub enum EqualizerAction {
+    Show,
+    Preset(String),
+    /// Set the gain of a band, counted from 1.
+    Band(usize, f32),
+    Preamp(f32),
+    Limiter(Option<bool>),
+    Bypass(Option<bool>),
+}
+
+/// The parts of the cache that can be cleared.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CachePart {
+    Audio,
+    Library,
+    Credentials,
+    All,
+}
+
+impl CachePart {
+    pub const NAMES: &'static [&'static str] = &["audio", "library", "credentials", "all"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "audio" => Some(Self::Audio),
+            "library" => Some(Self::Library),
+            "credentials" => Some(Self::Credentials),
+            "all" => Some(Self::All),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for CachePart {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let repr = match self {
+            Self::Audio => "audio",
+            Self::Library => "library",
+            Self::Credentials => "credentials",
+            Self::All => "all",
+        };
+        write!(f, "{repr}")
+    }
+}
+
+/// The audio features that can be shown as columns in track lists.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FeatureColumn {
+    Tempo,
+    Key,
+    Energy,
+    Danceability,
+}
+
+impl FeatureColumn {
+    pub const ALL: [Self; 4] = [Self::Tempo, Self::Key, Self::Energy, Self::Danceability];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "tempo" => Some(Self::Tempo),
+            "key" => Some(Self::Key),
+            "energy" => Some(Self::Energy),
+            "danceability" => Some(Self::Danceability),
+            _ => None,
+        }
+    }
+}
+
+/// Changes to the audio feature columns of track lists.
+#[derive(Clone, Debug)]
+pub enum FeaturesAction {
+    /// Show or hide all columns, or toggle them if no switch is given.
+    Switch(Option<bool>),
+    /// Show or hide a single column.
+    Toggle(FeatureColumn),
+}
+
+/// What to do with the cache.
+#[derive(Clone, Debug)]
+pub enum CacheAction {
+    Show,
+    Clear(CachePart),
+}
+
+/// File formats lists can be exported to.
+#[derive(Clone, Copy, Debug)]
+pub enum ExportFormat {
+    M3u,
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    /// The format matching the extension of `path`, defaulting to M3U.
+    pub fn from_path(path: &str) -> Self {
+        match Path::new(path).extension().and_then(|e| e.to_str()) {
+            Some("csv") => Self::Csv,
+            Some("json") => Self::Json,
+            _ => Self::M3u,
+        }
+    }
+
+    pub fn extension(&self) -> &str {
+        match self {
+            Self::M3u => "m3u",
+            Self::Csv => "csv",
+            Self::Json => "json",
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -64,6 +208,68 @@ pub enum GotoMode {
     Artist,
 }
 
+#[derive(Clone, Debug)]
+pub enum InboxAction {
+    Queue,
+    Dismiss,
+    Clear,
+}
+
+/// Actions on the album whose view is open.
+#[derive(Clone, Debug)]
+pub enum AlbumAction {
+    Save,
+    Queue,
+    Artists,
+}
+
+/// Audio features that recommendations can be tuned on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TuneAttribute {
+    Energy,
+    Tempo,
+    Valence,
+    Danceability,
+    Popularity,
+}
+
+impl TuneAttribute {
+    /// The range of values Spotify accepts for this attribute.
+    pub fn range(&self) -> (f32, f32) {
+        match self {
+            Self::Tempo => (0.0, 300.0),
+            Self::Popularity => (0.0, 100.0),
+            _ => (0.0, 1.0),
+        }
+    }
+}
+
+impl fmt::Display for TuneAttribute {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let repr = match self {
+            Self::Energy => "energy",
+            Self::Tempo => "tempo",
+            Self::Valence => "valence",
+            Self::Danceability => "danceability",
+            Self::Popularity => "popularity",
+        };
+        write!(f, "{repr}")
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TuneBound {
+    Min,
+    Max,
+    Target,
+}
+
+#[derive(Clone, Debug)]
+pub enum RecommendMode {
+    Show,
+    Queue,
+}
+
 #[derive(Clone, Debug)]
 pub enum SeekDirection {
     Relative(i32),
@@ -88,6 +294,7 @@ pub enum Command {
     VolumeDown(u16),
     Repeat(Option<RepeatSetting>),
     Shuffle(Option<bool>),
+    Autoplay(Option<bool>),
     Back,
     Open(TargetMode),
     Goto(GotoMode),
@@ -103,45 +310,38 @@ pub enum Command {
     Redraw,
     Execute(String),
     Reconnect,
+    Inbox(InboxAction),
+    Radio,
+    Seed,
+    Tune(TuneAttribute, Option<(TuneBound, f32)>),
+    Recommend(RecommendMode),
+    Follow,
+    Album(AlbumAction),
+    Info(TargetMode),
+    AudioFeatures(FeaturesAction),
+    Export(String, ExportFormat),
+    Import(ImportAction),
+    Undo,
+    Redo,
+    QueueSnapshot(SnapshotAction),
+    Save(TargetMode),
+    Bind(String, String),
+    Unbind(String),
+    Alias(String, String),
+    Keymap(KeymapAction),
+    Visualizer(Option<bool>),
+    Equalizer(EqualizerAction),
+    Cache(CacheAction),
 }
 
 impl Command {
+    /// The entry of the registry that describes this command.
+    pub fn spec(&self) -> Option<&'static CommandSpec> {
+        COMMANDS.iter().find(|spec| (spec.matches)(self))
+    }
+
     pub fn basename(&self) -> &str {
-        match self {
-            Self::Quit => "quit",
-            Self::TogglePlay => "playpause",
-            Self::Stop => "stop",
-            Self::Previous => "previous",
-            Self::Next => "next",
-            Self::Clear => "clear",
-            Self::Queue => "queue",
-            Self::PlayNext => "playnext",
-            Self::Play => "play",
-            Self::UpdateLibrary => "update",
-            Self::Focus(_) => "focus",
-            Self::Seek(_) => "seek",
-            Self::VolumeUp(_) => "volup",
-            Self::VolumeDown(_) => "voldown",
-            Self::Repeat(_) => "repeat",
-            Self::Shuffle(_) => "shuffle",
-            Self::Back => "back",
-            Self::Open(_) => "open",
-            Self::Goto(_) => "goto",
-            Self::Move(_, _) => "move",
-            Self::Shift(_, _) => "shift",
-            Self::Search(_) => "search",
-            Self::Jump(JumpMode::Previous) => "jumpprevious",
-            Self::Jump(JumpMode::Next) => "jumpnext",
-            Self::Jump(JumpMode::Query(_)) => "jump",
-            Self::Help => "help",
-            Self::Noop => "noop",
-            Self::Sort(_, _) => "sort",
-            Self::Logout => "logout",
-            Self::ShowRecommendations(_) => "similar",
-            Self::Redraw => "redraw",
-            Self::Execute(_) => "exec",
-            Self::Reconnect => "reconnect",
-        }
+        self.spec().map_or("", |spec| spec.names[0])
     }
 }
 
@@ -195,368 +395,1273 @@ impl fmt::Display for CommandParseError {
     }
 }
 
-pub fn parse(input: &str) -> Result<Vec<Command>, CommandParseError> {
-    let mut command_inputs = vec!["".to_string()];
-    let mut command_idx = 0;
-    enum ParseState {
-        Normal,
-        SeparatorEncountered,
+/// User defined names for command strings, see `:alias`.
+static ALIASES: RwLock<BTreeMap<String, String>> = RwLock::new(BTreeMap::new());
+
+/// Define `name` as a command that runs `command`. Aliases can't replace
+/// commands and can only refer to commands, not to other aliases.
+pub fn set_alias(name: &str, command: &str) -> Result<(), CommandParseError> {
+    if find_command(name).is_some() {
+        return Err(CommandParseError::ArgParseError {
+            arg: name.into(),
+            err: "an alias can't replace a command".into(),
+        });
     }
-    let mut parse_state = ParseState::Normal;
-    for c in input.chars() {
-        let is_separator = c == ';';
-        match parse_state {
-            ParseState::Normal if is_separator => parse_state = ParseState::SeparatorEncountered,
-            ParseState::Normal => command_inputs[command_idx].push(c),
-            // ";" is escaped using ";;", so if the previous char already was a ';' push a ';'.
-            ParseState::SeparatorEncountered if is_separator => {
-                command_inputs[command_idx].push(c);
-                parse_state = ParseState::Normal;
-            }
-            ParseState::SeparatorEncountered => {
-                command_idx += 1;
-                command_inputs.push(c.to_string());
-                parse_state = ParseState::Normal;
-            }
+    parse_commands(command, false)?;
+    ALIASES
+        .write()
+        .unwrap()
+        .insert(name.to_string(), command.to_string());
+    Ok(())
+}
+
+/// All aliases with the commands they run, sorted by name.
+pub fn aliases() -> Vec<(String, String)> {
+    ALIASES
+        .read()
+        .unwrap()
+        .iter()
+        .map(|(name, command)| (name.clone(), command.clone()))
+        .collect()
+}
+
+/// The kind of value an argument of a command takes.
+#[derive(Clone, Copy, Debug)]
+pub enum ArgKind {
+    /// One of the listed words.
+    Choice(&'static [&'static str]),
+    /// A number.
+    Number,
+    /// A word. As the last argument of a command it takes the rest of the command.
+    Text,
+    /// The rest of the command as it was typed, with its quotes.
+    Raw,
+}
+
+/// An argument of a command.
+#[derive(Clone, Copy, Debug)]
+pub struct ArgSpec {
+    pub name: &'static str,
+    pub kind: ArgKind,
+    pub optional: bool,
+}
+
+impl ArgSpec {
+    /// How the argument is written in the usage of a command, like `<name>` or `[on|off]`.
+    fn usage(&self, optional: bool) -> String {
+        let usage = match self.kind {
+            ArgKind::Choice(choices) => choices.join("|"),
+            _ => format!("<{}>", self.name),
+        };
+        if optional {
+            format!("[{usage}]")
+        } else {
+            usage
         }
     }
+}
 
-    let mut commands = vec![];
-    for command_input in command_inputs {
-        let components: Vec<_> = command_input.split_whitespace().collect();
-
-        if let Some((command, args)) = components.split_first() {
-            use CommandParseError::*;
-            let command = *command;
-            let command = match command {
-                "quit" | "q" | "x" => Command::Quit,
-                "playpause" | "pause" | "toggleplay" | "toggleplayback" => Command::TogglePlay,
-                "stop" => Command::Stop,
-                "previous" => Command::Previous,
-                "next" => Command::Next,
-                "clear" => Command::Clear,
-                "queue" => Command::Queue,
-                "playnext" => Command::PlayNext,
-                "play" => Command::Play,
-                "update" => Command::UpdateLibrary,
-                "focus" => {
-                    let &target = args.first().ok_or(InsufficientArgs {
-                        cmd: command.into(),
-                        hint: Some("queue|search|library".into()),
-                    })?;
-                    // TODO: this really should be strongly typed
-                    Command::Focus(target.into())
-                }
-                "seek" => {
-                    if args.is_empty() {
-                        return Err(InsufficientArgs {
-                            cmd: command.into(),
-                            hint: Some("a duration".into()),
-                        });
-                    }
-                    let arg = args.join(" ");
-                    let first_char = arg.chars().next();
-                    let duration_raw = match first_char {
-                        Some('+' | '-') => {
-                            arg.chars().skip(1).collect::<String>().trim().into()
-                            // `trim` is necessary here, otherwise `+1000` -> 1 second, but `+ 1000` -> 1000 seconds
-                            // this behaviour is inconsistent and could cause confusion
-                        }
-                        _ => arg,
-                    };
-                    let unsigned_millis = match duration_raw.parse() {
-                        // accept raw milliseconds
-                        Ok(millis) => millis,
-                        Err(_) => parse_duration::parse(&duration_raw) // accept fancy duration
-                            .map_err(|err| ArgParseError {
-                                arg: duration_raw.clone(),
-                                err: err.to_string(),
-                            })
-                            .and_then(|dur| {
-                                dur.as_millis().try_into().map_err(|_| ArgParseError {
-                                    arg: duration_raw.clone(),
-                                    err: "Duration value too large".into(),
-                                })
-                            })?,
-                    };
-                    let seek_direction = match first_char {
-                        // handle i32::MAX < unsigned_millis < u32::MAX gracefully
-                        Some('+') => i32::try_from(unsigned_millis).map(SeekDirection::Relative),
-                        Some('-') => i32::try_from(unsigned_millis)
-                            .map(|millis| SeekDirection::Relative(-millis)),
-                        _ => Ok(SeekDirection::Absolute(unsigned_millis)),
-                    }
-                    .map_err(|_| ArgParseError {
-                        arg: duration_raw,
-                        err: "Duration value too large".into(),
-                    })?;
-                    Command::Seek(seek_direction)
-                }
-                "volup" => {
-                    let amount = match args.first() {
-                        Some(&amount_raw) => {
-                            amount_raw.parse::<u16>().map_err(|err| ArgParseError {
-                                arg: amount_raw.into(),
-                                err: err.to_string(),
-                            })?
-                        }
-                        None => 1,
-                    };
-                    Command::VolumeUp(amount)
-                }
-                "voldown" => {
-                    let amount = match args.first() {
-                        Some(&amount_raw) => {
-                            amount_raw.parse::<u16>().map_err(|err| ArgParseError {
-                                arg: amount_raw.into(),
-                                err: err.to_string(),
-                            })?
-                        }
-                        None => 1,
-                    };
-                    Command::VolumeDown(amount)
-                }
-                "repeat" | "loop" => {
-                    let mode = match args.first().cloned() {
-                        Some("list" | "playlist" | "queue") => {
-                            Ok(Some(RepeatSetting::RepeatPlaylist))
-                        }
-                        Some("track" | "once" | "single") => Ok(Some(RepeatSetting::RepeatTrack)),
-                        Some("none" | "off") => Ok(Some(RepeatSetting::None)),
-                        Some(arg) => Err(BadEnumArg {
-                            arg: arg.into(),
-                            accept: vec![
-                                "list".into(),
-                                "playlist".into(),
-                                "queue".into(),
-                                "track".into(),
-                                "once".into(),
-                                "single".into(),
-                                "none".into(),
-                                "off".into(),
-                            ],
-                            optional: true,
-                        }),
-                        None => Ok(None),
-                    }?;
-                    Command::Repeat(mode)
-                }
-                "shuffle" => {
-                    let switch = match args.first().cloned() {
-                        Some("on") => Ok(Some(true)),
-                        Some("off") => Ok(Some(false)),
-                        Some(arg) => Err(BadEnumArg {
-                            arg: arg.into(),
-                            accept: vec!["on".into(), "off".into()],
-                            optional: true,
-                        }),
-                        None => Ok(None),
-                    }?;
-                    Command::Shuffle(switch)
-                }
-                "back" => Command::Back,
-                "open" => {
-                    let &target_mode_raw = args.first().ok_or(InsufficientArgs {
-                        cmd: command.into(),
-                        hint: Some("selected|current".into()),
-                    })?;
-                    let target_mode = match target_mode_raw {
-                        "selected" => Ok(TargetMode::Selected),
-                        "current" => Ok(TargetMode::Current),
-                        _ => Err(BadEnumArg {
-                            arg: target_mode_raw.into(),
-                            accept: vec!["selected".into(), "current".into()],
-                            optional: false,
-                        }),
-                    }?;
-                    Command::Open(target_mode)
-                }
-                "goto" => {
-                    let &goto_mode_raw = args.first().ok_or(InsufficientArgs {
-                        cmd: command.into(),
-                        hint: Some("album|artist".into()),
-                    })?;
-                    let goto_mode = match goto_mode_raw {
-                        "album" => Ok(GotoMode::Album),
-                        "artist" => Ok(GotoMode::Artist),
-                        _ => Err(BadEnumArg {
-                            arg: goto_mode_raw.into(),
-                            accept: vec!["album".into(), "artist".into()],
-                            optional: false,
-                        }),
-                    }?;
-                    Command::Goto(goto_mode)
-                }
-                "move" => {
-                    let &move_mode_raw = args.first().ok_or(InsufficientArgs {
-                        cmd: command.into(),
-                        hint: Some("a direction".into()),
-                    })?;
-                    let move_mode = {
-                        use MoveMode::*;
-                        match move_mode_raw {
-                            "playing" => Ok(Playing),
-                            "top" | "pageup" | "up" => Ok(Up),
-                            "bottom" | "pagedown" | "down" => Ok(Down),
-                            "leftmost" | "pageleft" | "left" => Ok(Left),
-                            "rightmost" | "pageright" | "right" => Ok(Right),
-                            _ => Err(BadEnumArg {
-                                arg: move_mode_raw.into(),
-                                accept: vec![
-                                    "playing".into(),
-                                    "top".into(),
-                                    "bottom".into(),
-                                    "leftmost".into(),
-                                    "rightmost".into(),
-                                    "pageup".into(),
-                                    "pagedown".into(),
-                                    "pageleft".into(),
-                                    "pageright".into(),
-                                    "up".into(),
-                                    "down".into(),
-                                    "left".into(),
-                                    "right".into(),
-                                ],
-                                optional: false,
-                            }),
-                        }?
-                    };
-                    let move_amount = match move_mode_raw {
-                        "playing" => Ok(MoveAmount::default()),
-                        "top" | "bottom" | "leftmost" | "rightmost" => Ok(MoveAmount::Extreme),
-                        "pageup" | "pagedown" | "pageleft" | "pageright" => {
-                            let amount = match args.get(1) {
-                                Some(&amount_raw) => amount_raw
-                                    .parse::<f32>()
-                                    .map(MoveAmount::Float)
-                                    .map_err(|err| ArgParseError {
-                                        arg: amount_raw.into(),
-                                        err: err.to_string(),
-                                    })?,
-                                None => MoveAmount::default(),
-                            };
-                            Ok(amount)
-                        }
-                        "up" | "down" | "left" | "right" => {
-                            let amount = match args.get(1) {
-                                Some(&amount_raw) => amount_raw
-                                    .parse::<i32>()
-                                    .map(MoveAmount::Integer)
-                                    .map_err(|err| ArgParseError {
-                                        arg: amount_raw.into(),
-                                        err: err.to_string(),
-                                    })?,
-                                None => MoveAmount::default(),
-                            };
-                            Ok(amount)
-                        }
-                        _ => unreachable!(), // already guarded when determining MoveMode
-                    }?;
-                    Command::Move(move_mode, move_amount)
-                }
-                "shift" => {
-                    let &shift_dir_raw = args.first().ok_or(InsufficientArgs {
-                        cmd: command.into(),
-                        hint: Some("up|down".into()),
-                    })?;
-                    let shift_dir = match shift_dir_raw {
-                        "up" => Ok(ShiftMode::Up),
-                        "down" => Ok(ShiftMode::Down),
-                        _ => Err(BadEnumArg {
-                            arg: shift_dir_raw.into(),
-                            accept: vec!["up".into(), "down".into()],
-                            optional: false,
-                        }),
-                    }?;
-                    let amount = match args.get(1) {
-                        Some(&amount_raw) => {
-                            let amount =
-                                amount_raw.parse::<i32>().map_err(|err| ArgParseError {
-                                    arg: amount_raw.into(),
-                                    err: err.to_string(),
-                                })?;
-                            Some(amount)
-                        }
-                        None => None,
-                    };
-                    Command::Shift(shift_dir, amount)
+const fn arg(name: &'static str, kind: ArgKind) -> ArgSpec {
+    ArgSpec {
+        name,
+        kind,
+        optional: false,
+    }
+}
+
+const fn optional(name: &'static str, kind: ArgKind) -> ArgSpec {
+    ArgSpec {
+        name,
+        kind,
+        optional: true,
+    }
+}
+
+const ON_OFF: ArgKind = ArgKind::Choice(&["on", "off"]);
+const TARGET: ArgKind = ArgKind::Choice(&["selected", "current"]);
+
+/// The description of a command in the registry. Parsing, completion, the hints on the command
+/// line and the help are all derived from it.
+pub struct CommandSpec {
+    /// The name of the command, followed by the other names it can be called by.
+    pub names: &'static [&'static str],
+    pub args: &'static [ArgSpec],
+    pub description: &'static str,
+    /// The section of the help the command is listed in.
+    pub section: &'static str,
+    /// The keys that are bound to the command by default, with what they run.
+    pub keys: &'static [(&'static str, &'static str)],
+    /// Build the command from arguments that match `args`.
+    build: fn(&Args) -> Result<Command, CommandParseError>,
+    /// Whether a command was built by this entry.
+    matches: fn(&Command) -> bool,
+}
+
+impl CommandSpec {
+    /// How the command is called, like `volup [<amount>]`.
+    pub fn usage(&self) -> String {
+        let args = self.args.iter().map(|arg| arg.usage(arg.optional));
+        std::iter::once(self.names[0].to_string())
+            .chain(args)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// The usage of the arguments from `index` on. If `required` is set, the argument at `index`
+    /// is shown as required even if it is declared as optional.
+    fn usage_from(&self, index: usize, required: bool) -> Option<String> {
+        let args = self.args.get(index..).filter(|args| !args.is_empty())?;
+        let usage: Vec<String> = args
+            .iter()
+            .enumerate()
+            .map(|(i, arg)| arg.usage(arg.optional && !(required && i == 0)))
+            .collect();
+        Some(usage.join(" "))
+    }
+
+    /// Match the words following the command name to the declared arguments and build the
+    /// command. `name` is the name the command was called by, `source` is the input up to the
+    /// end of the command.
+    fn parse(
+        &'static self,
+        name: &str,
+        words: &[Word],
+        source: &str,
+    ) -> Result<Command, CommandParseError> {
+        use CommandParseError::*;
+        let mut values = Vec::new();
+        for (index, arg) in self.args.iter().enumerate() {
+            let is_last = index + 1 == self.args.len();
+            let value = match (arg.kind, words.get(index)) {
+                (_, None) => None,
+                (ArgKind::Raw, Some(word)) => Some(source[word.start..].replace(";;", ";")),
+                (ArgKind::Text, Some(_)) if is_last => {
+                    let rest: Vec<&str> = words[index..].iter().map(|w| w.value.as_str()).collect();
+                    Some(rest.join(" "))
                 }
-                "search" => Command::Search(args.join(" ")),
-                "jump" => Command::Jump(JumpMode::Query(args.join(" "))),
-                "jumpnext" => Command::Jump(JumpMode::Next),
-                "jumpprevious" => Command::Jump(JumpMode::Previous),
-                "help" => Command::Help,
-                "noop" => Command::Noop,
-                "sort" => {
-                    let &key_raw = args.first().ok_or(InsufficientArgs {
-                        cmd: command.into(),
-                        hint: Some("a sort key".into()),
-                    })?;
-                    let key = match key_raw {
-                        "title" => Ok(SortKey::Title),
-                        "duration" => Ok(SortKey::Duration),
-                        "album" => Ok(SortKey::Album),
-                        "added" => Ok(SortKey::Added),
-                        "artist" => Ok(SortKey::Artist),
-                        _ => Err(BadEnumArg {
-                            arg: key_raw.into(),
-                            accept: vec![
-                                "title".into(),
-                                "duration".into(),
-                                "album".into(),
-                                "added".into(),
-                                "artist".into(),
-                            ],
-                            optional: false,
-                        }),
-                    }?;
-                    let direction = match args.get(1).copied() {
-                        Some("a" | "asc" | "ascending") => Ok(SortDirection::Ascending),
-                        Some("d" | "desc" | "descending") => Ok(SortDirection::Descending),
-                        Some(direction_raw) => Err(BadEnumArg {
-                            arg: direction_raw.into(),
-                            accept: vec![
-                                "a".into(),
-                                "asc".into(),
-                                "ascending".into(),
-                                "d".into(),
-                                "desc".into(),
-                                "descending".into(),
-                            ],
-                            optional: true,
-                        }),
-                        None => Ok(SortDirection::Ascending),
-                    }?;
-                    Command::Sort(key, direction)
+                (_, Some(word)) => Some(word.value.clone()),
+            };
+
+            match (value, arg.kind) {
+                (None, _) if arg.optional => break,
+                (None, _) => {
+                    return Err(InsufficientArgs {
+                        cmd: name.into(),
+                        hint: self.usage_from(index, true),
+                    })
                 }
-                "logout" => Command::Logout,
-                "similar" => {
-                    let &target_mode_raw = args.first().ok_or(InsufficientArgs {
-                        cmd: command.into(),
-                        hint: Some("selected|current".into()),
-                    })?;
-                    let target_mode = match target_mode_raw {
-                        "selected" => Ok(TargetMode::Selected),
-                        "current" => Ok(TargetMode::Current),
-                        _ => Err(BadEnumArg {
-                            arg: target_mode_raw.into(),
-                            accept: vec!["selected".into(), "current".into()],
-                            optional: false,
-                        }),
-                    }?;
-                    Command::ShowRecommendations(target_mode)
+                (Some(value), ArgKind::Choice(choices)) if !choices.contains(&value.as_str()) => {
+                    return Err(BadEnumArg {
+                        arg: value,
+                        accept: choices.iter().map(|choice| choice.to_string()).collect(),
+                        optional: arg.optional,
+                    })
                 }
-                "redraw" => Command::Redraw,
-                "exec" => Command::Execute(args.join(" ")),
-                "reconnect" => Command::Reconnect,
-                _ => {
-                    return Err(NoSuchCommand {
-                        cmd: command.into(),
+                (Some(value), ArgKind::Number) if value.parse::<f64>().is_err() => {
+                    return Err(ArgParseError {
+                        arg: value,
+                        err: "not a number".into(),
                     })
                 }
+                (Some(value), _) => values.push(value),
+            }
+        }
+
+        (self.build)(&Args {
+            name,
+            spec: self,
+            values,
+        })
+    }
+}
+
+/// The arguments a command was called with, in the order they are declared in its
+/// [CommandSpec].
+pub struct Args<'a> {
+    name: &'a str,
+    spec: &'static CommandSpec,
+    values: Vec<String>,
+}
+
+impl Args<'_> {
+    /// The argument at `index`, if it was given.
+    fn get(&self, index: usize) -> Option<&str> {
+        self.values.get(index).map(String::as_str)
+    }
+
+    /// The argument at `index`, which is required even if it is declared as optional.
+    fn require(&self, index: usize) -> Result<&str, CommandParseError> {
+        self.get(index)
+            .ok_or_else(|| CommandParseError::InsufficientArgs {
+                cmd: self.name.into(),
+                hint: self.spec.usage_from(index, true),
+            })
+    }
+
+    /// The argument at `index` parsed as a `T`, if it was given.
+    fn parse<T>(&self, index: usize) -> Result<Option<T>, CommandParseError>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        self.get(index)
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|err: T::Err| CommandParseError::ArgParseError {
+                        arg: value.into(),
+                        err: err.to_string(),
+                    })
+            })
+            .transpose()
+    }
+}
+
+fn parse_seek(args: &Args) -> Result<Command, CommandParseError> {
+    use CommandParseError::*;
+    let arg = args.require(0)?;
+    let first_char = arg.chars().next();
+    let duration_raw: String = match first_char {
+        Some('+' | '-') => {
+            arg.chars().skip(1).collect::<String>().trim().into()
+            // `trim` is necessary here, otherwise `+1000` -> 1 second, but `+ 1000` -> 1000 seconds
+            // this behaviour is inconsistent and could cause confusion
+        }
+        _ => arg.into(),
+    };
+    let unsigned_millis = match duration_raw.parse() {
+        // accept raw milliseconds
+        Ok(millis) => millis,
+        Err(_) => parse_duration::parse(&duration_raw) // accept fancy duration
+            .map_err(|err| ArgParseError {
+                arg: duration_raw.clone(),
+                err: err.to_string(),
+            })
+            .and_then(|dur| {
+                dur.as_millis().try_into().map_err(|_| ArgParseError {
+                    arg: duration_raw.clone(),
+                    err: "Duration value too large".into(),
+                })
+            })?,
+    };
+    let seek_direction = match first_char {
+        // handle i32::MAX < unsigned_millis < u32::MAX gracefully
+        Some('+') => i32::try_from(unsigned_millis).map(SeekDirection::Relative),
+        Some('-') => i32::try_from(unsigned_millis).map(|millis| SeekDirection::Relative(-millis)),
+        _ => Ok(SeekDirection::Absolute(unsigned_millis)),
+    }
+    .map_err(|_| ArgParseError {
+        arg: duration_raw,
+        err: "Duration value too large".into(),
+    })?;
+    Ok(Command::Seek(seek_direction))
+}
+
+fn parse_move(args: &Args) -> Result<Command, CommandParseError> {
+    let direction = args.require(0)?;
+    let mode = match direction {
+        "playing" => MoveMode::Playing,
+        "top" | "pageup" | "up" => MoveMode::Up,
+        "bottom" | "pagedown" | "down" => MoveMode::Down,
+        "leftmost" | "pageleft" | "left" => MoveMode::Left,
+        _ => MoveMode::Right,
+    };
+    let amount = match direction {
+        "playing" => None,
+        "top" | "bottom" | "leftmost" | "rightmost" => Some(MoveAmount::Extreme),
+        "pageup" | "pagedown" | "pageleft" | "pageright" => args.parse(1)?.map(MoveAmount::Float),
+        _ => args.parse(1)?.map(MoveAmount::Integer),
+    };
+    Ok(Command::Move(mode, amount.unwrap_or_default()))
+}
+
+fn parse_sort(args: &Args) -> Result<Command, CommandParseError> {
+    let key = match args.require(0)? {
+        "title" => SortKey::Title,
+        "duration" => SortKey::Duration,
+        "album" => SortKey::Album,
+        "added" => SortKey::Added,
+        "artist" => SortKey::Artist,
+        "tempo" => SortKey::Tempo,
+        "key" => SortKey::Key,
+        "energy" => SortKey::Energy,
+        _ => SortKey::Danceability,
+    };
+    let direction = match args.get(1) {
+        Some("d" | "desc" | "descending") => SortDirection::Descending,
+        _ => SortDirection::Ascending,
+    };
+    Ok(Command::Sort(key, direction))
+}
+
+fn parse_tune(args: &Args) -> Result<Command, CommandParseError> {
+    let attribute = match args.require(0)? {
+        "energy" => TuneAttribute::Energy,
+        "tempo" => TuneAttribute::Tempo,
+        "valence" => TuneAttribute::Valence,
+        "danceability" => TuneAttribute::Danceability,
+        _ => TuneAttribute::Popularity,
+    };
+    let bound = match args.require(1)? {
+        "min" => TuneBound::Min,
+        "max" => TuneBound::Max,
+        "target" => TuneBound::Target,
+        _ => return Ok(Command::Tune(attribute, None)),
+    };
+    let value_raw = args.require(2)?;
+    let value: f32 = args.parse(2)?.unwrap_or_default();
+    let (min, max) = attribute.range();
+    if !(min..=max).contains(&value) {
+        return Err(CommandParseError::ArgParseError {
+            arg: value_raw.into(),
+            err: format!("{attribute} must be between {min} and {max}"),
+        });
+    }
+    Ok(Command::Tune(attribute, Some((bound, value))))
+}
+
+fn parse_equalizer(args: &Args) -> Result<Command, CommandParseError> {
+    let switch = |index| match args.get(index) {
+        None => Ok(None),
+        Some("on") => Ok(Some(true)),
+        Some("off") => Ok(Some(false)),
+        Some(value) => Err(CommandParseError::BadEnumArg {
+            arg: value.into(),
+            accept: vec!["on".into(), "off".into()],
+            optional: true,
+        }),
+    };
+    let action = match args.get(0) {
+        None => EqualizerAction::Show,
+        Some("preset") => EqualizerAction::Preset(args.require(1)?.into()),
+        Some("band") => {
+            args.require(2)?;
+            let band: usize = args.parse(1)?.unwrap_or_default();
+            EqualizerAction::Band(band, args.parse(2)?.unwrap_or_default())
+        }
+        Some("preamp") => {
+            args.require(1)?;
+            EqualizerAction::Preamp(args.parse(1)?.unwrap_or_default())
+        }
+        Some("limiter") => EqualizerAction::Limiter(switch(1)?),
+        Some(_) => EqualizerAction::Bypass(switch(1)?),
+    };
+    Ok(Command::Equalizer(action))
+}
+
+/// Turn an optional `on|off` argument into a switch.
+fn switch(args: &Args) -> Option<bool> {
+    args.get(0).map(|value| value == "on")
+}
+
+/// Turn a `selected|current` argument into a target.
+fn target(args: &Args) -> TargetMode {
+    match args.get(0) {
+        Some("current") => TargetMode::Current,
+        _ => TargetMode::Selected,
+    }
+}
+
+/// All commands that can be entered on the command line or bound to keys.
+pub static COMMANDS: &[CommandSpec] = &[
+    // Playback control
+    CommandSpec {
+        names: &["playpause", "pause", "toggleplay", "toggleplayback"],
+        args: &[],
+        description: "play or pause",
+        section: "Playback control",
+        keys: &[("P", "playpause")],
+        build: |_| Ok(Command::TogglePlay),
+        matches: |cmd| matches!(cmd, Command::TogglePlay),
+    },
+    CommandSpec {
+        names: &["stop"],
+        args: &[],
+        description: "stop playback",
+        section: "Playback control",
+        keys: &[("S", "stop")],
+        build: |_| Ok(Command::Stop),
+        matches: |cmd| matches!(cmd, Command::Stop),
+    },
+    CommandSpec {
+        names: &["next"],
+        args: &[],
+        description: "play the next song",
+        section: "Playback control",
+        keys: &[(">", "next")],
+        build: |_| Ok(Command::Next),
+        matches: |cmd| matches!(cmd, Command::Next),
+    },
+    CommandSpec {
+        names: &["previous"],
+        args: &[],
+        description: "play the previous song",
+        section: "Playback control",
+        keys: &[("<", "previous")],
+        build: |_| Ok(Command::Previous),
+        matches: |cmd| matches!(cmd, Command::Previous),
+    },
+    CommandSpec {
+        names: &["seek"],
+        args: &[arg("duration", ArgKind::Text)],
+        description: "seek to a position, or relative to the current one with + or -",
+        section: "Playback control",
+        keys: &[
+            ("f", "seek +1000"),
+            ("b", "seek -1000"),
+            ("F", "seek +10000"),
+            ("B", "seek -10000"),
+        ],
+        build: parse_seek,
+        matches: |cmd| matches!(cmd, Command::Seek(_)),
+    },
+    CommandSpec {
+        names: &["repeat", "loop"],
+        args: &[optional(
+            "mode",
+            ArgKind::Choice(&[
+                "list", "playlist", "queue", "track", "once", "single", "none", "off",
+            ]),
+        )],
+        description: "set the repeat mode, or switch to the next one",
+        section: "Playback control",
+        keys: &[("r", "repeat")],
+        build: |args| {
+            let mode = args.get(0).map(|mode| match mode {
+                "list" | "playlist" | "queue" => RepeatSetting::RepeatPlaylist,
+                "track" | "once" | "single" => RepeatSetting::RepeatTrack,
+                _ => RepeatSetting::None,
+            });
+            Ok(Command::Repeat(mode))
+        },
+        matches: |cmd| matches!(cmd, Command::Repeat(_)),
+    },
+    CommandSpec {
+        names: &["shuffle"],
+        args: &[optional("switch", ON_OFF)],
+        description: "turn shuffling on or off, or toggle it",
+        section: "Playback control",
+        keys: &[("z", "shuffle")],
+        build: |args| Ok(Command::Shuffle(switch(args))),
+        matches: |cmd| matches!(cmd, Command::Shuffle(_)),
+    },
+    CommandSpec {
+        names: &["autoplay"],
+        args: &[optional("switch", ON_OFF)],
+        description: "turn autoplay of similar songs on or off, or toggle it",
+        section: "Playback control",
+        keys: &[("R", "autoplay")],
+        build: |args| Ok(Command::Autoplay(switch(args))),
+        matches: |cmd| matches!(cmd, Command::Autoplay(_)),
+    },
+    CommandSpec {
+        names: &["eq", "equalizer"],
+        args: &[
+            optional(
+                "action",
+                ArgKind::Choice(&["preset", "band", "preamp", "limiter", "bypass"]),
+            ),
+            optional("value", ArgKind::Text),
+            optional("gain", ArgKind::Number),
+        ],
+        description: "show the equalizer, or change its preset, bands, preamp, limiter or bypass",
+        section: "Playback control",
+        keys: &[],
+        build: parse_equalizer,
+        matches: |cmd| matches!(cmd, Command::Equalizer(_)),
+    },
+    CommandSpec {
+        names: &["reconnect"],
+        args: &[],
+        description: "reconnect to Spotify",
+        section: "Playback control",
+        keys: &[],
+        build: |_| Ok(Command::Reconnect),
+        matches: |cmd| matches!(cmd, Command::Reconnect),
+    },
+    // Volume control
+    CommandSpec {
+        names: &["volup"],
+        args: &[optional("amount", ArgKind::Number)],
+        description: "increase the volume by <amount> percent",
+        section: "Volume control",
+        keys: &[("+", "volup 1"), ("]", "volup 5")],
+        build: |args| Ok(Command::VolumeUp(args.parse(0)?.unwrap_or(1))),
+        matches: |cmd| matches!(cmd, Command::VolumeUp(_)),
+    },
+    CommandSpec {
+        names: &["voldown"],
+        args: &[optional("amount", ArgKind::Number)],
+        description: "decrease the volume by <amount> percent",
+        section: "Volume control",
+        keys: &[("-", "voldown 1"), ("[", "voldown 5")],
+        build: |args| Ok(Command::VolumeDown(args.parse(0)?.unwrap_or(1))),
+        matches: |cmd| matches!(cmd, Command::VolumeDown(_)),
+    },
+    // Navigation
+    CommandSpec {
+        names: &["move"],
+        args: &[
+            arg(
+                "direction",
+                ArgKind::Choice(&[
+                    "playing",
+                    "top",
+                    "bottom",
+                    "leftmost",
+                    "rightmost",
+                    "pageup",
+                    "pagedown",
+                    "pageleft",
+                    "pageright",
+                    "up",
+                    "down",
+                    "left",
+                    "right",
+                ]),
+            ),
+            optional("amount", ArgKind::Number),
+        ],
+        description: "move the selection, page moves are in screen heights",
+        section: "Navigation",
+        keys: &[
+            ("Up", "move up"),
+            ("Down", "move down"),
+            ("Left", "move left"),
+            ("Right", "move right"),
+            ("k", "move up"),
+            ("j", "move down"),
+            ("h", "move left"),
+            ("l", "move right"),
+            ("Ctrl+p", "move up"),
+            ("Ctrl+n", "move down"),
+            ("Ctrl+a", "move left"),
+            ("Ctrl+e", "move right"),
+            ("PageUp", "move up 5"),
+            ("PageDown", "move down 5"),
+            ("Home", "move top"),
+            ("End", "move bottom"),
+            ("p", "move playing"),
+        ],
+        build: parse_move,
+        matches: |cmd| matches!(cmd, Command::Move(_, _)),
+    },
+    CommandSpec {
+        names: &["focus"],
+        args: &[arg(
+            "screen",
+            ArgKind::Choice(&["queue", "search", "library"]),
+        )],
+        description: "show a screen",
+        section: "Navigation",
+        keys: &[
+            ("F1", "focus queue"),
+            ("F2", "focus search"),
+            ("F3", "focus library"),
+        ],
+        build: |args| Ok(Command::Focus(args.require(0)?.into())),
+        matches: |cmd| matches!(cmd, Command::Focus(_)),
+    },
+    CommandSpec {
+        names: &["back"],
+        args: &[],
+        description: "go back to the previous view",
+        section: "Navigation",
+        keys: &[("Backspace", "back")],
+        build: |_| Ok(Command::Back),
+        matches: |cmd| matches!(cmd, Command::Back),
+    },
+    CommandSpec {
+        names: &["open"],
+        args: &[arg("target", TARGET)],
+        description: "show the context menu of the selection or the playing song",
+        section: "Navigation",
+        keys: &[("o", "open selected"), ("O", "open current")],
+        build: |args| Ok(Command::Open(target(args))),
+        matches: |cmd| matches!(cmd, Command::Open(_)),
+    },
+    CommandSpec {
+        names: &["goto"],
+        args: &[arg("view", ArgKind::Choice(&["album", "artist"]))],
+        description: "show the album or artist of the selection",
+        section: "Navigation",
+        keys: &[("a", "goto album"), ("A", "goto artist")],
+        build: |args| match args.require(0)? {
+            "album" => Ok(Command::Goto(GotoMode::Album)),
+            _ => Ok(Command::Goto(GotoMode::Artist)),
+        },
+        matches: |cmd| matches!(cmd, Command::Goto(_)),
+    },
+    // Display control
+    CommandSpec {
+        names: &["redraw"],
+        args: &[],
+        description: "redraw the screen",
+        section: "Display control",
+        keys: &[("Ctrl+l", "redraw")],
+        build: |_| Ok(Command::Redraw),
+        matches: |cmd| matches!(cmd, Command::Redraw),
+    },
+    CommandSpec {
+        names: &["help"],
+        args: &[],
+        description: "show this help",
+        section: "Display control",
+        keys: &[("?", "help")],
+        build: |_| Ok(Command::Help),
+        matches: |cmd| matches!(cmd, Command::Help),
+    },
+    CommandSpec {
+        names: &["info"],
+        args: &[optional("target", TARGET)],
+        description: "show information about the selection or the playing song",
+        section: "Display control",
+        keys: &[("i", "info selected"), ("I", "info current")],
+        build: |args| Ok(Command::Info(target(args))),
+        matches: |cmd| matches!(cmd, Command::Info(_)),
+    },
+    CommandSpec {
+        names: &["visualizer"],
+        args: &[optional("switch", ON_OFF)],
+        description: "show a spectrum of the playing audio in the progress bar, or toggle it",
+        section: "Display control",
+        keys: &[("V", "visualizer")],
+        build: |args| Ok(Command::Visualizer(switch(args))),
+        matches: |cmd| matches!(cmd, Command::Visualizer(_)),
+    },
+    CommandSpec {
+        names: &["features"],
+        args: &[optional(
+            "column",
+            ArgKind::Choice(&["on", "off", "tempo", "key", "energy", "danceability"]),
+        )],
+        description: "show the audio features of songs as columns, or toggle one or all of them",
+        section: "Display control",
+        keys: &[],
+        build: |args| {
+            let action = match args.get(0).and_then(FeatureColumn::from_name) {
+                Some(column) => FeaturesAction::Toggle(column),
+                None => FeaturesAction::Switch(switch(args)),
+            };
+            Ok(Command::AudioFeatures(action))
+        },
+        matches: |cmd| matches!(cmd, Command::AudioFeatures(_)),
+    },
+    CommandSpec {
+        names: &["sort"],
+        args: &[
+            arg(
+                "key",
+                ArgKind::Choice(&[
+                    "title",
+                    "duration",
+                    "album",
+                    "added",
+                    "artist",
+                    "tempo",
+                    "key",
+                    "energy",
+                    "danceability",
+                ]),
+            ),
+            optional(
+                "direction",
+                ArgKind::Choice(&["a", "asc", "ascending", "d", "desc", "descending"]),
+            ),
+        ],
+        description: "sort the list",
+        section: "Display control",
+        keys: &[],
+        build: parse_sort,
+        matches: |cmd| matches!(cmd, Command::Sort(_, _)),
+    },
+    // Library actions
+    CommandSpec {
+        names: &["play"],
+        args: &[],
+        description: "play the selection",
+        section: "Library actions",
+        keys: &[("Enter", "play")],
+        build: |_| Ok(Command::Play),
+        matches: |cmd| matches!(cmd, Command::Play),
+    },
+    CommandSpec {
+        names: &["playnext"],
+        args: &[],
+        description: "play the selection after the playing song",
+        section: "Library actions",
+        keys: &[(".", "playnext; move down")],
+        build: |_| Ok(Command::PlayNext),
+        matches: |cmd| matches!(cmd, Command::PlayNext),
+    },
+    CommandSpec {
+        names: &["queue"],
+        args: &[
+            optional("action", ArgKind::Choice(&["save", "load", "list"])),
+            optional("name", ArgKind::Text),
+        ],
+        description: "add the selection to the queue, or save, load or list whole queues",
+        section: "Library actions",
+        keys: &[("Space", "queue; move down")],
+        build: |args| {
+            let action = match args.get(0) {
+                None => return Ok(Command::Queue),
+                Some("save") => SnapshotAction::Save(args.require(1)?.into()),
+                Some("load") => SnapshotAction::Load(args.require(1)?.into()),
+                Some(_) => SnapshotAction::List,
+            };
+            Ok(Command::QueueSnapshot(action))
+        },
+        matches: |cmd| matches!(cmd, Command::Queue | Command::QueueSnapshot(_)),
+    },
+    CommandSpec {
+        names: &["save"],
+        args: &[optional("target", TARGET)],
+        description: "save the selection or the playing song to the library, or remove it",
+        section: "Library actions",
+        keys: &[("s", "save")],
+        build: |args| Ok(Command::Save(target(args))),
+        matches: |cmd| matches!(cmd, Command::Save(_)),
+    },
+    CommandSpec {
+        names: &["similar"],
+        args: &[arg("target", TARGET)],
+        description: "show songs similar to the selection or the playing song",
+        section: "Library actions",
+        keys: &[("m", "similar selected"), ("M", "similar current")],
+        build: |args| Ok(Command::ShowRecommendations(target(args))),
+        matches: |cmd| matches!(cmd, Command::ShowRecommendations(_)),
+    },
+    CommandSpec {
+        names: &["album"],
+        args: &[arg(
+            "action",
+            ArgKind::Choice(&["save", "queue", "artists"]),
+        )],
+        description: "save or queue the album that is shown, or show its artists",
+        section: "Library actions",
+        keys: &[],
+        build: |args| match args.require(0)? {
+            "save" => Ok(Command::Album(AlbumAction::Save)),
+            "queue" => Ok(Command::Album(AlbumAction::Queue)),
+            _ => Ok(Command::Album(AlbumAction::Artists)),
+        },
+        matches: |cmd| matches!(cmd, Command::Album(_)),
+    },
+    CommandSpec {
+        names: &["follow"],
+        args: &[],
+        description: "follow the selected artist or playlist, or unfollow it",
+        section: "Library actions",
+        keys: &[],
+        build: |_| Ok(Command::Follow),
+        matches: |cmd| matches!(cmd, Command::Follow),
+    },
+    CommandSpec {
+        names: &["radio"],
+        args: &[],
+        description: "start a radio based on the selection",
+        section: "Library actions",
+        keys: &[],
+        build: |_| Ok(Command::Radio),
+        matches: |cmd| matches!(cmd, Command::Radio),
+    },
+    CommandSpec {
+        names: &["seed"],
+        args: &[],
+        description: "use the selection as a seed for recommendations",
+        section: "Library actions",
+        keys: &[],
+        build: |_| Ok(Command::Seed),
+        matches: |cmd| matches!(cmd, Command::Seed),
+    },
+    CommandSpec {
+        names: &["tune"],
+        args: &[
+            arg(
+                "attribute",
+                ArgKind::Choice(&["energy", "tempo", "valence", "danceability", "popularity"]),
+            ),
+            arg("bound", ArgKind::Choice(&["min", "max", "target", "clear"])),
+            optional("value", ArgKind::Number),
+        ],
+        description: "tune the recommendations on an audio feature",
+        section: "Library actions",
+        keys: &[],
+        build: parse_tune,
+        matches: |cmd| matches!(cmd, Command::Tune(_, _)),
+    },
+    CommandSpec {
+        names: &["recommend"],
+        args: &[optional("action", ArgKind::Choice(&["queue"]))],
+        description: "show the recommendations, or queue them",
+        section: "Library actions",
+        keys: &[],
+        build: |args| match args.get(0) {
+            Some(_) => Ok(Command::Recommend(RecommendMode::Queue)),
+            None => Ok(Command::Recommend(RecommendMode::Show)),
+        },
+        matches: |cmd| matches!(cmd, Command::Recommend(_)),
+    },
+    CommandSpec {
+        names: &["inbox"],
+        args: &[arg(
+            "action",
+            ArgKind::Choice(&["queue", "dismiss", "clear"]),
+        )],
+        description: "queue the new episodes, dismiss the selected one or clear them all",
+        section: "Library actions",
+        keys: &[],
+        build: |args| match args.require(0)? {
+            "queue" => Ok(Command::Inbox(InboxAction::Queue)),
+            "dismiss" => Ok(Command::Inbox(InboxAction::Dismiss)),
+            _ => Ok(Command::Inbox(InboxAction::Clear)),
+        },
+        matches: |cmd| matches!(cmd, Command::Inbox(_)),
+    },
+    CommandSpec {
+        names: &["export"],
+        args: &[
+            arg("path", ArgKind::Text),
+            optional("format", ArgKind::Choice(&["m3u", "csv", "json"])),
+        ],
+        description: "write the list to a file",
+        section: "Library actions",
+        keys: &[],
+        build: |args| {
+            let path = args.require(0)?;
+            let format = match args.get(1) {
+                Some("m3u") => ExportFormat::M3u,
+                Some("csv") => ExportFormat::Csv,
+                Some(_) => ExportFormat::Json,
+                None => ExportFormat::from_path(path),
+            };
+            Ok(Command::Export(path.into(), format))
+        },
+        matches: |cmd| matches!(cmd, Command::Export(_, _)),
+    },
+    CommandSpec {
+        names: &["import"],
+        args: &[
+            arg("path|queue|playlist", ArgKind::Text),
+            optional("name", ArgKind::Text),
+        ],
+        description: "find the songs listed in a file, then queue them or make a playlist",
+        section: "Library actions",
+        keys: &[],
+        build: |args| {
+            let action = match args.require(0)? {
+                "queue" => ImportAction::Queue,
+                "playlist" => ImportAction::Playlist(args.require(1)?.into()),
+                path => match args.get(1) {
+                    Some(rest) => ImportAction::File(format!("{path} {rest}")),
+                    None => ImportAction::File(path.into()),
+                },
+            };
+            Ok(Command::Import(action))
+        },
+        matches: |cmd| matches!(cmd, Command::Import(_)),
+    },
+    CommandSpec {
+        names: &["update"],
+        args: &[],
+        description: "update the library",
+        section: "Library actions",
+        keys: &[("U", "update")],
+        build: |_| Ok(Command::UpdateLibrary),
+        matches: |cmd| matches!(cmd, Command::UpdateLibrary),
+    },
+    // Queue actions
+    CommandSpec {
+        names: &["clear"],
+        args: &[],
+        description: "clear the queue",
+        section: "Queue actions",
+        keys: &[("c", "clear")],
+        build: |_| Ok(Command::Clear),
+        matches: |cmd| matches!(cmd, Command::Clear),
+    },
+    CommandSpec {
+        names: &["shift"],
+        args: &[
+            arg("direction", ArgKind::Choice(&["up", "down"])),
+            optional("amount", ArgKind::Number),
+        ],
+        description: "move the selected song up or down",
+        section: "Queue actions",
+        keys: &[("Shift+Up", "shift up"), ("Shift+Down", "shift down")],
+        build: |args| {
+            let mode = match args.require(0)? {
+                "up" => ShiftMode::Up,
+                _ => ShiftMode::Down,
             };
-            commands.push(command);
+            Ok(Command::Shift(mode, args.parse(1)?))
+        },
+        matches: |cmd| matches!(cmd, Command::Shift(_, _)),
+    },
+    CommandSpec {
+        names: &["undo"],
+        args: &[],
+        description: "undo the last change",
+        section: "Queue actions",
+        keys: &[("u", "undo")],
+        build: |_| Ok(Command::Undo),
+        matches: |cmd| matches!(cmd, Command::Undo),
+    },
+    CommandSpec {
+        names: &["redo"],
+        args: &[],
+        description: "redo the last undone change",
+        section: "Queue actions",
+        keys: &[("Ctrl+r", "redo")],
+        build: |_| Ok(Command::Redo),
+        matches: |cmd| matches!(cmd, Command::Redo),
+    },
+    // Search actions
+    CommandSpec {
+        names: &["search"],
+        args: &[optional("query", ArgKind::Text)],
+        description: "search Spotify",
+        section: "Search actions",
+        keys: &[],
+        build: |args| Ok(Command::Search(args.get(0).unwrap_or_default().into())),
+        matches: |cmd| matches!(cmd, Command::Search(_)),
+    },
+    CommandSpec {
+        names: &["jump"],
+        args: &[optional("query", ArgKind::Text)],
+        description: "jump to the first match in the list",
+        section: "Search actions",
+        keys: &[],
+        build: |args| {
+            let query = args.get(0).unwrap_or_default().into();
+            Ok(Command::Jump(JumpMode::Query(query)))
+        },
+        matches: |cmd| matches!(cmd, Command::Jump(JumpMode::Query(_))),
+    },
+    CommandSpec {
+        names: &["jumpnext"],
+        args: &[],
+        description: "jump to the next match",
+        section: "Search actions",
+        keys: &[("n", "jumpnext")],
+        build: |_| Ok(Command::Jump(JumpMode::Next)),
+        matches: |cmd| matches!(cmd, Command::Jump(JumpMode::Next)),
+    },
+    CommandSpec {
+        names: &["jumpprevious"],
+        args: &[],
+        description: "jump to the previous match",
+        section: "Search actions",
+        keys: &[("N", "jumpprevious")],
+        build: |_| Ok(Command::Jump(JumpMode::Previous)),
+        matches: |cmd| matches!(cmd, Command::Jump(JumpMode::Previous)),
+    },
+    // Key bindings
+    CommandSpec {
+        names: &["bind"],
+        args: &[arg("key", ArgKind::Text), arg("command", ArgKind::Raw)],
+        description: "bind a key to a command",
+        section: "Key bindings",
+        keys: &[],
+        build: |args| {
+            Ok(Command::Bind(
+                args.require(0)?.into(),
+                args.require(1)?.into(),
+            ))
+        },
+        matches: |cmd| matches!(cmd, Command::Bind(_, _)),
+    },
+    CommandSpec {
+        names: &["unbind"],
+        args: &[arg("key", ArgKind::Text)],
+        description: "remove the binding of a key",
+        section: "Key bindings",
+        keys: &[],
+        build: |args| Ok(Command::Unbind(args.require(0)?.into())),
+        matches: |cmd| matches!(cmd, Command::Unbind(_)),
+    },
+    CommandSpec {
+        names: &["alias"],
+        args: &[arg("name", ArgKind::Text), arg("command", ArgKind::Raw)],
+        description: "add a command called <name> that runs <command>",
+        section: "Key bindings",
+        keys: &[],
+        build: |args| {
+            Ok(Command::Alias(
+                args.require(0)?.into(),
+                args.require(1)?.into(),
+            ))
+        },
+        matches: |cmd| matches!(cmd, Command::Alias(_, _)),
+    },
+    CommandSpec {
+        names: &["keymap"],
+        args: &[optional("action", ArgKind::Choice(&["save"]))],
+        description: "show the key bindings, or keep them and the aliases for the next start",
+        section: "Key bindings",
+        keys: &[],
+        build: |args| match args.get(0) {
+            Some(_) => Ok(Command::Keymap(KeymapAction::Save)),
+            None => Ok(Command::Keymap(KeymapAction::Show)),
+        },
+        matches: |cmd| matches!(cmd, Command::Keymap(_)),
+    },
+    // Other
+    CommandSpec {
+        names: &["exec"],
+        args: &[arg("command", ArgKind::Raw)],
+        description: "run a shell command",
+        section: "Other",
+        keys: &[],
+        build: |args| Ok(Command::Execute(args.require(0)?.into())),
+        matches: |cmd| matches!(cmd, Command::Execute(_)),
+    },
+    CommandSpec {
+        names: &["noop"],
+        args: &[],
+        description: "do nothing",
+        section: "Other",
+        keys: &[],
+        build: |_| Ok(Command::Noop),
+        matches: |cmd| matches!(cmd, Command::Noop),
+    },
+    CommandSpec {
+        names: &["cache"],
+        args: &[
+            optional("action", ArgKind::Choice(&["clear"])),
+            optional("part", ArgKind::Choice(CachePart::NAMES)),
+        ],
+        description: "show how much disk space the cache takes up, or clear a part of it",
+        section: "Other",
+        keys: &[],
+        build: |args| match args.get(0) {
+            None => Ok(Command::Cache(CacheAction::Show)),
+            Some(_) => {
+                let part = CachePart::from_name(args.require(1)?).unwrap_or(CachePart::All);
+                Ok(Command::Cache(CacheAction::Clear(part)))
+            }
+        },
+        matches: |cmd| matches!(cmd, Command::Cache(_)),
+    },
+    CommandSpec {
+        names: &["logout"],
+        args: &[],
+        description: "forget the credentials and quit",
+        section: "Other",
+        keys: &[],
+        build: |_| Ok(Command::Logout),
+        matches: |cmd| matches!(cmd, Command::Logout),
+    },
+    CommandSpec {
+        names: &["quit", "q", "x"],
+        args: &[],
+        description: "quit",
+        section: "Other",
+        keys: &[("q", "quit")],
+        build: |_| Ok(Command::Quit),
+        matches: |cmd| matches!(cmd, Command::Quit),
+    },
+];
+
+/// The entry of the registry for the command called `name`.
+pub fn find_command(name: &str) -> Option<&'static CommandSpec> {
+    COMMANDS.iter().find(|spec| spec.names.contains(&name))
+}
+
+/// A word of a command, with the byte range of `input` it was read from.
+struct Word {
+    value: String,
+    start: usize,
+    end: usize,
+}
+
+/// Split `input` into commands and the commands into words. Commands are separated by `;`,
+/// which is written as `;;` inside a command. Words are separated by whitespace, which can be
+/// included in a word by quoting it with `"` or `'` or by escaping it with `\`. Quotes only
+/// start quoting at the start of a word and if they are closed, other quotes are kept as they
+/// are. Returns the words of every command with the offset the command ends at.
+fn split(input: &str) -> Vec<(Vec<Word>, usize)> {
+    let mut commands = Vec::new();
+    let mut words = Vec::new();
+    let mut word: Option<Word> = None;
+    let mut quote: Option<char> = None;
+
+    let mut chars = input.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let mut end = i + c.len_utf8();
+        let character = match (quote, c) {
+            (Some(q), c) if c == q => {
+                quote = None;
+                None
+            }
+            (Some('"') | None, '\\') => match chars.next() {
+                Some((j, escaped)) => {
+                    end = j + escaped.len_utf8();
+                    Some(escaped)
+                }
+                None => Some(c),
+            },
+            (Some(_), c) => Some(c),
+            (None, '"' | '\'') if word.is_none() && is_closed(&input[end..], c) => {
+                quote = Some(c);
+                None
+            }
+            (None, ';') if matches!(chars.peek(), Some((_, ';'))) => {
+                chars.next();
+                end += 1;
+                Some(';')
+            }
+            (None, c) if c == ';' || c.is_whitespace() => {
+                words.extend(word.take());
+                if c == ';' {
+                    commands.push((std::mem::take(&mut words), i));
+                }
+                continue;
+            }
+            (None, c) => Some(c),
+        };
+
+        let word = word.get_or_insert_with(|| Word {
+            value: String::new(),
+            start: i,
+            end,
+        });
+        word.value.extend(character);
+        word.end = end;
+    }
+    words.extend(word);
+    commands.push((words, input.len()));
+    commands
+}
+
+pub fn parse(input: &str) -> Result<Vec<Command>, CommandParseError> {
+    parse_commands(input, true)
+}
+
+fn parse_commands(input: &str, expand_aliases: bool) -> Result<Vec<Command>, CommandParseError> {
+    let mut commands = vec![];
+    for (words, end) in split(input) {
+        let Some((name, args)) = words.split_first() else {
+            continue;
         };
+
+        if let Some(spec) = find_command(&name.value) {
+            commands.push(spec.parse(&name.value, args, &input[..end])?);
+            continue;
+        }
+
+        let alias = ALIASES.read().unwrap().get(&name.value).cloned();
+        match alias {
+            Some(alias) if expand_aliases => {
+                let rest = args.first().map_or("", |arg| &input[arg.start..end]);
+                commands.extend(parse_commands(&format!("{alias} {rest}"), false)?);
+            }
+            _ => {
+                return Err(CommandParseError::NoSuchCommand {
+                    cmd: name.value.clone(),
+                })
+            }
+        }
     }
     Ok(commands)
 }
+
+/// Whether `rest` contains the `quote` that closes a quote opened right before it.
+fn is_closed(rest: &str, quote: char) -> bool {
+    let mut chars = rest.chars();
+    while let Some(c) = chars.next() {
+        if c == quote {
+            return true;
+        } else if c == '\\' && quote == '"' {
+            chars.next();
+        }
+    }
+    false
+}
+
+/// The words of the last command of `input` before the word that is being typed, that word and
+/// the offset it starts at.
+fn split_last_word(input: &str) -> (Vec<String>, String, usize) {
+    let mut words = split(input)
+        .pop()
+        .map(|(words, _)| words)
+        .unwrap_or_default();
+    let current = match words.last() {
+        Some(word) if word.end == input.len() => words.pop(),
+        _ => None,
+    };
+    let words = words.into_iter().map(|word| word.value).collect();
+    match current {
+        Some(word) => (words, word.value, word.start),
+        None => (words, String::new(), input.len()),
+    }
+}
+
+/// The values that are accepted for the argument at `index` of the command called `name`, if it
+/// is a choice.
+fn accepted_values(name: &str, index: usize) -> &'static [&'static str] {
+    match find_command(name).and_then(|spec| spec.args.get(index)) {
+        Some(ArgSpec {
+            kind: ArgKind::Choice(choices),
+            ..
+        }) => choices,
+        _ => &[],
+    }
+}
+
+/// Complete the word at the end of `input`. Returns the byte offset the word starts at and the
+/// possible completions, which are command names for the first word and the accepted values of
+/// choices for the others.
+pub fn complete(input: &str) -> (usize, Vec<String>) {
+    let (words, current, start) = split_last_word(input);
+    let candidates: Vec<String> = match words.split_first() {
+        None => COMMANDS
+            .iter()
+            .map(|spec| spec.names[0].to_string())
+            .chain(aliases().into_iter().map(|(name, _)| name))
+            .collect(),
+        Some((name, args)) => accepted_values(name, args.len())
+            .iter()
+            .map(|value| value.to_string())
+            .collect(),
+    };
+
+    let completions = candidates
+        .into_iter()
+        .filter(|candidate| candidate.starts_with(&current))
+        .collect();
+    (start, completions)
+}
+
+/// A hint about what is still missing from the command that is being typed in `input`.
+pub fn hint(input: &str) -> Option<String> {
+    let (words, current, _) = split_last_word(input);
+    let Some((name, args)) = words.split_first() else {
+        let (_, names) = complete(input);
+        let complete = names.is_empty() || names.contains(&current);
+        return (!current.is_empty() && !complete).then(|| names.join(" "));
+    };
+
+    if current.is_empty() {
+        return find_command(name)?.usage_from(args.len(), false);
+    }
+
+    let values = accepted_values(name, args.len());
+    if values.is_empty() || values.contains(&current.as_str()) {
+        return None;
+    }
+    let matching: Vec<&str> = values
+        .iter()
+        .copied()
+        .filter(|value| value.starts_with(&current))
+        .collect();
+    Some(
+        if matching.is_empty() {
+            values
+        } else {
+            &matching
+        }
+        .join("|"),
+    )
+}
@@ -1,28 +1,43 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 
 use crate::application::send_command;
+use crate::cache::{self, format_size, CacheUsage};
 use crate::command::{
-    Command, GotoMode, JumpMode, MoveAmount, MoveMode, SeekDirection, ShiftMode, TargetMode,
+    self, CacheAction, CachePart, Command, EqualizerAction, FeatureColumn, FeaturesAction,
+    ImportAction, InboxAction, KeymapAction, RecommendMode, SeekDirection, SnapshotAction,
+    TargetMode,
 };
 use crate::events::EventManager;
 use crate::ext_traits::CursiveExt;
-use crate::fs::cache_path;
+use crate::fs::{cache_path, config_path};
+use crate::keymap::{default_bindings, escape_command, key_name, parse_key, KEYMAP_FILE};
 use crate::library::Library;
+use crate::model::playable::Playable;
+use crate::model::snapshot::QueueSnapshot;
 use crate::queue::{Queue, RepeatSetting};
-use crate::spotify::{Spotify, VOLUME_PERCENT};
+use crate::spotify::{PlayerEvent, Spotify};
 use crate::traits::{IntoBoxedViewExt, ViewExt};
 use crate::ui::contextmenu::ContextMenu;
+use crate::ui::equalizer::{frequency_label, EqualizerView};
 use crate::ui::help::HelpView;
 use crate::ui::layout::Layout;
+use crate::ui::import::ImportView;
+use crate::ui::info::info_dialog;
+use crate::ui::keymap::KeymapView;
 use crate::ui::modal::Modal;
+use crate::ui::recommendations::RecommendationsView;
 use crate::ui::search_results::SearchResultsView;
+use crate::ui::snapshots::SnapshotsView;
 
-use cursive::event::{Event, Key};
+use cursive::event::Event;
 use cursive::traits::View;
 use cursive::views::Dialog;
 use cursive::Cursive;
-use log::{debug, info};
+use log::{error, info};
 
 pub enum CommandResult {
     Consumed(Option<String>),
@@ -36,6 +51,8 @@ pub struct CommandManager {
     queue: Arc<Queue>,
     library: Arc<Library>,
     events: EventManager,
+    /// The command every bound key runs, by the name of the key.
+    bindings: RefCell<BTreeMap<String, String>>,
 }
 
 impl CommandManager {
@@ -50,6 +67,7 @@ impl CommandManager {
             queue,
             library,
             events,
+            bindings: RefCell::new(BTreeMap::new()),
         }
     }
 
@@ -96,6 +114,34 @@ impl CommandManager {
                 s.add_layer(Modal::new(confirmation));
                 Ok(None)
             }
+            Command::Undo => match self.queue.undo() {
+                true => Ok(None),
+                false => Err("Nothing to undo".into()),
+            },
+            Command::Redo => match self.queue.redo() {
+                true => Ok(None),
+                false => Err("Nothing to redo".into()),
+            },
+            Command::QueueSnapshot(SnapshotAction::Save(name)) => {
+                self.queue.snapshot(name).save()?;
+                Ok(Some(format!("Saved queue \"{name}\"")))
+            }
+            Command::QueueSnapshot(SnapshotAction::Load(name)) => {
+                let snapshot = QueueSnapshot::load(name)?;
+                let playing = matches!(
+                    self.spotify.get_current_status(),
+                    PlayerEvent::Playing(_)
+                );
+                self.queue.restore_snapshot(&snapshot, playing);
+                Ok(Some(format!("Loaded queue \"{name}\"")))
+            }
+            Command::QueueSnapshot(SnapshotAction::List) => {
+                let view = SnapshotsView::new(self.queue.clone(), self.library.clone());
+                s.call_on_name("main", move |v: &mut Layout| {
+                    v.push_view(Box::new(view));
+                });
+                Ok(None)
+            }
             Command::UpdateLibrary => {
                 self.library.update_library();
                 Ok(None)
@@ -109,6 +155,112 @@ impl CommandManager {
                 self.queue.set_shuffle(mode);
                 Ok(None)
             }
+            Command::Info(TargetMode::Current) => {
+                if let Some(playable) = self.queue.get_current() {
+                    s.add_layer(info_dialog(&self.spotify.api, &playable));
+                }
+                Ok(None)
+            }
+            Command::Save(TargetMode::Current) => {
+                let playable = self.queue.get_current().ok_or("Nothing is playing")?;
+                let was_saved = self.library.is_saved_track(&playable);
+                let mut item = playable.as_listitem();
+                item.save(&self.library);
+                let title = item.display_left(&self.library);
+                if was_saved {
+                    Ok(Some(format!("Removed \"{title}\" from the library")))
+                } else {
+                    Ok(Some(format!("Saved \"{title}\"")))
+                }
+            }
+            Command::Import(ImportAction::File(path)) => {
+                let view = ImportView::new(self.queue.clone(), self.library.clone(), path)?;
+                s.call_on_name("main", move |v: &mut Layout| {
+                    v.push_view(Box::new(view));
+                });
+                Ok(None)
+            }
+            Command::AudioFeatures(action) => {
+                let mut columns = self.library.audio_feature_columns.write().unwrap();
+                match action {
+                    FeaturesAction::Switch(show) => {
+                        *columns = if show.unwrap_or(columns.is_empty()) {
+                            FeatureColumn::ALL.to_vec()
+                        } else {
+                            Vec::new()
+                        };
+                    }
+                    FeaturesAction::Toggle(column) => {
+                        match columns.iter().position(|c| c == column) {
+                            Some(index) => {
+                                columns.remove(index);
+                            }
+                            None => {
+                                columns.push(*column);
+                                columns.sort();
+                            }
+                        }
+                    }
+                }
+                if !columns.is_empty() {
+                    let library = self.library.clone();
+                    std::thread::spawn(move || {
+                        let tracks = library.tracks.read().unwrap().clone();
+                        library.fetch_audio_features(&tracks);
+                    });
+                }
+                Ok(None)
+            }
+            Command::Visualizer(mode) => {
+                let visualizer = self.spotify.visualizer();
+                visualizer.set_enabled(mode.unwrap_or_else(|| !visualizer.is_enabled()));
+                Ok(None)
+            }
+            Command::Equalizer(EqualizerAction::Show) => {
+                let view = Box::new(EqualizerView::new(self.spotify.equalizer().clone()));
+                s.call_on_name("main", move |v: &mut Layout| v.push_view(view));
+                Ok(None)
+            }
+            Command::Equalizer(action) => {
+                let message = self.spotify.equalizer().update(|settings| match action {
+                    EqualizerAction::Show => Ok(String::new()),
+                    EqualizerAction::Preset(name) => {
+                        settings.apply_preset(name)?;
+                        Ok(format!("Equalizer preset \"{name}\""))
+                    }
+                    EqualizerAction::Band(band, gain) => {
+                        let index = band.checked_sub(1).ok_or("Bands are counted from 1")?;
+                        settings.set_gain(index, *gain)?;
+                        let band = &settings.bands[index];
+                        Ok(format!(
+                            "Band {} ({}) set to {:+.1} dB",
+                            index + 1,
+                            frequency_label(band.frequency),
+                            band.gain
+                        ))
+                    }
+                    EqualizerAction::Preamp(gain) => {
+                        settings.set_preamp(*gain);
+                        Ok(format!("Preamp set to {:+.1} dB", settings.preamp))
+                    }
+                    EqualizerAction::Limiter(mode) => {
+                        settings.limiter = mode.unwrap_or(!settings.limiter);
+                        let state = if settings.limiter { "on" } else { "off" };
+                        Ok(format!("Limiter {state}"))
+                    }
+                    EqualizerAction::Bypass(mode) => {
+                        settings.bypass = mode.unwrap_or(!settings.bypass);
+                        let state = if settings.bypass { "bypassed" } else { "on" };
+                        Ok(format!("Equalizer {state}"))
+                    }
+                })?;
+                Ok(Some(message))
+            }
+            Command::Autoplay(mode) => {
+                let mode = mode.unwrap_or_else(|| !self.queue.get_radio());
+                self.queue.set_radio(mode);
+                Ok(None)
+            }
             Command::Repeat(mode) => {
                 let mode = mode.unwrap_or_else(|| match self.queue.get_repeat() {
                     RepeatSetting::None => RepeatSetting::RepeatPlaylist,
@@ -127,24 +279,15 @@ impl CommandManager {
                 Ok(None)
             }
             Command::VolumeUp(amount) => {
-                let volume = self
-                    .spotify
-                    .volume()
-                    .saturating_add(VOLUME_PERCENT * amount);
-                self.spotify.set_volume(volume);
+                self.spotify.change_volume(*amount as i32);
                 Ok(None)
             }
             Command::VolumeDown(amount) => {
-                let volume = self
-                    .spotify
-                    .volume()
-                    .saturating_sub(VOLUME_PERCENT * amount);
-                debug!("vol {}", volume);
-                self.spotify.set_volume(volume);
+                self.spotify.change_volume(-(*amount as i32));
                 Ok(None)
             }
             Command::Help => {
-                let view = Box::new(HelpView::new());
+                let view = Box::new(HelpView::new(&self.bindings.borrow()));
                 s.call_on_name("main", move |v: &mut Layout| v.push_view(view));
                 Ok(None)
             }
@@ -167,6 +310,27 @@ impl CommandManager {
                 });
                 Ok(None)
             }
+            Command::Cache(CacheAction::Show) => {
+                let usage = CacheUsage::measure();
+                Ok(Some(format!(
+                    "Cache: {}, {} in total",
+                    usage,
+                    format_size(usage.total())
+                )))
+            }
+            Command::Cache(CacheAction::Clear(part)) => {
+                let freed = cache::clear(*part, Some(self.spotify.cache()));
+                let mut message =
+                    format!("Cleared {} of the cache, freeing {}", part, format_size(freed));
+                if matches!(part, CachePart::Library | CachePart::All) {
+                    // fetch the library again so its cache is written anew
+                    self.library.update_library();
+                }
+                if matches!(part, CachePart::Credentials | CachePart::All) {
+                    message.push_str(", log in again on the next start");
+                }
+                Ok(Some(message))
+            }
             Command::Logout => {
                 self.spotify.shutdown();
 
@@ -188,6 +352,68 @@ impl CommandManager {
                 self.spotify.shutdown();
                 Ok(None)
             }
+            Command::Inbox(InboxAction::Queue) => {
+                let episodes = self.library.inbox_oldest_first();
+                for episode in episodes.iter() {
+                    self.queue.append(Playable::Episode(episode.clone()));
+                }
+                Ok(Some(format!("Queued {} episodes", episodes.len())))
+            }
+            Command::Inbox(InboxAction::Clear) => {
+                self.library.mark_inbox_checked();
+                Ok(None)
+            }
+
+            Command::Bind(key, command) => {
+                self.bind(s, key, command)?;
+                Ok(Some(format!("Bound {key} to \"{command}\"")))
+            }
+            Command::Unbind(key) => {
+                self.unbind(s, key)?;
+                Ok(Some(format!("Unbound {key}")))
+            }
+            Command::Alias(name, command) => {
+                command::set_alias(name, command).map_err(|e| e.to_string())?;
+                Ok(None)
+            }
+            Command::Keymap(KeymapAction::Show) => {
+                let bindings = self
+                    .bindings
+                    .borrow()
+                    .iter()
+                    .map(|(key, command)| (key.clone(), command.clone()))
+                    .collect();
+                let view = Box::new(KeymapView::new(bindings));
+                s.call_on_name("main", move |v: &mut Layout| v.push_view(view));
+                Ok(None)
+            }
+            Command::Keymap(KeymapAction::Save) => {
+                let path = self.save_keymap()?;
+                Ok(Some(format!("Saved key bindings to {}", path.display())))
+            }
+
+            Command::Tune(attribute, tuning) => {
+                self.library.tune_recommendations(*attribute, *tuning)?;
+                Ok(None)
+            }
+            Command::Recommend(RecommendMode::Show) => {
+                let view = Box::new(RecommendationsView::new(
+                    self.queue.clone(),
+                    self.library.clone(),
+                ));
+                s.call_on_name("main", move |v: &mut Layout| v.push_view(view));
+                Ok(None)
+            }
+            Command::Recommend(RecommendMode::Queue) => {
+                let tracks = self
+                    .library
+                    .recommendations()
+                    .ok_or("No recommendations found")?;
+                for track in tracks.iter() {
+                    self.queue.append(Playable::Track(track.clone()));
+                }
+                Ok(Some(format!("Queued {} tracks", tracks.len())))
+            }
 
             Command::Queue
             | Command::PlayNext
@@ -200,7 +426,16 @@ impl CommandManager {
             | Command::Shift(_, _)
             | Command::Jump(_)
             | Command::ShowRecommendations(_)
-            | Command::Sort(_, _) => Err(format!(
+            | Command::Sort(_, _)
+            | Command::Inbox(InboxAction::Dismiss)
+            | Command::Radio
+            | Command::Seed
+            | Command::Follow
+            | Command::Save(TargetMode::Selected)
+            | Command::Album(_)
+            | Command::Info(TargetMode::Selected)
+            | Command::Export(_, _)
+            | Command::Import(ImportAction::Queue | ImportAction::Playlist(_)) => Err(format!(
                 "The command \"{}\" is unsupported in this view",
                 cmd.basename()
             )),
@@ -241,71 +476,96 @@ impl CommandManager {
         s.on_event(Event::Refresh);
     }
 
-    pub fn register_keybindings(&self, cursive: &mut Cursive) {
-        cursive.add_global_callback(Event::Char('q'), move |siv| send_command(siv, Command::Quit));
-
-        cursive.add_global_callback(Event::CtrlChar('l'), move |siv| send_command(siv, Command::Redraw));
-        cursive.add_global_callback(Event::Char('P'), move |siv| send_command(siv, Command::TogglePlay));
-        cursive.add_global_callback(Event::Char('U'), move |siv| send_command(siv, Command::UpdateLibrary));
-        cursive.add_global_callback(Event::Char('S'), move |siv| send_command(siv, Command::Stop));
-        cursive.add_global_callback(Event::Char('<'), move |siv| send_command(siv, Command::Previous));
-        cursive.add_global_callback(Event::Char('>'), move |siv| send_command(siv, Command::Next));
-        cursive.add_global_callback(Event::Char('c'), move |siv| send_command(siv, Command::Clear));
+    /// Bind `key` to run `command`, replacing what it was bound to before.
+    fn bind(&self, cursive: &mut Cursive, key: &str, command: &str) -> Result<(), String> {
+        let event = parse_key(key)?;
+        let commands = command::parse(command).map_err(|e| e.to_string())?;
 
-        cursive.add_global_callback(Event::Char(' '), move |siv| send_command(siv, Command::Queue));
-        cursive.add_global_callback(Event::Char(' '), move |siv| send_command(siv, Command::Move(MoveMode::Down, Default::default())));
-        cursive.add_global_callback(Event::Char('.'), move |siv| send_command(siv, Command::PlayNext));
-        cursive.add_global_callback(Event::Char('.'), move |siv| send_command(siv, Command::Move(MoveMode::Down, Default::default())));
+        cursive.clear_global_callbacks(event.clone());
+        cursive.add_global_callback(event.clone(), move |siv| {
+            for cmd in commands.iter() {
+                send_command(siv, cmd.clone());
+            }
+        });
+        self.bindings
+            .borrow_mut()
+            .insert(key_name(&event), command.to_string());
+        Ok(())
+    }
 
-        cursive.add_global_callback(Event::Key(Key::Enter), move |siv| send_command(siv, Command::Play));
-        cursive.add_global_callback(Event::Char('n'), move |siv| send_command(siv, Command::Jump(JumpMode::Next)));
-        cursive.add_global_callback(Event::Char('N'), move |siv| send_command(siv, Command::Jump(JumpMode::Previous)));
-        cursive.add_global_callback(Event::Char('f'), move |siv| send_command(siv, Command::Seek(SeekDirection::Relative(1000))));
-        cursive.add_global_callback(Event::Char('b'), move |siv| send_command(siv, Command::Seek(SeekDirection::Relative(-1000))));
-        cursive.add_global_callback(Event::Char('F'), move |siv| send_command(siv, Command::Seek(SeekDirection::Relative(10000))));
-        cursive.add_global_callback(Event::Char('B'), move |siv| send_command(siv, Command::Seek(SeekDirection::Relative(-10000))));
-        cursive.add_global_callback(Event::Char('+'), move |siv| send_command(siv, Command::VolumeUp(1)));
-        cursive.add_global_callback(Event::Char(']'), move |siv| send_command(siv, Command::VolumeUp(5)));
-        cursive.add_global_callback(Event::Char('-'), move |siv| send_command(siv, Command::VolumeDown(1)));
-        cursive.add_global_callback(Event::Char('['), move |siv| send_command(siv, Command::VolumeDown(5)));
+    /// Remove the binding of `key`.
+    fn unbind(&self, cursive: &mut Cursive, key: &str) -> Result<(), String> {
+        let event = parse_key(key)?;
+        if self.bindings.borrow_mut().remove(&key_name(&event)).is_none() {
+            return Err(format!("\"{key}\" isn't bound"));
+        }
+        cursive.clear_global_callbacks(event);
+        Ok(())
+    }
 
-        cursive.add_global_callback(Event::Char('r'), move |siv| send_command(siv, Command::Repeat(None)));
-        cursive.add_global_callback(Event::Char('z'), move |siv| send_command(siv, Command::Shuffle(None)));
+    /// Write the aliases and bindings to the keymap file, so that they are
+    /// restored at the next start. Default bindings that were removed are
+    /// written as `unbind`.
+    fn save_keymap(&self) -> Result<PathBuf, String> {
+        let mut lines = vec!["# Every line is a command that is run at startup.".to_string()];
+        for (name, command) in command::aliases() {
+            lines.push(format!("alias {} {}", name, escape_command(&command)));
+        }
 
-        cursive.add_global_callback(Event::Key(Key::F1), move |siv| send_command(siv, Command::Focus("queue".into())));
-        cursive.add_global_callback(Event::Key(Key::F2), move |siv| send_command(siv, Command::Focus("search".into())));
-        cursive.add_global_callback(Event::Key(Key::F3), move |siv| send_command(siv, Command::Focus("library".into())));
-        cursive.add_global_callback(Event::Char('?'), move |siv| send_command(siv, Command::Help));
-        cursive.add_global_callback(Event::Key(Key::Backspace), move |siv| send_command(siv, Command::Back));
+        let bindings = self.bindings.borrow();
+        for (key, _) in default_bindings() {
+            let name = parse_key(key).map(|event| key_name(&event));
+            if let Some(name) = name.ok().filter(|name| !bindings.contains_key(name)) {
+                lines.push(format!("unbind {name}"));
+            }
+        }
+        for (key, command) in bindings.iter() {
+            lines.push(format!("bind {} {}", key, escape_command(command)));
+        }
 
-        cursive.add_global_callback(Event::Char('o'), move |siv| send_command(siv, Command::Open(TargetMode::Selected)));
-        cursive.add_global_callback(Event::Char('O'), move |siv| send_command(siv, Command::Open(TargetMode::Current)));
-        cursive.add_global_callback(Event::Char('a'), move |siv| send_command(siv, Command::Goto(GotoMode::Album)));
-        cursive.add_global_callback(Event::Char('A'), move |siv| send_command(siv, Command::Goto(GotoMode::Artist)));
+        let path = config_path(KEYMAP_FILE);
+        std::fs::write(&path, lines.join("\n") + "\n")
+            .map_err(|e| format!("Could not write {}: {}", path.display(), e))?;
+        Ok(path)
+    }
 
-        cursive.add_global_callback(Event::Char('m'), move |siv| send_command(siv, Command::ShowRecommendations(TargetMode::Selected)));
-        cursive.add_global_callback(Event::Char('M'), move |siv| send_command(siv, Command::ShowRecommendations(TargetMode::Current)));
+    /// Run the `bind`, `unbind` and `alias` commands in the keymap file.
+    fn load_keymap(&self, cursive: &mut Cursive) {
+        let path = config_path(KEYMAP_FILE);
+        let Ok(contents) = std::fs::read_to_string(&path) else {
+            return;
+        };
 
-        cursive.add_global_callback(Event::Key(Key::Up), move |siv| send_command(siv, Command::Move(MoveMode::Up, Default::default())));
-        cursive.add_global_callback(Event::Char('p'), move |siv| send_command(siv, Command::Move(MoveMode::Playing, Default::default())));
-        cursive.add_global_callback(Event::Key(Key::Down), move |siv| send_command(siv, Command::Move(MoveMode::Down, Default::default())));
-        cursive.add_global_callback(Event::Key(Key::Left), move |siv| send_command(siv, Command::Move(MoveMode::Left, Default::default())));
-        cursive.add_global_callback(Event::Key(Key::Right), move |siv| send_command(siv, Command::Move(MoveMode::Right, Default::default())));
-        cursive.add_global_callback(Event::Key(Key::PageUp), move |siv| send_command(siv, Command::Move(MoveMode::Up, MoveAmount::Integer(5))));
-        cursive.add_global_callback(Event::Key(Key::PageDown), move |siv| send_command(siv, Command::Move(MoveMode::Down, MoveAmount::Integer(5))));
-        cursive.add_global_callback(Event::Key(Key::Home), move |siv| send_command(siv, Command::Move(MoveMode::Up, MoveAmount::Extreme)));
-        cursive.add_global_callback(Event::Key(Key::End), move |siv| send_command(siv, Command::Move(MoveMode::Down, MoveAmount::Extreme)));
-        cursive.add_global_callback(Event::Char('k'), move |siv| send_command(siv, Command::Move(MoveMode::Up, Default::default())));
-        cursive.add_global_callback(Event::Char('j'), move |siv| send_command(siv, Command::Move(MoveMode::Down, Default::default())));
-        cursive.add_global_callback(Event::Char('h'), move |siv| send_command(siv, Command::Move(MoveMode::Left, Default::default())));
-        cursive.add_global_callback(Event::Char('l'), move |siv| send_command(siv, Command::Move(MoveMode::Right, Default::default())));
+        let lines = contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'));
+        for line in lines {
+            let result = command::parse(line)
+                .map_err(|e| e.to_string())
+                .and_then(|commands| {
+                    commands.iter().try_for_each(|cmd| match cmd {
+                        Command::Bind(key, command) => self.bind(cursive, key, command),
+                        Command::Unbind(key) => self.unbind(cursive, key),
+                        Command::Alias(name, command) => {
+                            command::set_alias(name, command).map_err(|e| e.to_string())
+                        }
+                        _ => Err(format!("\"{}\" can't be used here", cmd.basename())),
+                    })
+                });
+            if let Err(e) = result {
+                error!("{}: {}: {}", path.display(), line, e);
+            }
+        }
+    }
 
-        cursive.add_global_callback(Event::CtrlChar('p'), move |siv| send_command(siv, Command::Move(MoveMode::Up, Default::default())));
-        cursive.add_global_callback(Event::CtrlChar('n'), move |siv| send_command(siv, Command::Move(MoveMode::Down, Default::default())));
-        cursive.add_global_callback(Event::CtrlChar('a'), move |siv| send_command(siv, Command::Move(MoveMode::Left, Default::default())));
-        cursive.add_global_callback(Event::CtrlChar('e'), move |siv| send_command(siv, Command::Move(MoveMode::Right, Default::default())));
+    pub fn register_keybindings(&self, cursive: &mut Cursive) {
+        for (key, command) in default_bindings() {
+            if let Err(e) = self.bind(cursive, key, command) {
+                error!("invalid default binding {}: {}", key, e);
+            }
+        }
 
-        cursive.add_global_callback(Event::Shift(Key::Up), move |siv| send_command(siv, Command::Shift(ShiftMode::Up, None)));
-        cursive.add_global_callback(Event::Shift(Key::Down), move |siv| send_command(siv, Command::Shift(ShiftMode::Down, None)));
+        self.load_keymap(cursive);
     }
 }
@@ -0,0 +1,37 @@
+use std::fs;
+
+use crate::cache::CacheConfig;
+use crate::fs::config_path;
+use crate::hooks::HookConfig;
+use crate::pcm::PcmConfig;
+use crate::playback::PlaybackConfig;
+use crate::scrobbler::ListenBrainzConfig;
+use crate::ui::cover::CoverConfig;
+
+/// The name of the configuration file in the configuration directory.
+pub const CONFIGURATION_FILE_NAME: &str = "config.toml";
+
+/// The settings that can be changed in the configuration file. Everything is
+/// optional, missing settings keep their default.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct ConfigValues {
+    pub hooks: Option<HookConfig>,
+    pub listenbrainz: Option<ListenBrainzConfig>,
+    pub cover: Option<CoverConfig>,
+    pub pcm: Option<PcmConfig>,
+    pub playback: Option<PlaybackConfig>,
+    pub cache: Option<CacheConfig>,
+}
+
+impl ConfigValues {
+    /// Read the configuration file. A missing file gives the defaults, a file
+    /// that can't be parsed is an error.
+    pub fn load() -> Result<Self, String> {
+        let path = config_path(CONFIGURATION_FILE_NAME);
+        match fs::read_to_string(&path) {
+            Ok(contents) => toml::from_str(&contents)
+                .map_err(|e| format!("Could not parse {}: {}", path.display(), e)),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+}
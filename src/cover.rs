@@ -0,0 +1,143 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io::Read;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use image::imageops::FilterType;
+use image::RgbImage;
+use log::{debug, error};
+
+use crate::application::ASYNC_RUNTIME;
+use crate::events::EventManager;
+use crate::fs::cache_path;
+
+/// The covers that are downloaded or loaded from the cache directory. Set up
+/// once when the application starts.
+pub static COVERS: OnceLock<Covers> = OnceLock::new();
+
+/// The directory in the cache directory that keeps the downloaded covers.
+const COVER_DIRECTORY: &str = "covers";
+
+/// Covers are downscaled to this size when they are loaded, which is enough
+/// for the few cells they take up in the terminal.
+const MAX_COVER_SIZE: u32 = 300;
+
+/// The most covers kept in memory at once.
+const MAX_LOADED_COVERS: usize = 64;
+
+/// The largest image that is downloaded.
+const MAX_DOWNLOAD_SIZE: u64 = 10 * 1024 * 1024;
+
+enum CoverState {
+    Loading,
+    Loaded(Arc<RgbImage>),
+    Failed,
+}
+
+/// Loads cover images by their URL in the background, keeping them on disk so
+/// they only have to be downloaded once.
+pub struct Covers {
+    events: EventManager,
+    covers: Mutex<HashMap<String, CoverState>>,
+}
+
+impl Covers {
+    pub fn init(events: EventManager) {
+        let covers = Self {
+            events,
+            covers: Mutex::new(HashMap::new()),
+        };
+        if COVERS.set(covers).is_err() {
+            error!("covers were already set up");
+        }
+    }
+
+    /// The cover at `url`, or `None` while it is loading or if it couldn't be
+    /// loaded. Loading it is started on first use and the screen is redrawn
+    /// when it's done.
+    pub fn get(url: &str) -> Option<Arc<RgbImage>> {
+        let this = COVERS.get()?;
+        let mut covers = this.covers.lock().unwrap();
+        match covers.get(url) {
+            Some(CoverState::Loaded(image)) => return Some(image.clone()),
+            Some(CoverState::Loading | CoverState::Failed) => return None,
+            None => {}
+        }
+
+        if covers.len() >= MAX_LOADED_COVERS {
+            covers.retain(|_, state| matches!(state, CoverState::Loading));
+        }
+        covers.insert(url.to_string(), CoverState::Loading);
+
+        let url = url.to_string();
+        ASYNC_RUNTIME.get().unwrap().spawn_blocking(move || {
+            let state = match load(&url) {
+                Ok(image) => CoverState::Loaded(Arc::new(image)),
+                Err(e) => {
+                    error!("could not load cover {}: {}", url, e);
+                    CoverState::Failed
+                }
+            };
+            this.covers.lock().unwrap().insert(url, state);
+            this.events.trigger();
+        });
+        None
+    }
+}
+
+/// The file in the cache directory for the cover at `url`.
+fn cache_file(url: &str) -> PathBuf {
+    // Spotify's image URLs end in a unique id, which is more readable than a
+    // hash of the URL.
+    let name = url
+        .rsplit('/')
+        .next()
+        .filter(|name| !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric()))
+        .map(String::from)
+        .unwrap_or_else(|| {
+            let mut hasher = DefaultHasher::new();
+            url.hash(&mut hasher);
+            format!("{:016x}", hasher.finish())
+        });
+
+    let mut path = cache_path(COVER_DIRECTORY);
+    if let Err(e) = fs::create_dir_all(&path) {
+        error!("could not create {}: {}", path.display(), e);
+    }
+    path.push(name);
+    path
+}
+
+/// Read the cover at `url` from the cache directory, or download it there.
+fn load(url: &str) -> Result<RgbImage, String> {
+    let path = cache_file(url);
+    let bytes = match fs::read(&path) {
+        Ok(bytes) => bytes,
+        Err(_) => {
+            debug!("downloading cover {}", url);
+            let mut bytes = Vec::new();
+            ureq::get(url)
+                .call()
+                .map_err(|e| e.to_string())?
+                .into_reader()
+                .take(MAX_DOWNLOAD_SIZE)
+                .read_to_end(&mut bytes)
+                .map_err(|e| e.to_string())?;
+            if let Err(e) = fs::write(&path, &bytes) {
+                error!("could not write {}: {}", path.display(), e);
+            }
+            bytes
+        }
+    };
+
+    let image = image::load_from_memory(&bytes).map_err(|e| e.to_string())?;
+    let image = if image.width() > MAX_COVER_SIZE || image.height() > MAX_COVER_SIZE {
+        image.resize(MAX_COVER_SIZE, MAX_COVER_SIZE, FilterType::Triangle)
+    } else {
+        image
+    };
+    Ok(image.to_rgb8())
+}
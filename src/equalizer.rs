@@ -0,0 +1,344 @@
+use std::f64::consts::PI;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+
+use librespot_playback::audio_backend::{Sink, SinkResult};
+use librespot_playback::convert::Converter;
+use librespot_playback::decoder::AudioPacket;
+use librespot_playback::{NUM_CHANNELS, SAMPLE_RATE};
+use log::error;
+
+use crate::fs::config_path;
+
+/// The file in the configuration directory the settings are kept in.
+const SETTINGS_FILE: &str = "equalizer.json";
+
+/// The center frequencies of the bands in Hz.
+pub const FREQUENCIES: [f32; 10] = [
+    31.0, 62.0, 125.0, 250.0, 500.0, 1000.0, 2000.0, 4000.0, 8000.0, 16000.0,
+];
+
+/// The bandwidth of the bands, about an octave.
+const DEFAULT_Q: f32 = 1.41;
+
+/// The most a band or the preamp can boost or cut, in dB.
+pub const MAX_GAIN: f32 = 12.0;
+
+/// The level the limiter keeps peaks under, in dBFS.
+const LIMITER_THRESHOLD: f64 = -1.0;
+
+/// How quickly the limiter lets go after a peak, in seconds.
+const LIMITER_RELEASE: f64 = 0.1;
+
+/// The gains of the bands of every preset, from low to high.
+pub const PRESETS: &[(&str, [f32; 10])] = &[
+    ("flat", [0.0; 10]),
+    ("bass", [6.0, 5.0, 4.0, 2.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0]),
+    ("treble", [0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 2.0, 4.0, 5.0, 6.0]),
+    (
+        "vocal",
+        [-2.0, -2.0, -1.0, 0.0, 2.0, 3.0, 3.0, 2.0, 0.0, -1.0],
+    ),
+    (
+        "loudness",
+        [5.0, 4.0, 2.0, 0.0, -1.0, 0.0, 1.0, 3.0, 4.0, 4.0],
+    ),
+    (
+        "headphones",
+        [3.0, 2.0, 1.0, 0.0, -1.0, 0.0, 1.0, 2.0, 3.0, 2.0],
+    ),
+];
+
+/// A peaking filter around `frequency`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Band {
+    pub frequency: f32,
+    pub gain: f32,
+    pub q: f32,
+}
+
+/// The settings of the DSP stage, persisted across sessions.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct EqualizerSettings {
+    /// Pass the audio through unchanged.
+    pub bypass: bool,
+    pub preamp: f32,
+    pub limiter: bool,
+    pub bands: Vec<Band>,
+    /// The preset the gains were last set from.
+    pub preset: Option<String>,
+}
+
+impl Default for EqualizerSettings {
+    fn default() -> Self {
+        Self {
+            bypass: true,
+            preamp: 0.0,
+            limiter: true,
+            bands: FREQUENCIES
+                .iter()
+                .map(|frequency| Band {
+                    frequency: *frequency,
+                    gain: 0.0,
+                    q: DEFAULT_Q,
+                })
+                .collect(),
+            preset: Some("flat".into()),
+        }
+    }
+}
+
+impl EqualizerSettings {
+    /// Load the settings, keeping the gains of a hand edited file in range.
+    fn load() -> Self {
+        let mut settings: Self = std::fs::read_to_string(config_path(SETTINGS_FILE))
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        settings.preamp = settings.preamp.clamp(-MAX_GAIN, MAX_GAIN);
+        for band in settings.bands.iter_mut() {
+            band.gain = band.gain.clamp(-MAX_GAIN, MAX_GAIN);
+        }
+        settings
+    }
+
+    fn save(&self) {
+        let path = config_path(SETTINGS_FILE);
+        let result = serde_json::to_string_pretty(self)
+            .map_err(std::io::Error::from)
+            .and_then(|contents| std::fs::write(&path, contents));
+        if let Err(e) = result {
+            error!("could not write {}: {}", path.display(), e);
+        }
+    }
+
+    /// Set the gains of the bands from the preset called `name`.
+    pub fn apply_preset(&mut self, name: &str) -> Result<(), String> {
+        let (name, gains) = PRESETS
+            .iter()
+            .find(|(preset, _)| *preset == name)
+            .ok_or_else(|| {
+                let names: Vec<&str> = PRESETS.iter().map(|(name, _)| *name).collect();
+                format!("No preset \"{}\", try {}", name, names.join(", "))
+            })?;
+        self.bands = FREQUENCIES
+            .iter()
+            .zip(gains)
+            .map(|(frequency, gain)| Band {
+                frequency: *frequency,
+                gain: *gain,
+                q: DEFAULT_Q,
+            })
+            .collect();
+        self.preset = Some(name.to_string());
+        self.bypass = false;
+        Ok(())
+    }
+
+    /// Set the gain of the band at `index`.
+    pub fn set_gain(&mut self, index: usize, gain: f32) -> Result<(), String> {
+        let count = self.bands.len();
+        let band = self
+            .bands
+            .get_mut(index)
+            .ok_or_else(|| format!("There are only {count} bands"))?;
+        band.gain = gain.clamp(-MAX_GAIN, MAX_GAIN);
+        self.preset = None;
+        Ok(())
+    }
+
+    pub fn set_preamp(&mut self, gain: f32) {
+        self.preamp = gain.clamp(-MAX_GAIN, MAX_GAIN);
+    }
+}
+
+/// The equalizer settings shared between the interface and the audio thread.
+/// Every change is saved and picked up by the sink with the next packet.
+#[derive(Clone)]
+pub struct Equalizer {
+    settings: Arc<RwLock<EqualizerSettings>>,
+    version: Arc<AtomicU64>,
+}
+
+impl Equalizer {
+    pub fn new() -> Self {
+        Self {
+            settings: Arc::new(RwLock::new(EqualizerSettings::load())),
+            version: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    pub fn settings(&self) -> EqualizerSettings {
+        self.settings.read().unwrap().clone()
+    }
+
+    /// Change the settings with `change`, saving them if it succeeds.
+    pub fn update<T>(
+        &self,
+        change: impl FnOnce(&mut EqualizerSettings) -> Result<T, String>,
+    ) -> Result<T, String> {
+        let mut settings = self.settings.write().unwrap();
+        let result = change(&mut settings)?;
+        settings.save();
+        self.version.fetch_add(1, Ordering::SeqCst);
+        Ok(result)
+    }
+
+    /// Wrap `sink` so the audio written to it goes through the equalizer,
+    /// preamp and limiter.
+    pub fn wrap(&self, sink: Box<dyn Sink>) -> Box<dyn Sink> {
+        Box::new(EqualizerSink {
+            sink,
+            equalizer: self.clone(),
+            version: None,
+            chain: Chain::default(),
+        })
+    }
+}
+
+impl Default for Equalizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A biquad peaking filter for a single channel, from the Audio EQ Cookbook.
+#[derive(Clone, Default)]
+struct Filter {
+    b: [f64; 3],
+    a: [f64; 2],
+    x: [f64; 2],
+    y: [f64; 2],
+}
+
+impl Filter {
+    fn peaking(band: &Band) -> Self {
+        let a = 10_f64.powf(band.gain as f64 / 40.0);
+        let w0 = 2.0 * PI * band.frequency as f64 / SAMPLE_RATE as f64;
+        let alpha = w0.sin() / (2.0 * band.q as f64);
+        let cos = w0.cos();
+        let a0 = 1.0 + alpha / a;
+        Self {
+            b: [
+                (1.0 + alpha * a) / a0,
+                -2.0 * cos / a0,
+                (1.0 - alpha * a) / a0,
+            ],
+            a: [-2.0 * cos / a0, (1.0 - alpha / a) / a0],
+            ..Default::default()
+        }
+    }
+
+    fn process(&mut self, x: f64) -> f64 {
+        let y = self.b[0] * x + self.b[1] * self.x[0] + self.b[2] * self.x[1]
+            - self.a[0] * self.y[0]
+            - self.a[1] * self.y[1];
+        self.x = [x, self.x[0]];
+        self.y = [y, self.y[0]];
+        y
+    }
+}
+
+/// The processing built from the settings, with the state of the filters.
+#[derive(Default)]
+struct Chain {
+    bypass: bool,
+    preamp: f64,
+    /// The filters of every band that isn't flat, one per channel.
+    filters: Vec<Vec<Filter>>,
+    limiter: bool,
+    /// The gain the limiter currently applies.
+    reduction: f64,
+}
+
+impl Chain {
+    fn new(settings: &EqualizerSettings) -> Self {
+        let channels = NUM_CHANNELS as usize;
+        Self {
+            bypass: settings.bypass,
+            preamp: 10_f64.powf(settings.preamp as f64 / 20.0),
+            filters: settings
+                .bands
+                .iter()
+                .filter(|band| band.gain != 0.0)
+                .map(|band| vec![Filter::peaking(band); channels])
+                .collect(),
+            limiter: settings.limiter,
+            reduction: 1.0,
+        }
+    }
+
+    fn process(&mut self, samples: &mut [f64]) {
+        if self.bypass {
+            return;
+        }
+
+        let threshold = 10_f64.powf(LIMITER_THRESHOLD / 20.0);
+        let release = 1.0 - (-1.0 / (LIMITER_RELEASE * SAMPLE_RATE as f64)).exp();
+        for frame in samples.chunks_mut(NUM_CHANNELS as usize) {
+            for (channel, sample) in frame.iter_mut().enumerate() {
+                *sample *= self.preamp;
+                for filters in self.filters.iter_mut() {
+                    *sample = filters[channel].process(*sample);
+                }
+            }
+
+            if self.limiter {
+                // Reduce the gain right away on a peak and let go slowly, the
+                // same for all channels to keep the stereo image.
+                let peak = frame.iter().fold(0.0, |peak: f64, s| peak.max(s.abs()));
+                let target = if peak > threshold {
+                    threshold / peak
+                } else {
+                    1.0
+                };
+                if target < self.reduction {
+                    self.reduction = target;
+                } else {
+                    self.reduction += (target - self.reduction) * release;
+                }
+                frame
+                    .iter_mut()
+                    .for_each(|sample| *sample *= self.reduction);
+            }
+        }
+    }
+}
+
+/// Runs the audio through the equalizer before passing it on to the sink it
+/// wraps.
+struct EqualizerSink {
+    sink: Box<dyn Sink>,
+    equalizer: Equalizer,
+    /// The version of the settings `chain` was built from.
+    version: Option<u64>,
+    chain: Chain,
+}
+
+impl Sink for EqualizerSink {
+    fn start(&mut self) -> SinkResult<()> {
+        self.sink.start()
+    }
+
+    fn stop(&mut self) -> SinkResult<()> {
+        self.sink.stop()
+    }
+
+    fn write(&mut self, packet: AudioPacket, converter: &mut Converter) -> SinkResult<()> {
+        let version = self.equalizer.version.load(Ordering::SeqCst);
+        if self.version != Some(version) {
+            self.chain = Chain::new(&self.equalizer.settings());
+            self.version = Some(version);
+        }
+
+        let packet = match packet {
+            AudioPacket::Samples(mut samples) => {
+                self.chain.process(&mut samples);
+                AudioPacket::Samples(samples)
+            }
+            packet => packet,
+        };
+        self.sink.write(packet, converter)
+    }
+}
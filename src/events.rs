@@ -8,6 +8,8 @@ pub enum Event {
     Player(PlayerEvent),
     Queue(QueueEvent),
     SessionDied,
+    /// The library finished updating.
+    LibraryUpdated,
 }
 
 pub type EventSender = Sender<Event>;
@@ -0,0 +1,119 @@
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use rspotify::model::{EpisodeId, Id};
+
+use crate::command::ExportFormat;
+use crate::model::playable::Playable;
+
+/// A single exported track or episode.
+#[derive(Serialize)]
+struct Entry {
+    uri: String,
+    url: String,
+    artists: Vec<String>,
+    title: String,
+    album: Option<String>,
+    /// Duration in milliseconds.
+    duration: u32,
+    added_at: Option<DateTime<Utc>>,
+}
+
+impl From<&Playable> for Entry {
+    fn from(playable: &Playable) -> Self {
+        match playable {
+            Playable::Track(track) => Self {
+                uri: track.uri.clone(),
+                // local files don't have a share URL
+                url: if track.url.is_empty() {
+                    track.uri.clone()
+                } else {
+                    track.url.clone()
+                },
+                artists: track.artists.clone(),
+                title: track.title.clone(),
+                album: track.album.clone(),
+                duration: track.duration,
+                added_at: track.added_at,
+            },
+            Playable::Episode(episode) => Self {
+                uri: episode.uri.clone(),
+                url: EpisodeId::from_id(&episode.id)
+                    .map(|id| id.url())
+                    .unwrap_or_else(|_| episode.uri.clone()),
+                artists: Vec::new(),
+                title: episode.name.clone(),
+                album: None,
+                duration: episode.duration,
+                added_at: episode.added_at,
+            },
+        }
+    }
+}
+
+fn m3u(entries: &[Entry]) -> String {
+    let mut m3u = String::from("#EXTM3U\n");
+    for entry in entries {
+        let name = if entry.artists.is_empty() {
+            entry.title.clone()
+        } else {
+            format!("{} - {}", entry.artists.join(", "), entry.title)
+        };
+        m3u.push_str(&format!("#EXTINF:{},{}\n", entry.duration / 1000, name));
+        m3u.push_str(&entry.url);
+        m3u.push('\n');
+    }
+    m3u
+}
+
+/// Quote `field` if it contains characters that are special in CSV.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn csv(entries: &[Entry]) -> String {
+    let mut csv = String::from("uri,url,artists,title,album,duration,added_at\n");
+    for entry in entries {
+        let fields = [
+            entry.uri.clone(),
+            entry.url.clone(),
+            entry.artists.join(", "),
+            entry.title.clone(),
+            entry.album.clone().unwrap_or_default(),
+            entry.duration.to_string(),
+            entry
+                .added_at
+                .map(|added_at| added_at.to_rfc3339())
+                .unwrap_or_default(),
+        ];
+        let fields: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Expand a leading `~` in `path` to the home directory of the user.
+pub fn expand_path(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ if path == "~" => dirs::home_dir().unwrap_or_else(|| path.into()),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Write `items` to the file at `path` in the given `format`.
+pub fn export(path: &Path, format: ExportFormat, items: &[Playable]) -> Result<(), String> {
+    let entries: Vec<Entry> = items.iter().map(Entry::from).collect();
+    let contents = match format {
+        ExportFormat::M3u => m3u(&entries),
+        ExportFormat::Csv => csv(&entries),
+        ExportFormat::Json => serde_json::to_string_pretty(&entries).map_err(|e| e.to_string())?,
+    };
+    std::fs::write(path, contents)
+        .map_err(|e| format!("Could not write to {}: {}", path.display(), e))
+}
@@ -0,0 +1,91 @@
+use std::path::PathBuf;
+
+use log::error;
+
+use crate::fs::cache_path;
+
+/// The amount of entries that are kept in a [History].
+const HISTORY_SIZE: usize = 500;
+
+/// Previously entered lines of the command line, stored one per line in a
+/// file in the cache directory so that they are kept across sessions.
+pub struct History {
+    path: PathBuf,
+    /// The entries, oldest first.
+    entries: Vec<String>,
+    /// The index of the entry that is currently shown while browsing.
+    position: Option<usize>,
+    /// What was typed before browsing started, restored when browsing past the
+    /// newest entry.
+    draft: String,
+}
+
+impl History {
+    /// Load the history that is stored in the cache file called `file`.
+    pub fn load(file: &str) -> Self {
+        let path = cache_path(file);
+        let entries = std::fs::read_to_string(&path)
+            .map(|contents| contents.lines().map(String::from).collect())
+            .unwrap_or_default();
+        Self {
+            path,
+            entries,
+            position: None,
+            draft: String::new(),
+        }
+    }
+
+    /// Add `entry` as the newest entry and stop browsing.
+    pub fn push(&mut self, entry: &str) {
+        self.reset();
+        if entry.trim().is_empty() || self.entries.last().map(String::as_str) == Some(entry) {
+            return;
+        }
+
+        self.entries.push(entry.to_string());
+        if self.entries.len() > HISTORY_SIZE {
+            self.entries.drain(..self.entries.len() - HISTORY_SIZE);
+        }
+
+        let mut contents = self.entries.join("\n");
+        contents.push('\n');
+        if let Err(e) = std::fs::write(&self.path, contents) {
+            error!("could not write history to {:?}: {}", self.path, e);
+        }
+    }
+
+    /// The entry before the one that is currently shown. `current` is what is
+    /// typed on the command line, it is restored after the newest entry.
+    pub fn previous(&mut self, current: &str) -> Option<&str> {
+        let index = match self.position {
+            Some(0) => return None,
+            Some(position) => position - 1,
+            None => {
+                self.draft = current.to_string();
+                self.entries.len().checked_sub(1)?
+            }
+        };
+        self.position = Some(index);
+        self.entries.get(index).map(String::as_str)
+    }
+
+    /// The entry after the one that is currently shown, or what was typed
+    /// before browsing started.
+    pub fn next(&mut self) -> Option<&str> {
+        let position = self.position?;
+        if position + 1 < self.entries.len() {
+            self.position = Some(position + 1);
+            self.entries.get(position + 1).map(String::as_str)
+        } else {
+            self.position = None;
+            Some(&self.draft)
+        }
+    }
+
+    /// Stop browsing, so that the next call to [History::previous] starts at
+    /// the newest entry again.
+    pub fn reset(&mut self) {
+        self.position = None;
+        self.draft.clear();
+    }
+}
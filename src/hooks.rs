@@ -0,0 +1,208 @@
+use std::process::Stdio;
+use std::time::Duration;
+
+use log::{error, info, warn};
+use tokio::process::Command;
+
+use crate::application::ASYNC_RUNTIME;
+use crate::model::playable::Playable;
+use crate::spotify::PlayerEvent;
+
+/// How long a hook may run before it is killed, unless configured otherwise.
+const DEFAULT_TIMEOUT: u64 = 10;
+
+/// The programs to run when something happens in the player, from the
+/// `[hooks]` section of the configuration file. They are run by the shell, so
+/// they can use the `NCSPOT_*` environment variables in their arguments.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct HookConfig {
+    pub track_change: Option<String>,
+    pub play: Option<String>,
+    pub pause: Option<String>,
+    pub stop: Option<String>,
+    pub queue_end: Option<String>,
+    pub library_updated: Option<String>,
+    /// The time in seconds after which a hook is killed.
+    pub timeout: Option<u64>,
+}
+
+/// The events hooks can be configured for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HookEvent {
+    TrackChange,
+    Play,
+    Pause,
+    Stop,
+    QueueEnd,
+    LibraryUpdated,
+}
+
+impl HookEvent {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::TrackChange => "track_change",
+            Self::Play => "play",
+            Self::Pause => "pause",
+            Self::Stop => "stop",
+            Self::QueueEnd => "queue_end",
+            Self::LibraryUpdated => "library_updated",
+        }
+    }
+}
+
+/// Runs the configured hooks. It follows the player events to find out when
+/// the track changes and when playback is started, paused or stopped.
+pub struct Hooks {
+    config: HookConfig,
+    /// The URI of the track that was playing at the last player event.
+    track: Option<String>,
+    /// The last player event, to ignore repeated events for the same state.
+    state: Option<PlayerEvent>,
+}
+
+impl Hooks {
+    pub fn new(config: HookConfig) -> Self {
+        Self {
+            config,
+            track: None,
+            state: None,
+        }
+    }
+
+    /// Run the hooks for the player event `event`. `playable` is the current
+    /// item of the queue and `position` the playback position in it.
+    pub fn player_event(
+        &mut self,
+        event: &PlayerEvent,
+        playable: Option<&Playable>,
+        position: Duration,
+    ) {
+        let uri = playable.map(Playable::uri);
+        let track_changed = matches!(event, PlayerEvent::Playing(_)) && uri != self.track;
+        if track_changed {
+            self.track = uri;
+            self.run(HookEvent::TrackChange, playable, position);
+        }
+
+        let state_changed = match (&self.state, event) {
+            (Some(PlayerEvent::Playing(_)), PlayerEvent::Playing(_)) => track_changed,
+            (Some(PlayerEvent::Paused(_)), PlayerEvent::Paused(_)) => false,
+            (Some(previous), event) => previous != event,
+            (None, _) => true,
+        };
+        self.state = Some(event.clone());
+        if !state_changed {
+            return;
+        }
+
+        match event {
+            PlayerEvent::Playing(_) => self.run(HookEvent::Play, playable, position),
+            PlayerEvent::Paused(_) => self.run(HookEvent::Pause, playable, position),
+            PlayerEvent::Stopped => {
+                self.track = None;
+                self.run(HookEvent::Stop, playable, position);
+            }
+            PlayerEvent::FinishedTrack => {}
+        }
+    }
+
+    /// Run the hook for `event` in the background, if one is configured.
+    pub fn run(&self, event: HookEvent, playable: Option<&Playable>, position: Duration) {
+        let command = match event {
+            HookEvent::TrackChange => &self.config.track_change,
+            HookEvent::Play => &self.config.play,
+            HookEvent::Pause => &self.config.pause,
+            HookEvent::Stop => &self.config.stop,
+            HookEvent::QueueEnd => &self.config.queue_end,
+            HookEvent::LibraryUpdated => &self.config.library_updated,
+        };
+        let Some(command) = command.clone() else {
+            return;
+        };
+
+        let mut process = Command::from(shell(&command));
+        process
+            .envs(environment(event, playable, position))
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .kill_on_drop(true);
+        let timeout = Duration::from_secs(self.config.timeout.unwrap_or(DEFAULT_TIMEOUT));
+
+        ASYNC_RUNTIME.get().unwrap().spawn(async move {
+            let mut child = match process.spawn() {
+                Ok(child) => child,
+                Err(e) => {
+                    error!("could not run {} hook \"{}\": {}", event.name(), command, e);
+                    return;
+                }
+            };
+
+            match tokio::time::timeout(timeout, child.wait()).await {
+                Ok(Ok(status)) => info!("{} hook \"{}\" {}", event.name(), command, status),
+                Ok(Err(e)) => error!("{} hook \"{}\" failed: {}", event.name(), command, e),
+                Err(_) => {
+                    warn!(
+                        "{} hook \"{}\" timed out after {:?}",
+                        event.name(),
+                        command,
+                        timeout
+                    );
+                    if let Err(e) = child.kill().await {
+                        error!("could not kill {} hook: {}", event.name(), e);
+                    }
+                }
+            }
+        });
+    }
+}
+
+/// A process that runs `command` with the shell.
+pub(crate) fn shell(command: &str) -> std::process::Command {
+    #[cfg(unix)]
+    let mut process = std::process::Command::new("sh");
+    #[cfg(unix)]
+    process.arg("-c");
+    #[cfg(windows)]
+    let mut process = std::process::Command::new("cmd");
+    #[cfg(windows)]
+    process.arg("/C");
+
+    process.arg(command);
+    process
+}
+
+/// The environment variables that describe `event` and the current item.
+fn environment(
+    event: HookEvent,
+    playable: Option<&Playable>,
+    position: Duration,
+) -> Vec<(&'static str, String)> {
+    let (artist, title, album) = match playable {
+        Some(Playable::Track(track)) => (
+            track.artists.join(", "),
+            track.title.clone(),
+            track.album.clone().unwrap_or_default(),
+        ),
+        Some(Playable::Episode(episode)) => (String::new(), episode.name.clone(), String::new()),
+        None => Default::default(),
+    };
+
+    vec![
+        ("NCSPOT_EVENT", event.name().to_string()),
+        ("NCSPOT_ARTIST", artist),
+        ("NCSPOT_TITLE", title),
+        ("NCSPOT_ALBUM", album),
+        (
+            "NCSPOT_URI",
+            playable.map(Playable::uri).unwrap_or_default(),
+        ),
+        (
+            "NCSPOT_DURATION",
+            playable
+                .map(|p| p.duration().to_string())
+                .unwrap_or_default(),
+        ),
+        ("NCSPOT_POSITION", position.as_millis().to_string()),
+    ]
+}
@@ -0,0 +1,398 @@
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::Arc;
+
+use rspotify::model::{SearchResult, SearchType};
+
+use crate::library::Library;
+use crate::model::artist::Artist;
+use crate::model::playable::Playable;
+use crate::model::track::Track;
+use crate::queue::Queue;
+use crate::spotify_api::WebApi;
+use crate::traits::{ListItem, ViewExt};
+
+/// Matches scoring below this are shown as uncertain in the review.
+pub const LOW_CONFIDENCE: f32 = 0.7;
+
+/// The amount of search results that are considered for every line.
+const CANDIDATES: u32 = 10;
+
+/// A track as described by a line of an imported file.
+#[derive(Clone, Debug)]
+pub struct ImportLine {
+    /// The line as it appeared in the file, used to report unmatched lines.
+    pub text: String,
+    pub artist: Option<String>,
+    pub title: String,
+    /// Duration in milliseconds.
+    pub duration: Option<u32>,
+    /// The id of the track if the line contains a Spotify URI or URL.
+    pub id: Option<String>,
+}
+
+impl ImportLine {
+    /// Parse a plain "Artist - Title" line. Lines without a separator are
+    /// taken to only contain the title.
+    fn from_text(text: &str) -> Self {
+        let (artist, title) = match text.split_once(" - ") {
+            Some((artist, title)) => (Some(artist.trim().to_string()), title.trim().to_string()),
+            None => (None, text.trim().to_string()),
+        };
+        Self {
+            text: text.trim().to_string(),
+            artist,
+            title,
+            duration: None,
+            id: None,
+        }
+    }
+}
+
+/// Extract the track id from a Spotify track URI or URL.
+fn spotify_track_id(location: &str) -> Option<String> {
+    let id = location
+        .strip_prefix("spotify:track:")
+        .or_else(|| location.split("open.spotify.com/track/").nth(1))?;
+    let id = id.split(['?', '/']).next()?;
+    (!id.is_empty()).then(|| id.to_string())
+}
+
+fn parse_m3u(contents: &str) -> Vec<ImportLine> {
+    let mut lines = Vec::new();
+    let mut info: Option<ImportLine> = None;
+    for line in contents.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        if let Some(extinf) = line.strip_prefix("#EXTINF:") {
+            let (seconds, name) = extinf.split_once(',').unwrap_or(("", extinf));
+            let mut parsed = ImportLine::from_text(name);
+            parsed.duration = seconds
+                .trim()
+                .parse::<i64>()
+                .ok()
+                .filter(|s| *s > 0)
+                .and_then(|s| u32::try_from(s).ok().and_then(|s| s.checked_mul(1000)));
+            info = Some(parsed);
+        } else if !line.starts_with('#') {
+            let mut parsed = info.take().unwrap_or_else(|| {
+                // without #EXTINF only the file name can describe the track
+                let name = Path::new(line)
+                    .file_stem()
+                    .map(|s| s.to_string_lossy().to_string())
+                    .unwrap_or_else(|| line.to_string());
+                ImportLine::from_text(&name)
+            });
+            parsed.id = spotify_track_id(line);
+            lines.push(parsed);
+        }
+    }
+    lines
+}
+
+/// Split a line of CSV into its fields, honoring quotes.
+fn csv_fields(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+/// Parse CSV with a header naming the columns, as written by the export. Files
+/// without a recognizable header are read as "artist,title[,duration]".
+fn parse_csv(contents: &str) -> Vec<ImportLine> {
+    let mut rows = contents.lines().filter(|l| !l.trim().is_empty()).peekable();
+    let header: Vec<String> = rows
+        .peek()
+        .map(|h| {
+            csv_fields(h)
+                .iter()
+                .map(|f| f.trim().to_lowercase())
+                .collect()
+        })
+        .unwrap_or_default();
+    let column = |names: &[&str]| header.iter().position(|h| names.contains(&h.as_str()));
+    let title_column = column(&["title", "name", "track", "track name"]);
+    let (artist, title, duration, uri) = match title_column {
+        Some(title) => {
+            rows.next();
+            (
+                column(&["artist", "artists", "artist name", "artist name(s)"]),
+                title,
+                column(&["duration", "duration (ms)", "duration_ms"]),
+                column(&["uri", "url", "track uri", "spotify uri"]),
+            )
+        }
+        None => (Some(0), 1, Some(2), None),
+    };
+
+    rows.map(|row| {
+        let fields = csv_fields(row);
+        let field = |index: Option<usize>| {
+            index
+                .and_then(|i| fields.get(i))
+                .map(|f| f.trim().to_string())
+                .filter(|f| !f.is_empty())
+        };
+        ImportLine {
+            text: row.to_string(),
+            artist: field(artist),
+            title: field(Some(title)).unwrap_or_default(),
+            duration: field(duration).and_then(|d| d.parse().ok()),
+            id: field(uri).and_then(|u| spotify_track_id(&u)),
+        }
+    })
+    .collect()
+}
+
+fn parse_text(contents: &str) -> Vec<ImportLine> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .map(ImportLine::from_text)
+        .collect()
+}
+
+/// Read the tracks listed in the M3U, CSV or plain text file at `path`. The
+/// format is chosen by the extension of the file.
+pub fn parse(path: &Path) -> Result<Vec<ImportLine>, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    Ok(match extension.as_str() {
+        "m3u" | "m3u8" => parse_m3u(&contents),
+        "csv" => parse_csv(&contents),
+        _ => parse_text(&contents),
+    })
+}
+
+/// A search result for an [ImportLine] and how well it matches.
+#[derive(Clone, Debug)]
+pub struct Candidate {
+    pub track: Track,
+    /// How well the track matches, from 0 to 1.
+    pub score: f32,
+}
+
+/// Lowercase words of `s`, ignoring punctuation and anything in brackets like
+/// "(Remastered 2011)" or "[feat. ...]".
+fn words(s: &str) -> HashSet<String> {
+    let mut depth = 0;
+    let mut cleaned = String::new();
+    for c in s.chars() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth = std::cmp::max(depth - 1, 0),
+            c if depth == 0 => cleaned.extend(c.to_lowercase()),
+            _ => {}
+        }
+    }
+    // "Title - Remastered" and friends only describe the version
+    let cleaned = cleaned.split(" - ").next().unwrap_or_default().to_string();
+    cleaned
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// The Sørensen–Dice coefficient of the words in `a` and `b`.
+fn similarity(a: &str, b: &str) -> f32 {
+    let (a, b) = (words(a), words(b));
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    2.0 * a.intersection(&b).count() as f32 / (a.len() + b.len()) as f32
+}
+
+/// Score how well `track` matches `line`, weighing the title most, then the
+/// artist and finally the duration. Fields that are missing from the line
+/// don't count.
+fn score(line: &ImportLine, track: &Track) -> f32 {
+    let mut scores = vec![(0.5, similarity(&line.title, &track.title))];
+    if let Some(artist) = &line.artist {
+        let artist_score = similarity(artist, &track.artists.join(" ")).max(
+            track
+                .artists
+                .iter()
+                .map(|a| similarity(artist, a))
+                .fold(0.0, f32::max),
+        );
+        scores.push((0.35, artist_score));
+    }
+    if let Some(duration) = line.duration {
+        let difference = (duration as f32 - track.duration as f32).abs() / 1000.0;
+        // a few seconds off is common between releases
+        scores.push((0.15, (1.0 - (difference - 3.0).max(0.0) / 30.0).max(0.0)));
+    }
+    let weights: f32 = scores.iter().map(|(w, _)| w).sum();
+    scores.iter().map(|(w, s)| w * s).sum::<f32>() / weights
+}
+
+fn search(api: &WebApi, query: &str) -> Vec<Track> {
+    match api.search(SearchType::Track, query, CANDIDATES, 0) {
+        Some(SearchResult::Tracks(page)) => page.items.iter().map(|ft| ft.into()).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Find tracks matching `line`, the best match first.
+pub fn candidates(api: &WebApi, line: &ImportLine) -> Vec<Candidate> {
+    if let Some(track) = line.id.as_ref().and_then(|id| api.track(id)) {
+        return vec![Candidate {
+            track: (&track).into(),
+            score: 1.0,
+        }];
+    }
+
+    let mut tracks = match &line.artist {
+        Some(artist) => search(api, &format!("track:{} artist:{}", line.title, artist)),
+        None => Vec::new(),
+    };
+    if tracks.is_empty() {
+        let query = match &line.artist {
+            Some(artist) => format!("{} {}", artist, line.title),
+            None => line.title.clone(),
+        };
+        tracks = search(api, &query);
+    }
+
+    let mut candidates: Vec<Candidate> = tracks
+        .into_iter()
+        .map(|track| Candidate {
+            score: score(line, &track),
+            track,
+        })
+        .collect();
+    candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+    candidates
+}
+
+/// The result of matching an [ImportLine], with the candidate the user picked.
+#[derive(Clone, Debug)]
+pub struct ImportMatch {
+    pub line: ImportLine,
+    pub candidates: Vec<Candidate>,
+    /// The index of the chosen candidate, or `None` if the line is skipped.
+    pub selected: Option<usize>,
+}
+
+impl ImportMatch {
+    pub fn new(line: ImportLine, candidates: Vec<Candidate>) -> Self {
+        let selected = (!candidates.is_empty()).then_some(0);
+        Self {
+            line,
+            candidates,
+            selected,
+        }
+    }
+
+    pub fn candidate(&self) -> Option<&Candidate> {
+        self.candidates.get(self.selected?)
+    }
+
+    pub fn is_uncertain(&self) -> bool {
+        self.candidate().is_some_and(|c| c.score < LOW_CONFIDENCE)
+    }
+
+    fn as_track(&self) -> Option<Box<dyn ListItem>> {
+        self.candidate().map(|c| c.track.as_listitem())
+    }
+}
+
+impl ListItem for ImportMatch {
+    fn is_playing(&self, queue: &Queue) -> bool {
+        self.as_track().is_some_and(|t| t.is_playing(queue))
+    }
+
+    fn display_left(&self, _library: &Library) -> String {
+        match self.candidate() {
+            Some(c) => format!("{} - {}", c.track.artists.join(", "), c.track.title),
+            None => self.line.text.clone(),
+        }
+    }
+
+    fn display_center(&self, _library: &Library) -> String {
+        match self.candidate() {
+            Some(_) => self.line.text.clone(),
+            None => String::new(),
+        }
+    }
+
+    fn display_right(&self, _library: &Library) -> String {
+        match self.candidate() {
+            Some(c) => format!(
+                "{}{:.0}% {}",
+                if self.is_uncertain() { "? " } else { "" },
+                c.score * 100.0,
+                c.track.duration_str()
+            ),
+            None if self.candidates.is_empty() => "no match".to_string(),
+            None => "skipped".to_string(),
+        }
+    }
+
+    fn play(&mut self, queue: &Queue) {
+        if let Some(mut track) = self.as_track() {
+            track.play(queue);
+        }
+    }
+
+    fn play_next(&mut self, queue: &Queue) {
+        if let Some(mut track) = self.as_track() {
+            track.play_next(queue);
+        }
+    }
+
+    fn queue(&mut self, queue: &Queue) {
+        if let Some(mut track) = self.as_track() {
+            track.queue(queue);
+        }
+    }
+
+    fn save(&mut self, _library: &Library) {}
+
+    fn open(&self, _queue: Arc<Queue>, _library: Arc<Library>) -> Option<Box<dyn ViewExt>> {
+        None
+    }
+
+    fn artists(&self) -> Option<Vec<Artist>> {
+        Some(
+            self.as_track()
+                .and_then(|t| t.artists())
+                .unwrap_or_default(),
+        )
+    }
+
+    fn track(&self) -> Option<Track> {
+        self.candidate().map(|c| c.track.clone())
+    }
+
+    fn playable(&self) -> Option<Playable> {
+        self.track().map(Playable::Track)
+    }
+
+    #[inline]
+    fn is_playable(&self) -> bool {
+        self.candidate().is_some()
+    }
+
+    fn as_listitem(&self) -> Box<dyn ListItem> {
+        Box::new(self.clone())
+    }
+}
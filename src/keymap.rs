@@ -0,0 +1,136 @@
+use cursive::event::{Event, Key};
+
+use crate::command::COMMANDS;
+
+/// The file in the configuration directory that `:keymap save` writes the
+/// bindings and aliases to, and that is read at startup.
+pub const KEYMAP_FILE: &str = "keybindings";
+
+/// The key bindings that are active unless they are changed with `:bind` or
+/// `:unbind`, as declared by the commands in the registry.
+pub fn default_bindings() -> impl Iterator<Item = &'static (&'static str, &'static str)> {
+    COMMANDS.iter().flat_map(|spec| spec.keys.iter())
+}
+
+/// The names of the keys that don't produce a character.
+const KEY_NAMES: &[(&str, Key)] = &[
+    ("Enter", Key::Enter),
+    ("Tab", Key::Tab),
+    ("Backspace", Key::Backspace),
+    ("Esc", Key::Esc),
+    ("Left", Key::Left),
+    ("Right", Key::Right),
+    ("Up", Key::Up),
+    ("Down", Key::Down),
+    ("Insert", Key::Ins),
+    ("Delete", Key::Del),
+    ("Home", Key::Home),
+    ("End", Key::End),
+    ("PageUp", Key::PageUp),
+    ("PageDown", Key::PageDown),
+    ("PauseBreak", Key::PauseBreak),
+    ("NumpadCenter", Key::NumpadCenter),
+    ("F0", Key::F0),
+    ("F1", Key::F1),
+    ("F2", Key::F2),
+    ("F3", Key::F3),
+    ("F4", Key::F4),
+    ("F5", Key::F5),
+    ("F6", Key::F6),
+    ("F7", Key::F7),
+    ("F8", Key::F8),
+    ("F9", Key::F9),
+    ("F10", Key::F10),
+    ("F11", Key::F11),
+    ("F12", Key::F12),
+];
+
+fn key_from_name(name: &str) -> Option<Key> {
+    KEY_NAMES
+        .iter()
+        .find(|(key_name, _)| key_name.eq_ignore_ascii_case(name))
+        .map(|(_, key)| *key)
+}
+
+fn name_of_key(key: Key) -> String {
+    KEY_NAMES
+        .iter()
+        .find(|(_, k)| *k == key)
+        .map(|(name, _)| name.to_string())
+        .unwrap_or_else(|| format!("{key:?}"))
+}
+
+/// Parse a key name like `a`, `Space`, `Ctrl+r`, `Alt+Left` or `F5` into the
+/// event cursive emits when the key is pressed.
+pub fn parse_key(name: &str) -> Result<Event, String> {
+    let (mut ctrl, mut alt, mut shift) = (false, false, false);
+    let mut key = name;
+    while let Some((modifier, rest)) = key.split_once('+').filter(|(_, rest)| !rest.is_empty()) {
+        match modifier.to_lowercase().as_str() {
+            "ctrl" | "control" => ctrl = true,
+            "alt" | "meta" => alt = true,
+            "shift" => shift = true,
+            _ => return Err(format!("Unknown modifier \"{modifier}\" in \"{name}\"")),
+        }
+        key = rest;
+    }
+
+    let character = match key {
+        _ if key.eq_ignore_ascii_case("space") => Some(' '),
+        _ if key.chars().count() == 1 => key.chars().next(),
+        _ => None,
+    };
+
+    let unsupported = || format!("Unsupported combination of modifiers in \"{name}\"");
+    let event = if let Some(c) = character {
+        match (ctrl, alt, shift) {
+            (false, false, false) => Event::Char(c),
+            (false, false, true) => Event::Char(c.to_ascii_uppercase()),
+            (true, false, false) => Event::CtrlChar(c.to_ascii_lowercase()),
+            (false, true, false) => Event::AltChar(c),
+            _ => return Err(unsupported()),
+        }
+    } else {
+        let key = key_from_name(key).ok_or_else(|| format!("Unknown key \"{key}\""))?;
+        match (ctrl, alt, shift) {
+            (false, false, false) => Event::Key(key),
+            (false, false, true) => Event::Shift(key),
+            (false, true, false) => Event::Alt(key),
+            (false, true, true) => Event::AltShift(key),
+            (true, false, false) => Event::Ctrl(key),
+            (true, false, true) => Event::CtrlShift(key),
+            (true, true, false) => Event::CtrlAlt(key),
+            _ => return Err(unsupported()),
+        }
+    };
+    Ok(event)
+}
+
+/// The name of the key that emits `event`, in the syntax understood by
+/// [parse_key].
+pub fn key_name(event: &Event) -> String {
+    match event {
+        Event::Char(' ') => "Space".to_string(),
+        Event::Char(c) => c.to_string(),
+        Event::CtrlChar(c) => format!("Ctrl+{c}"),
+        Event::AltChar(c) => format!("Alt+{c}"),
+        Event::Key(key) => name_of_key(*key),
+        Event::Shift(key) => format!("Shift+{}", name_of_key(*key)),
+        Event::Alt(key) => format!("Alt+{}", name_of_key(*key)),
+        Event::AltShift(key) => format!("Alt+Shift+{}", name_of_key(*key)),
+        Event::Ctrl(key) => format!("Ctrl+{}", name_of_key(*key)),
+        Event::CtrlShift(key) => format!("Ctrl+Shift+{}", name_of_key(*key)),
+        Event::CtrlAlt(key) => format!("Ctrl+Alt+{}", name_of_key(*key)),
+        _ => format!("{event:?}"),
+    }
+}
+
+/// Quote `command` if necessary, so that it stays a single argument of `:bind`
+/// or `:alias`.
+pub fn escape_command(command: &str) -> String {
+    let is_plain = |c: char| !c.is_whitespace() && !matches!(c, '"' | '\'' | '\\' | ';');
+    if !command.is_empty() && command.chars().all(is_plain) {
+        return command.to_string();
+    }
+    format!("\"{}\"", command.replace('\\', "\\\\").replace('"', "\\\""))
+}
@@ -5,25 +5,68 @@ use std::path::PathBuf;
 use std::sync::{Arc, RwLock, RwLockReadGuard};
 use std::thread;
 
+use chrono::{DateTime, Duration, NaiveDate, Utc};
 use log::{debug, error, info};
 use rspotify::model::Id;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 
-use crate::events::EventManager;
+use crate::events::{Event, EventManager};
 use crate::fs::cache_path;
 use crate::model::album::Album;
 use crate::model::artist::Artist;
+use crate::model::audio_features::AudioFeatures;
+use crate::model::episode::Episode;
 use crate::model::playable::Playable;
 use crate::model::playlist::Playlist;
+use crate::model::seed::{Seed, Tuning};
 use crate::model::show::Show;
 use crate::model::track::Track;
+use crate::command::{FeatureColumn, TuneAttribute, TuneBound};
 use crate::spotify::Spotify;
+use crate::spotify_api::{
+    RecommendationSeeds, MAX_AUDIO_FEATURES_TRACKS, MAX_RECOMMENDATION_SEEDS,
+};
 
-const CACHE_TRACKS: &str = "tracks.db";
+pub const CACHE_TRACKS: &str = "tracks.db";
 const CACHE_ALBUMS: &str = "albums.db";
 const CACHE_ARTISTS: &str = "artists.db";
-const CACHE_PLAYLISTS: &str = "playlists.db";
+pub const CACHE_PLAYLISTS: &str = "playlists.db";
+const CACHE_INBOX: &str = "inbox.db";
+const CACHE_AUDIO_FEATURES: &str = "features.db";
+
+/// The files in the cache directory the library is kept in, which can be
+/// fetched again. The inbox state can't, so it isn't one of them.
+pub const CACHE_FILES: &[&str] = &[
+    CACHE_TRACKS,
+    CACHE_ALBUMS,
+    CACHE_ARTISTS,
+    CACHE_PLAYLISTS,
+    CACHE_AUDIO_FEATURES,
+];
+
+/// The amount of most recent episodes per saved show that are checked for the
+/// inbox.
+const INBOX_EPISODES_PER_SHOW: u32 = 10;
+
+/// The persisted state of the new episodes inbox.
+#[derive(Deserialize, Serialize)]
+struct InboxState {
+    /// Episodes released before this day aren't considered new.
+    last_checked: DateTime<Utc>,
+    /// Episodes that were removed from the inbox by the user, with their
+    /// release date.
+    dismissed: HashMap<String, NaiveDate>,
+}
+
+impl Default for InboxState {
+    fn default() -> Self {
+        Self {
+            last_checked: Utc::now() - Duration::days(7),
+            dismissed: HashMap::new(),
+        }
+    }
+}
 
 #[derive(Clone)]
 pub struct Library {
@@ -32,6 +75,16 @@ pub struct Library {
     pub artists: Arc<RwLock<Vec<Artist>>>,
     pub playlists: Arc<RwLock<Vec<Playlist>>>,
     pub shows: Arc<RwLock<Vec<Show>>>,
+    pub inbox: Arc<RwLock<Vec<Episode>>>,
+    inbox_state: Arc<RwLock<InboxState>>,
+    /// The items the recommendations builder bases its results on.
+    pub recommendation_seeds: Arc<RwLock<Vec<Seed>>>,
+    /// The audio feature constraints of the recommendations builder.
+    pub recommendation_tuning: Arc<RwLock<Vec<Tuning>>>,
+    /// Audio features of tracks by their id, fetched on demand.
+    pub audio_features: Arc<RwLock<HashMap<String, AudioFeatures>>>,
+    /// The audio features track lists show in extra columns, in order.
+    pub audio_feature_columns: Arc<RwLock<Vec<FeatureColumn>>>,
     pub is_done: Arc<RwLock<bool>>,
     pub user_id: Option<String>,
     pub display_name: Option<String>,
@@ -51,6 +104,12 @@ impl Library {
             artists: Arc::new(RwLock::new(Vec::new())),
             playlists: Arc::new(RwLock::new(Vec::new())),
             shows: Arc::new(RwLock::new(Vec::new())),
+            inbox: Arc::new(RwLock::new(Vec::new())),
+            inbox_state: Arc::new(RwLock::new(Self::load_inbox_state())),
+            recommendation_seeds: Arc::new(RwLock::new(Vec::new())),
+            recommendation_tuning: Arc::new(RwLock::new(Vec::new())),
+            audio_features: Arc::new(RwLock::new(Self::load_audio_features())),
+            audio_feature_columns: Arc::new(RwLock::new(Vec::new())),
             is_done: Arc::new(RwLock::new(false)),
             user_id,
             display_name,
@@ -168,6 +227,7 @@ impl Library {
                 let library = library.clone();
                 thread::spawn(move || {
                     library.fetch_shows();
+                    library.fetch_inbox();
                 })
             };
 
@@ -184,7 +244,7 @@ impl Library {
             let mut is_done = library.is_done.write().unwrap();
             *is_done = true;
 
-            library.ev.trigger();
+            library.ev.send(Event::LibraryUpdated);
         });
     }
 
@@ -212,6 +272,224 @@ impl Library {
         *self.shows.write().unwrap() = saved_shows;
     }
 
+    /// Collect the episodes of saved shows that were released since the inbox
+    /// was last checked, newest first.
+    fn fetch_inbox(&self) {
+        debug!("loading inbox");
+
+        let shows = self.shows.read().unwrap().clone();
+        let mut episodes: Vec<Episode> = shows
+            .iter()
+            .filter_map(|show| {
+                self.spotify
+                    .api
+                    .show_latest_episodes(&show.id, INBOX_EPISODES_PER_SHOW)
+            })
+            .flatten()
+            .collect();
+
+        {
+            let state = self.inbox_state.read().unwrap();
+            let last_checked = state.last_checked.date_naive();
+            episodes.retain(|episode| {
+                !state.dismissed.contains_key(&episode.id)
+                    && episode
+                        .release_date()
+                        .map(|date| date >= last_checked)
+                        .unwrap_or(false)
+            });
+        }
+        episodes.sort_by_key(|episode| std::cmp::Reverse(episode.release_date()));
+
+        *self.inbox.write().unwrap() = episodes;
+        self.ev.trigger();
+    }
+
+    fn load_inbox_state() -> InboxState {
+        let path = cache_path(CACHE_INBOX);
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                error!("can't parse inbox state: {}", e);
+                InboxState::default()
+            }),
+            Err(_) => InboxState::default(),
+        }
+    }
+
+    fn save_inbox_state(&self) {
+        let state = self.inbox_state.read().unwrap();
+        match serde_json::to_string(&*state) {
+            Ok(contents) => std::fs::write(cache_path(CACHE_INBOX), contents).unwrap(),
+            Err(e) => error!("could not write inbox state: {:?}", e),
+        }
+    }
+
+    fn load_audio_features() -> HashMap<String, AudioFeatures> {
+        let path = cache_path(CACHE_AUDIO_FEATURES);
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                error!("can't parse audio features: {}", e);
+                HashMap::new()
+            }),
+            Err(_) => HashMap::new(),
+        }
+    }
+
+    fn save_audio_features(&self) {
+        let features = self.audio_features.read().unwrap();
+        match serde_json::to_string(&*features) {
+            Ok(contents) => std::fs::write(cache_path(CACHE_AUDIO_FEATURES), contents).unwrap(),
+            Err(e) => error!("could not write audio features: {:?}", e),
+        }
+    }
+
+    /// The cached audio features of `track`, if they were fetched before.
+    pub fn track_features(&self, track: &Track) -> Option<AudioFeatures> {
+        let id = track.id.as_ref()?;
+        self.audio_features.read().unwrap().get(id).cloned()
+    }
+
+    /// Fetch the audio features of all `tracks` that aren't cached yet, in
+    /// batches of [MAX_AUDIO_FEATURES_TRACKS]. Returns whether any were missing.
+    pub fn fetch_audio_features(&self, tracks: &[Track]) -> bool {
+        let missing: Vec<&str> = {
+            let features = self.audio_features.read().unwrap();
+            let mut missing: Vec<&str> = tracks
+                .iter()
+                .filter(|t| !t.is_local)
+                .filter_map(|t| t.id.as_deref())
+                .filter(|id| !features.contains_key(*id))
+                .collect();
+            missing.sort_unstable();
+            missing.dedup();
+            missing
+        };
+        if missing.is_empty() {
+            return false;
+        }
+
+        debug!("fetching audio features of {} tracks", missing.len());
+        for batch in missing.chunks(MAX_AUDIO_FEATURES_TRACKS) {
+            if let Some(fetched) = self.spotify.api.tracks_features(batch) {
+                let mut features = self.audio_features.write().unwrap();
+                for f in fetched {
+                    features.insert(f.id.clone(), f);
+                }
+            }
+        }
+
+        self.save_audio_features();
+        self.ev.trigger();
+        true
+    }
+
+    /// Remove `episode` from the inbox and remember it as dismissed.
+    pub fn dismiss_from_inbox(&self, episode: &Episode) {
+        self.inbox.write().unwrap().retain(|e| e.id != episode.id);
+        if let Some(date) = episode.release_date() {
+            self.inbox_state
+                .write()
+                .unwrap()
+                .dismissed
+                .insert(episode.id.clone(), date);
+        }
+        self.save_inbox_state();
+        self.ev.trigger();
+    }
+
+    /// Empty the inbox, so that only episodes released from now on show up.
+    pub fn mark_inbox_checked(&self) {
+        {
+            let mut inbox = self.inbox.write().unwrap();
+            let mut state = self.inbox_state.write().unwrap();
+            state.last_checked = Utc::now();
+            // Episodes released earlier today would show up again otherwise.
+            for episode in inbox.drain(..) {
+                if let Some(date) = episode.release_date() {
+                    state.dismissed.insert(episode.id, date);
+                }
+            }
+            let last_checked = state.last_checked.date_naive();
+            state.dismissed.retain(|_, date| *date >= last_checked);
+        }
+        self.save_inbox_state();
+        self.ev.trigger();
+    }
+
+    /// The episodes in the inbox, oldest first.
+    pub fn inbox_oldest_first(&self) -> Vec<Episode> {
+        let mut episodes = self.inbox.read().unwrap().clone();
+        episodes.sort_by_key(|episode| episode.release_date());
+        episodes
+    }
+
+    /// Add `seed` to the recommendations builder, or remove it if it was
+    /// already added. Returns the amount of seeds afterwards.
+    pub fn toggle_recommendation_seed(&self, seed: Seed) -> Result<usize, String> {
+        let mut seeds = self.recommendation_seeds.write().unwrap();
+        if let Some(index) = seeds.iter().position(|s| *s == seed) {
+            seeds.remove(index);
+        } else if seeds.len() >= MAX_RECOMMENDATION_SEEDS {
+            return Err(format!(
+                "Recommendations can be based on at most {MAX_RECOMMENDATION_SEEDS} seeds"
+            ));
+        } else {
+            seeds.push(seed);
+        }
+        self.ev.trigger();
+        Ok(seeds.len())
+    }
+
+    /// Constrain `attribute` of recommended tracks to `value`, or remove all
+    /// constraints on it if no value is given. Fails if the minimum would be
+    /// above the maximum.
+    pub fn tune_recommendations(
+        &self,
+        attribute: TuneAttribute,
+        tuning: Option<(TuneBound, f32)>,
+    ) -> Result<(), String> {
+        let mut tunings = self.recommendation_tuning.write().unwrap();
+        match tuning {
+            Some((bound, value)) => {
+                let tuning = Tuning {
+                    attribute,
+                    bound,
+                    value,
+                };
+                let conflict = tunings.iter().find(|t| {
+                    t.attribute == attribute
+                        && match (bound, t.bound) {
+                            (TuneBound::Min, TuneBound::Max) => value > t.value,
+                            (TuneBound::Max, TuneBound::Min) => value < t.value,
+                            _ => false,
+                        }
+                });
+                if let Some(conflict) = conflict {
+                    return Err(format!("\"{tuning}\" conflicts with \"{conflict}\""));
+                }
+                tunings.retain(|t| t.attribute != attribute || t.bound != bound);
+                tunings.push(tuning);
+            }
+            None => tunings.retain(|t| t.attribute != attribute),
+        }
+        self.ev.trigger();
+        Ok(())
+    }
+
+    /// Fetch recommendations based on the seeds and tuning of the
+    /// recommendations builder.
+    pub fn recommendations(&self) -> Option<Vec<Track>> {
+        let seeds: RecommendationSeeds = self.recommendation_seeds.read().unwrap().iter().collect();
+        let attributes: Vec<_> = self
+            .recommendation_tuning
+            .read()
+            .unwrap()
+            .iter()
+            .map(Into::into)
+            .collect();
+        self.spotify.api.recommended_tracks(&seeds, &attributes)
+    }
+
     fn fetch_playlists(&self) {
         debug!("loading playlists");
         let mut stale_lists = self.playlists.read().unwrap().clone();
@@ -306,6 +584,171 @@ impl Library {
         }
     }
 
+    /// Albums are sorted by artist, year and title, ignoring a leading "The".
+    fn album_sort_key(album: &Album) -> String {
+        let album_artist = album
+            .artists
+            .first()
+            .map(|a| a.strip_prefix("The ").unwrap_or(a))
+            .unwrap_or_default();
+        let album_title = album.title.strip_prefix("The ").unwrap_or(&album.title);
+        format!(
+            "{}{}{}",
+            album_artist.to_lowercase(),
+            album.year,
+            album_title.to_lowercase()
+        )
+    }
+
+    /// Create a playlist named `name` containing `tracks` and add it to the
+    /// library.
+    pub fn create_playlist(&self, name: &str, tracks: &[Track]) -> Option<Playlist> {
+        let user_id = self.user_id.as_deref()?;
+        let created = self.spotify.api.user_playlist_create(user_id, name)?;
+        let mut playlist: Playlist = (&created).into();
+
+        let ids: Vec<&str> = tracks.iter().filter_map(|t| t.id.as_deref()).collect();
+        self.spotify.api.playlist_add_tracks(&playlist.id, &ids)?;
+        // the snapshot is outdated now, so the next update fetches the list
+        playlist.num_tracks = ids.len();
+        playlist.tracks = Some(tracks.iter().cloned().map(Playable::Track).collect());
+
+        self.playlists.write().unwrap().insert(0, playlist.clone());
+        self.save_cache(cache_path(CACHE_PLAYLISTS), self.playlists.clone());
+        self.ev.trigger();
+        Some(playlist)
+    }
+
+    /// Save `track` to the library.
+    pub fn save_track(&self, track: &Track) {
+        let Some(id) = track.id.as_deref() else {
+            return;
+        };
+        if self.spotify.api.current_user_saved_tracks_add(id).is_none() {
+            return;
+        }
+
+        {
+            let mut store = self.tracks.write().unwrap();
+            if !store.iter().any(|t| t.id == track.id) {
+                let mut track = track.clone();
+                track.added_at = Some(Utc::now());
+                store.insert(0, track);
+            }
+        }
+
+        self.save_cache(cache_path(CACHE_TRACKS), self.tracks.clone());
+        self.ev.trigger();
+    }
+
+    /// Remove `track` from the library.
+    pub fn unsave_track(&self, track: &Track) {
+        let Some(id) = track.id.as_deref() else {
+            return;
+        };
+        if self
+            .spotify
+            .api
+            .current_user_saved_tracks_delete(id)
+            .is_none()
+        {
+            return;
+        }
+
+        self.tracks.write().unwrap().retain(|t| t.id != track.id);
+        self.save_cache(cache_path(CACHE_TRACKS), self.tracks.clone());
+        self.ev.trigger();
+    }
+
+    /// Save `album` to the library.
+    pub fn save_album(&self, album: &Album) {
+        let Some(id) = album.id.as_deref() else {
+            return;
+        };
+        if self.spotify.api.current_user_saved_albums_add(id).is_none() {
+            return;
+        }
+
+        {
+            let mut store = self.albums.write().unwrap();
+            if !store.iter().any(|a| a.id == album.id) {
+                let mut album = album.clone();
+                album.added_at = Some(Utc::now());
+                store.push(album);
+                store.sort_unstable_by_key(Self::album_sort_key);
+            }
+        }
+
+        self.save_cache(cache_path(CACHE_ALBUMS), self.albums.clone());
+        self.ev.trigger();
+    }
+
+    /// Remove `album` from the library.
+    pub fn unsave_album(&self, album: &Album) {
+        let Some(id) = album.id.as_deref() else {
+            return;
+        };
+        if self
+            .spotify
+            .api
+            .current_user_saved_albums_delete(id)
+            .is_none()
+        {
+            return;
+        }
+
+        self.albums.write().unwrap().retain(|a| a.id != album.id);
+        self.save_cache(cache_path(CACHE_ALBUMS), self.albums.clone());
+        self.ev.trigger();
+    }
+
+    /// Follow `artist` and add it to the library.
+    pub fn follow_artist(&self, artist: &Artist) {
+        let Some(id) = artist.id.as_deref() else {
+            return;
+        };
+        if self.spotify.api.user_follow_artist(id).is_none() {
+            return;
+        }
+
+        {
+            let mut store = self.artists.write().unwrap();
+            if let Some(local) = store.iter_mut().find(|a| a.id == artist.id) {
+                local.is_followed = true;
+            } else {
+                let mut artist = artist.clone();
+                artist.is_followed = true;
+                artist.tracks = Some(Vec::new());
+                store.push(artist);
+            }
+        }
+
+        self.save_cache(cache_path(CACHE_ARTISTS), self.artists.clone());
+        self.ev.trigger();
+    }
+
+    /// Unfollow `artist`. It stays in the library as long as it has saved
+    /// tracks.
+    pub fn unfollow_artist(&self, artist: &Artist) {
+        let Some(id) = artist.id.as_deref() else {
+            return;
+        };
+        if self.spotify.api.user_unfollow_artist(id).is_none() {
+            return;
+        }
+
+        {
+            let mut store = self.artists.write().unwrap();
+            if let Some(local) = store.iter_mut().find(|a| a.id == artist.id) {
+                local.is_followed = false;
+            }
+            store.retain(|a| a.is_followed || a.tracks.as_ref().is_some_and(|t| !t.is_empty()));
+        }
+
+        self.save_cache(cache_path(CACHE_ARTISTS), self.artists.clone());
+        self.ev.trigger();
+    }
+
     fn insert_artist(&self, id: &str, name: &str) {
         let mut artists = self.artists.write().unwrap();
 
@@ -346,18 +789,7 @@ impl Library {
             }
         }
 
-        albums.sort_unstable_by_key(|album| {
-            let album_artist = album.artists[0]
-                .strip_prefix("The ")
-                .unwrap_or(&album.artists[0]);
-            let album_title = album.title.strip_prefix("The ").unwrap_or(&album.title);
-            format!(
-                "{}{}{}",
-                album_artist.to_lowercase(),
-                album.year,
-                album_title.to_lowercase()
-            )
-        });
+        albums.sort_unstable_by_key(Self::album_sort_key);
 
         *(self.albums.write().unwrap()) = albums;
     }
@@ -12,22 +12,37 @@ use librespot_playback::audio_backend;
 
 mod application;
 mod authentication;
+mod cache;
 mod cli;
 mod command;
 mod commands;
+mod config;
+mod cover;
+mod equalizer;
 mod events;
+mod export;
 mod ext_traits;
 mod fs;
+mod history;
+mod hooks;
+mod import;
+mod keymap;
 mod library;
 mod model;
+#[cfg(feature = "notify")]
+mod notification;
 mod panic;
+mod pcm;
+mod playback;
 mod queue;
+mod scrobbler;
 mod spotify;
 mod spotify_api;
 mod spotify_worker;
 mod traits;
 mod ui;
 mod utils;
+mod visualizer;
 
 pub fn program_arguments() -> clap::Command {
     let backends = {
@@ -48,7 +63,38 @@ pub fn program_arguments() -> clap::Command {
                 .value_parser(PathBufValueParser::new())
                 .help("Enable debug logging to the specified file"),
         )
-        .subcommands([clap::Command::new("info").about("Print platform information like paths")])
+        .subcommands([
+            clap::Command::new("info").about("Print platform information like paths"),
+            clap::Command::new("cache")
+                .about("Show how much disk space the cache takes up")
+                .subcommand(
+                    clap::Command::new("clear")
+                        .about("Clear a part of the cache, also while ncspot is running")
+                        .arg(
+                            clap::Arg::new("part")
+                                .value_parser(command::CachePart::NAMES.to_vec())
+                                .default_value("all")
+                                .help("The part of the cache to clear"),
+                        ),
+                ),
+            clap::Command::new("export")
+                .about("Export the cached saved tracks and playlists, one file each")
+                .arg(
+                    clap::Arg::new("directory")
+                        .required(true)
+                        .value_name("DIRECTORY")
+                        .value_parser(PathBufValueParser::new())
+                        .help("The directory to write the files to"),
+                )
+                .arg(
+                    clap::Arg::new("format")
+                        .short('f')
+                        .long("format")
+                        .value_parser(["m3u", "csv", "json"])
+                        .default_value("m3u")
+                        .help("The format of the files"),
+                ),
+        ])
 }
 
 fn main() {
@@ -66,6 +112,20 @@ fn main() {
 
     match matches.subcommand() {
         Some(("info", _subcommand_matches)) => cli::info(),
+        Some(("cache", subcommand_matches)) => {
+            let part = subcommand_matches
+                .subcommand_matches("clear")
+                .map(|clear| clear.get_one::<String>("part").unwrap());
+            cli::cache(part.map(String::as_str));
+        }
+        Some(("export", subcommand_matches)) => {
+            let directory = subcommand_matches.get_one::<PathBuf>("directory").unwrap();
+            let format = subcommand_matches.get_one::<String>("format").unwrap();
+            if let Err(e) = cli::export(directory, format) {
+                eprintln!("{e}");
+                std::process::exit(1);
+            }
+        }
         Some((_, _)) => unreachable!(),
         None => {
             // Create the application.
@@ -6,13 +6,16 @@ use std::sync::{Arc, RwLock};
 use chrono::{DateTime, Utc};
 use log::debug;
 use rspotify::model::album::{FullAlbum, SavedAlbum, SimplifiedAlbum};
+use rspotify::model::CopyrightType;
 
 use crate::library::Library;
 use crate::model::artist::Artist;
 use crate::model::playable::Playable;
+use crate::model::seed::Seed;
 use crate::model::track::Track;
 use crate::queue::Queue;
 use crate::spotify::Spotify;
+use crate::spotify_api::{RecommendationSeeds, MAX_RECOMMENDATION_SEEDS};
 use crate::traits::{IntoBoxedViewExt, ListItem, ViewExt};
 use crate::ui::{album::AlbumView, listview::ListView};
 
@@ -23,10 +26,23 @@ pub struct Album {
     pub artists: Vec<String>,
     pub artist_ids: Vec<String>,
     pub year: String,
+    /// The release date, with a precision of a year, month or day.
+    #[serde(default)]
+    pub release_date: Option<String>,
     pub url: Option<String>,
     pub tracks: Option<Vec<Track>>,
     pub added_at: Option<DateTime<Utc>>,
-    total_tracks: Option<usize>,
+    pub total_tracks: Option<usize>,
+    #[serde(default)]
+    pub label: Option<String>,
+    /// Copyright notices, prefixed with © or ℗.
+    #[serde(default)]
+    pub copyrights: Vec<String>,
+    #[serde(default)]
+    pub popularity: Option<u32>,
+    /// The URL of the largest cover image.
+    #[serde(default)]
+    pub cover_url: Option<String>,
 }
 
 impl Album {
@@ -38,6 +54,13 @@ impl Album {
         if let Some(ref album_id) = self.id {
             let mut collected_tracks = Vec::new();
             if let Some(full_album) = spotify.api.album(album_id) {
+                // simplified albums lack the details shown in the album header
+                let details = Self::from(&full_album);
+                self.release_date = details.release_date;
+                self.label = details.label;
+                self.copyrights = details.copyrights;
+                self.popularity = details.popularity;
+
                 let mut tracks_result = Some(full_album.tracks.clone());
                 while let Some(ref tracks) = tracks_result {
                     for t in &tracks.items {
@@ -86,10 +109,15 @@ impl From<&SimplifiedAlbum> for Album {
                 .next()
                 .unwrap()
                 .into(),
+            release_date: sa.release_date.clone(),
             url: sa.id.as_ref().map(|id| id.url()),
             tracks: None,
             added_at: None,
             total_tracks: None,
+            label: None,
+            copyrights: Vec::new(),
+            popularity: None,
+            cover_url: sa.images.first().map(|i| i.url.clone()),
         }
     }
 }
@@ -114,10 +142,26 @@ impl From<&FullAlbum> for Album {
                 .filter_map(|a| a.id.as_ref().map(|id| id.id().to_string()))
                 .collect(),
             year: fa.release_date.split('-').next().unwrap().into(),
+            release_date: Some(fa.release_date.clone()),
             url: Some(fa.id.uri()),
             tracks,
             added_at: None,
             total_tracks: Some(fa.tracks.total as usize),
+            label: fa.label.clone(),
+            copyrights: fa
+                .copyrights
+                .iter()
+                .map(|c| {
+                    let symbol = match c._type {
+                        CopyrightType::Copyright => "©",
+                        CopyrightType::Performance => "℗",
+                    };
+                    let text = c.text.trim_start_matches(symbol).trim_start();
+                    format!("{symbol} {text}")
+                })
+                .collect(),
+            popularity: Some(fa.popularity),
+            cover_url: fa.images.first().map(|i| i.url.clone()),
         }
     }
 }
@@ -208,7 +252,13 @@ impl ListItem for Album {
         }
     }
 
-    fn save(&mut self, _library: &Library) {}
+    fn save(&mut self, library: &Library) {
+        if library.is_saved_album(self) {
+            library.unsave_album(self);
+        } else {
+            library.save_album(self);
+        }
+    }
 
     fn open(&self, queue: Arc<Queue>, library: Arc<Library>) -> Option<Box<dyn ViewExt>> {
         Some(AlbumView::new(queue, library, self).into_boxed_view_ext())
@@ -219,46 +269,50 @@ impl ListItem for Album {
         queue: Arc<Queue>,
         library: Arc<Library>,
     ) -> Option<Box<dyn ViewExt>> {
+        let seeds = self.recommendation_seeds(&queue)?;
+        let recommendations = queue.get_spotify().api.recommended_tracks(&seeds, &[]);
+
+        recommendations.map(|tracks| {
+            ListView::new(
+                Arc::new(RwLock::new(tracks)),
+                queue.clone(),
+                library.clone(),
+            )
+            .with_title(&format!("Similar to Album \"{}\"", self.title))
+            .into_boxed_view_ext()
+        })
+    }
+
+    fn recommendation_seeds(&mut self, queue: &Queue) -> Option<RecommendationSeeds> {
         self.load_all_tracks(queue.get_spotify());
-        const MAX_SEEDS: usize = 5;
-        let track_ids: Vec<&str> = self
+        let tracks: Vec<String> = self
             .tracks
             .as_ref()?
             .iter()
-            .filter_map(|t| t.id.as_deref())
+            .filter_map(|t| t.id.clone())
             // spotify allows at max 5 seed items, so choose 4 random tracks...
-            .choose_multiple(&mut thread_rng(), MAX_SEEDS - 1);
+            .choose_multiple(&mut thread_rng(), MAX_RECOMMENDATION_SEEDS - 1);
 
-        let artist_id: Option<String> = self
+        let artists: Vec<String> = self
             .artist_ids
             .iter()
             .cloned()
             // ...and one artist
-            .choose(&mut thread_rng());
+            .choose(&mut thread_rng())
+            .into_iter()
+            .collect();
 
-        if track_ids.is_empty() && artist_id.is_some() {
-            return None;
-        }
+        let seeds = RecommendationSeeds {
+            tracks,
+            artists,
+            ..Default::default()
+        };
+        (!seeds.is_empty()).then_some(seeds)
+    }
 
-        let spotify = queue.get_spotify();
-        let recommendations: Option<Vec<Track>> = spotify
-            .api
-            .recommendations(
-                artist_id.as_ref().map(|aid| vec![aid.as_str()]),
-                None,
-                Some(track_ids),
-            )
-            .map(|r| r.tracks)
-            .map(|tracks| tracks.iter().map(Track::from).collect());
-        recommendations.map(|tracks| {
-            ListView::new(
-                Arc::new(RwLock::new(tracks)),
-                queue.clone(),
-                library.clone(),
-            )
-            .with_title(&format!("Similar to Album \"{}\"", self.title))
-            .into_boxed_view_ext()
-        })
+    fn seed(&self) -> Option<Seed> {
+        // albums can't be seeds themselves, so use their main artist instead
+        self.artists()?.into_iter().next().map(Seed::Artist)
     }
 
     fn artists(&self) -> Option<Vec<Artist>> {
@@ -272,6 +326,15 @@ impl ListItem for Album {
     }
 
     #[inline]
+    fn exported(&self) -> Vec<Playable> {
+        self.tracks
+            .iter()
+            .flatten()
+            .cloned()
+            .map(Playable::Track)
+            .collect()
+    }
+
     fn is_saved(&self, library: &Library) -> Option<bool> {
         Some(library.is_saved_album(self))
     }
@@ -6,9 +6,11 @@ use rspotify::model::Id;
 
 use crate::library::Library;
 use crate::model::playable::Playable;
+use crate::model::seed::Seed;
 use crate::model::track::Track;
 use crate::queue::Queue;
 use crate::spotify::Spotify;
+use crate::spotify_api::RecommendationSeeds;
 use crate::traits::{IntoBoxedViewExt, ListItem, ViewExt};
 use crate::ui::{artist::ArtistView, listview::ListView};
 
@@ -19,6 +21,10 @@ pub struct Artist {
     pub url: Option<String>,
     pub tracks: Option<Vec<Track>>,
     pub is_followed: bool,
+    #[serde(default)]
+    pub followers: Option<u32>,
+    #[serde(default)]
+    pub genres: Vec<String>,
 }
 
 impl Artist {
@@ -29,6 +35,8 @@ impl Artist {
             url: None,
             tracks: None,
             is_followed: false,
+            followers: None,
+            genres: Vec::new(),
         }
     }
 
@@ -49,6 +57,8 @@ impl From<&SimplifiedArtist> for Artist {
             url: sa.id.as_ref().map(|id| id.url()),
             tracks: None,
             is_followed: false,
+            followers: None,
+            genres: Vec::new(),
         }
     }
 }
@@ -61,6 +71,8 @@ impl From<&FullArtist> for Artist {
             url: Some(fa.id.url()),
             tracks: None,
             is_followed: false,
+            followers: Some(fa.followers.total),
+            genres: fa.genres.clone(),
         }
     }
 }
@@ -154,14 +166,8 @@ impl ListItem for Artist {
         queue: Arc<Queue>,
         library: Arc<Library>,
     ) -> Option<Box<dyn ViewExt>> {
-        let id = self.id.as_ref()?.to_string();
-
-        let spotify = queue.get_spotify();
-        let recommendations: Option<Vec<Track>> = spotify
-            .api
-            .recommendations(Some(vec![&id]), None, None)
-            .map(|r| r.tracks)
-            .map(|tracks| tracks.iter().map(Track::from).collect());
+        let seeds = self.recommendation_seeds(&queue)?;
+        let recommendations = queue.get_spotify().api.recommended_tracks(&seeds, &[]);
 
         recommendations.map(|tracks| {
             ListView::new(
@@ -174,7 +180,27 @@ impl ListItem for Artist {
         })
     }
 
+    fn recommendation_seeds(&mut self, _queue: &Queue) -> Option<RecommendationSeeds> {
+        Some(RecommendationSeeds {
+            artists: vec![self.id.clone()?],
+            ..Default::default()
+        })
+    }
+
+    fn seed(&self) -> Option<Seed> {
+        Some(Seed::Artist(self.clone()))
+    }
+
     #[inline]
+    fn exported(&self) -> Vec<Playable> {
+        self.tracks
+            .iter()
+            .flatten()
+            .cloned()
+            .map(Playable::Track)
+            .collect()
+    }
+
     fn is_saved(&self, library: &Library) -> Option<bool> {
         Some(library.is_followed_artist(self))
     }
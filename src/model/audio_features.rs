@@ -0,0 +1,78 @@
+use rspotify::model::{Id, Modality};
+
+use crate::command::FeatureColumn;
+
+const PITCH_CLASSES: [&str; 12] = [
+    "C", "C♯/D♭", "D", "D♯/E♭", "E", "F", "F♯/G♭", "G", "G♯/A♭", "A", "A♯/B♭", "B",
+];
+
+/// The audio features Spotify computed for a track.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AudioFeatures {
+    pub id: String,
+    pub tempo: f32,
+    /// The pitch class of the key, or -1 if none was detected.
+    pub key: i32,
+    pub major: Option<bool>,
+    pub energy: f32,
+    pub danceability: f32,
+    pub valence: f32,
+}
+
+impl AudioFeatures {
+    /// The name of the key in standard pitch class notation, e.g. "D♯/E♭ minor".
+    pub fn key_name(&self) -> Option<String> {
+        let pitch = PITCH_CLASSES.get(usize::try_from(self.key).ok()?)?;
+        Some(match self.major {
+            Some(true) => format!("{pitch} major"),
+            Some(false) => format!("{pitch} minor"),
+            None => pitch.to_string(),
+        })
+    }
+
+    /// The value of `column` in a compact form that fits in a list column,
+    /// e.g. "128 BPM", "F♯m", "E 0.81" or "D 0.67".
+    pub fn cell(&self, column: FeatureColumn) -> String {
+        match column {
+            FeatureColumn::Tempo => format!("{:.0} BPM", self.tempo),
+            FeatureColumn::Key => usize::try_from(self.key)
+                .ok()
+                .and_then(|k| PITCH_CLASSES.get(k))
+                .and_then(|pitch| pitch.split('/').next())
+                .map(|pitch| match self.major {
+                    Some(false) => format!("{pitch}m"),
+                    _ => pitch.to_string(),
+                })
+                .unwrap_or_else(|| "-".to_string()),
+            FeatureColumn::Energy => format!("E {:.2}", self.energy),
+            FeatureColumn::Danceability => format!("D {:.2}", self.danceability),
+        }
+    }
+
+    /// The width of `column`, so that the columns of all tracks line up.
+    pub fn cell_width(column: FeatureColumn) -> usize {
+        match column {
+            FeatureColumn::Tempo => 7,
+            FeatureColumn::Key => 3,
+            FeatureColumn::Energy | FeatureColumn::Danceability => 6,
+        }
+    }
+}
+
+impl From<&rspotify::model::AudioFeatures> for AudioFeatures {
+    fn from(features: &rspotify::model::AudioFeatures) -> Self {
+        Self {
+            id: features.id.id().to_string(),
+            tempo: features.tempo,
+            key: features.key,
+            major: match features.mode {
+                Modality::Major => Some(true),
+                Modality::Minor => Some(false),
+                Modality::NoResult => None,
+            },
+            energy: features.energy,
+            danceability: features.danceability,
+            valence: features.valence,
+        }
+    }
+}
@@ -3,7 +3,7 @@ use crate::model::playable::Playable;
 use crate::queue::Queue;
 use crate::traits::{ListItem, ViewExt};
 use crate::utils::ms_to_hms;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use rspotify::model::show::{FullEpisode, SimplifiedEpisode};
 use rspotify::model::Id;
 use std::fmt;
@@ -25,6 +25,16 @@ impl Episode {
     pub fn duration_str(&self) -> String {
         ms_to_hms(self.duration)
     }
+
+    /// Parse the release date, which Spotify reports with a precision of a
+    /// year, month or day. Missing parts default to the first month or day.
+    pub fn release_date(&self) -> Option<NaiveDate> {
+        let mut parts = self.release_date.split('-');
+        let year = parts.next()?.parse().ok()?;
+        let month = parts.next().and_then(|m| m.parse().ok()).unwrap_or(1);
+        let day = parts.next().and_then(|d| d.parse().ok()).unwrap_or(1);
+        NaiveDate::from_ymd_opt(year, month, day)
+    }
 }
 
 impl From<&SimplifiedEpisode> for Episode {
@@ -98,6 +108,10 @@ impl ListItem for Episode {
         None
     }
 
+    fn playable(&self) -> Option<Playable> {
+        Some(Playable::Episode(self.clone()))
+    }
+
     #[inline]
     fn is_playable(&self) -> bool {
         true
@@ -1,8 +1,11 @@
 pub mod album;
+pub mod audio_features;
 pub mod artist;
 pub mod category;
 pub mod episode;
 pub mod playable;
 pub mod playlist;
+pub mod seed;
 pub mod show;
+pub mod snapshot;
 pub mod track;
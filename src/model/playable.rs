@@ -5,8 +5,10 @@ use crate::library::Library;
 use crate::model::album::Album;
 use crate::model::artist::Artist;
 use crate::model::episode::Episode;
+use crate::model::seed::Seed;
 use crate::model::track::Track;
 use crate::queue::Queue;
+use crate::spotify_api::RecommendationSeeds;
 use crate::traits::{ListItem, ViewExt};
 use crate::utils::ms_to_hms;
 use std::fmt;
@@ -177,12 +179,22 @@ impl ListItem for Playable {
         self.as_listitem().queue(queue)
     }
 
-    fn save(&mut self, _library: &Library) {}
+    fn save(&mut self, library: &Library) {
+        self.as_listitem().save(library)
+    }
 
     fn open(&self, queue: Arc<Queue>, library: Arc<Library>) -> Option<Box<dyn ViewExt>> {
         self.as_listitem().open(queue, library)
     }
 
+    fn recommendation_seeds(&mut self, queue: &Queue) -> Option<RecommendationSeeds> {
+        self.as_listitem().recommendation_seeds(queue)
+    }
+
+    fn seed(&self) -> Option<Seed> {
+        self.as_listitem().seed()
+    }
+
     fn album(&self, queue: &Queue) -> Option<Album> {
         self.as_listitem().album(queue)
     }
@@ -195,6 +207,10 @@ impl ListItem for Playable {
         self.as_listitem().track()
     }
 
+    fn playable(&self) -> Option<Playable> {
+        Some(self.clone())
+    }
+
     fn as_listitem(&self) -> Box<dyn ListItem> {
         self.as_listitem()
     }
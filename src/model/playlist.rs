@@ -11,6 +11,7 @@ use crate::model::playable::Playable;
 use crate::model::track::Track;
 use crate::queue::Queue;
 use crate::spotify::Spotify;
+use crate::spotify_api::{RecommendationSeeds, MAX_RECOMMENDATION_SEEDS};
 use crate::traits::{IntoBoxedViewExt, ListItem, ViewExt};
 use crate::ui::{listview::ListView, playlist::PlaylistView};
 use crate::{command::SortDirection, command::SortKey, library::Library};
@@ -54,7 +55,9 @@ impl Playlist {
         })
     }
 
-    pub fn sort(&mut self, key: &SortKey, direction: &SortDirection) {
+    /// Sort the tracks of the playlist. Sorting on audio features uses the
+    /// features cached in the `library`, tracks without them are put last.
+    pub fn sort(&mut self, key: &SortKey, direction: &SortDirection, library: &Library) {
         fn compare_artists(a: &[String], b: &[String]) -> Ordering {
             let sanitize_artists_name = |x: &[String]| -> Vec<String> {
                 x.iter()
@@ -82,20 +85,71 @@ impl Playlist {
                 .then_with(|| a.track_number.cmp(&b.track_number))
         }
 
+        // tracks without features stay last in either direction
+        fn compare_features<T: PartialOrd>(
+            a: Option<T>,
+            b: Option<T>,
+            direction: &SortDirection,
+        ) -> Ordering {
+            match (a, b) {
+                (Some(a), Some(b)) => {
+                    let ordering = a.partial_cmp(&b).unwrap_or(Ordering::Equal);
+                    match *direction {
+                        SortDirection::Ascending => ordering,
+                        SortDirection::Descending => ordering.reverse(),
+                    }
+                }
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            }
+        }
+
+        let features = library.audio_features.read().unwrap();
+        let feature = |t: &Track| t.id.as_ref().and_then(|id| features.get(id));
+
         if let Some(c) = self.tracks.as_mut() {
             c.sort_by(|a, b| match (a.track(), b.track()) {
                 (Some(a), Some(b)) => {
-                    let (a, b) = match *direction {
-                        SortDirection::Ascending => (a, b),
-                        SortDirection::Descending => (b, a),
-                    };
-                    match *key {
+                    let ordering = match *key {
                         SortKey::Title => a.title.to_lowercase().cmp(&b.title.to_lowercase()),
                         SortKey::Duration => a.duration.cmp(&b.duration),
                         SortKey::Album => compare_album(&a, &b),
                         SortKey::Added => a.added_at.cmp(&b.added_at),
                         SortKey::Artist => compare_artists(&a.artists, &b.artists)
                             .then_with(|| compare_album(&a, &b)),
+                        SortKey::Tempo => {
+                            return compare_features(
+                                feature(&a).map(|f| f.tempo),
+                                feature(&b).map(|f| f.tempo),
+                                direction,
+                            )
+                        }
+                        SortKey::Key => {
+                            return compare_features(
+                                feature(&a).map(|f| (f.key, f.major)),
+                                feature(&b).map(|f| (f.key, f.major)),
+                                direction,
+                            )
+                        }
+                        SortKey::Energy => {
+                            return compare_features(
+                                feature(&a).map(|f| f.energy),
+                                feature(&b).map(|f| f.energy),
+                                direction,
+                            )
+                        }
+                        SortKey::Danceability => {
+                            return compare_features(
+                                feature(&a).map(|f| f.danceability),
+                                feature(&b).map(|f| f.danceability),
+                                direction,
+                            )
+                        }
+                    };
+                    match *direction {
+                        SortDirection::Ascending => ordering,
+                        SortDirection::Descending => ordering.reverse(),
                     }
                 }
                 _ => std::cmp::Ordering::Equal,
@@ -211,9 +265,23 @@ impl ListItem for Playlist {
         queue: Arc<Queue>,
         library: Arc<Library>,
     ) -> Option<Box<dyn ViewExt>> {
+        let seeds = self.recommendation_seeds(&queue)?;
+        let recommendations = queue.get_spotify().api.recommended_tracks(&seeds, &[]);
+
+        recommendations.map(|tracks| {
+            ListView::new(
+                Arc::new(RwLock::new(tracks)),
+                queue.clone(),
+                library.clone(),
+            )
+            .with_title(&format!("Similar to Tracks in \"{}\"", self.name))
+            .into_boxed_view_ext()
+        })
+    }
+
+    fn recommendation_seeds(&mut self, queue: &Queue) -> Option<RecommendationSeeds> {
         self.load_tracks(queue.get_spotify());
-        const MAX_SEEDS: usize = 5;
-        let track_ids: Vec<String> = self
+        let tracks: Vec<String> = self
             .tracks
             .as_ref()?
             .iter()
@@ -222,34 +290,22 @@ impl ListItem for Playlist {
             .collect::<HashSet<_>>()
             .into_iter()
             // spotify allows at max 5 seed items, so choose them at random
-            .choose_multiple(&mut thread_rng(), MAX_SEEDS);
+            .choose_multiple(&mut thread_rng(), MAX_RECOMMENDATION_SEEDS);
 
-        if track_ids.is_empty() {
+        if tracks.is_empty() {
             return None;
         }
 
-        let spotify = queue.get_spotify();
-        let recommendations: Option<Vec<Track>> = spotify
-            .api
-            .recommendations(
-                None,
-                None,
-                Some(track_ids.iter().map(|t| t.as_ref()).collect()),
-            )
-            .map(|r| r.tracks)
-            .map(|tracks| tracks.iter().map(Track::from).collect());
-
-        recommendations.map(|tracks| {
-            ListView::new(
-                Arc::new(RwLock::new(tracks)),
-                queue.clone(),
-                library.clone(),
-            )
-            .with_title(&format!("Similar to Tracks in \"{}\"", self.name))
-            .into_boxed_view_ext()
+        Some(RecommendationSeeds {
+            tracks,
+            ..Default::default()
         })
     }
 
+    fn exported(&self) -> Vec<Playable> {
+        self.tracks.clone().unwrap_or_default()
+    }
+
     fn is_saved(&self, library: &Library) -> Option<bool> {
         // save status of personal playlists can't be toggled for safety
         if !library.is_followed_playlist(self) {
@@ -0,0 +1,198 @@
+use std::fmt;
+use std::sync::Arc;
+
+use rspotify::model::RecommendationsAttribute;
+
+use crate::command::{TuneAttribute, TuneBound};
+use crate::library::Library;
+use crate::model::artist::Artist;
+use crate::model::playable::Playable;
+use crate::model::track::Track;
+use crate::queue::Queue;
+use crate::spotify_api::RecommendationSeeds;
+use crate::traits::{ListItem, ViewExt};
+
+/// A single item that recommendations can be based on.
+#[derive(Clone, Debug)]
+pub enum Seed {
+    Track(Track),
+    Artist(Artist),
+    Genre(String),
+}
+
+impl Seed {
+    fn as_item(&self) -> Option<Box<dyn ListItem>> {
+        match self {
+            Self::Track(track) => Some(track.as_listitem()),
+            Self::Artist(artist) => Some(artist.as_listitem()),
+            Self::Genre(_) => None,
+        }
+    }
+}
+
+impl PartialEq for Seed {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Track(a), Self::Track(b)) => a.id == b.id,
+            (Self::Artist(a), Self::Artist(b)) => a.id == b.id,
+            (Self::Genre(a), Self::Genre(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl<'a> FromIterator<&'a Seed> for RecommendationSeeds {
+    fn from_iter<T: IntoIterator<Item = &'a Seed>>(iter: T) -> Self {
+        let mut seeds = Self::default();
+        for seed in iter {
+            match seed {
+                Seed::Track(track) => seeds.tracks.extend(track.id.clone()),
+                Seed::Artist(artist) => seeds.artists.extend(artist.id.clone()),
+                Seed::Genre(genre) => seeds.genres.push(genre.clone()),
+            }
+        }
+        seeds
+    }
+}
+
+impl ListItem for Seed {
+    fn is_playing(&self, queue: &Queue) -> bool {
+        match self {
+            Self::Track(track) => track.is_playing(queue),
+            _ => false,
+        }
+    }
+
+    fn display_left(&self, library: &Library) -> String {
+        match self {
+            Self::Genre(genre) => genre.clone(),
+            _ => self
+                .as_item()
+                .map(|i| i.display_left(library))
+                .unwrap_or_default(),
+        }
+    }
+
+    fn display_center(&self, _library: &Library) -> String {
+        match self {
+            Self::Track(_) => "Track",
+            Self::Artist(_) => "Artist",
+            Self::Genre(_) => "Genre",
+        }
+        .to_string()
+    }
+
+    fn display_right(&self, library: &Library) -> String {
+        let seeds = library.recommendation_seeds.read().unwrap();
+        if seeds.contains(self) { "✓" } else { "" }.to_string()
+    }
+
+    fn play(&mut self, queue: &Queue) {
+        if let Some(mut item) = self.as_item() {
+            item.play(queue);
+        }
+    }
+
+    fn play_next(&mut self, queue: &Queue) {
+        if let Some(mut item) = self.as_item() {
+            item.play_next(queue);
+        }
+    }
+
+    fn queue(&mut self, queue: &Queue) {
+        if let Some(mut item) = self.as_item() {
+            item.queue(queue);
+        }
+    }
+
+    fn save(&mut self, library: &Library) {
+        if let Some(mut item) = self.as_item() {
+            item.save(library);
+        }
+    }
+
+    fn open(&self, queue: Arc<Queue>, library: Arc<Library>) -> Option<Box<dyn ViewExt>> {
+        self.as_item()?.open(queue, library)
+    }
+
+    fn recommendation_seeds(&mut self, _queue: &Queue) -> Option<RecommendationSeeds> {
+        Some(std::iter::once(&*self).collect())
+    }
+
+    fn seed(&self) -> Option<Seed> {
+        Some(self.clone())
+    }
+
+    fn artists(&self) -> Option<Vec<Artist>> {
+        match self {
+            Self::Track(track) => track.artists(),
+            Self::Artist(artist) => Some(vec![artist.clone()]),
+            Self::Genre(_) => Some(Vec::new()),
+        }
+    }
+
+    fn track(&self) -> Option<Track> {
+        match self {
+            Self::Track(track) => Some(track.clone()),
+            _ => None,
+        }
+    }
+
+    fn playable(&self) -> Option<Playable> {
+        self.track().map(Playable::Track)
+    }
+
+    #[inline]
+    fn is_playable(&self) -> bool {
+        matches!(self, Self::Track(_))
+    }
+
+    fn as_listitem(&self) -> Box<dyn ListItem> {
+        Box::new(self.clone())
+    }
+}
+
+/// A constraint on an audio feature of recommended tracks.
+#[derive(Clone, Copy, Debug)]
+pub struct Tuning {
+    pub attribute: TuneAttribute,
+    pub bound: TuneBound,
+    pub value: f32,
+}
+
+impl From<&Tuning> for RecommendationsAttribute {
+    fn from(tuning: &Tuning) -> Self {
+        use RecommendationsAttribute::*;
+        let value = tuning.value;
+        // popularity is the only one of these measured in integers
+        let integer = value.round() as i32;
+        match (tuning.attribute, tuning.bound) {
+            (TuneAttribute::Energy, TuneBound::Min) => MinEnergy(value),
+            (TuneAttribute::Energy, TuneBound::Max) => MaxEnergy(value),
+            (TuneAttribute::Energy, TuneBound::Target) => TargetEnergy(value),
+            (TuneAttribute::Tempo, TuneBound::Min) => MinTempo(value),
+            (TuneAttribute::Tempo, TuneBound::Max) => MaxTempo(value),
+            (TuneAttribute::Tempo, TuneBound::Target) => TargetTempo(value),
+            (TuneAttribute::Valence, TuneBound::Min) => MinValence(value),
+            (TuneAttribute::Valence, TuneBound::Max) => MaxValence(value),
+            (TuneAttribute::Valence, TuneBound::Target) => TargetValence(value),
+            (TuneAttribute::Danceability, TuneBound::Min) => MinDanceability(value),
+            (TuneAttribute::Danceability, TuneBound::Max) => MaxDanceability(value),
+            (TuneAttribute::Danceability, TuneBound::Target) => TargetDanceability(value),
+            (TuneAttribute::Popularity, TuneBound::Min) => MinPopularity(integer),
+            (TuneAttribute::Popularity, TuneBound::Max) => MaxPopularity(integer),
+            (TuneAttribute::Popularity, TuneBound::Target) => TargetPopularity(integer),
+        }
+    }
+}
+
+impl fmt::Display for Tuning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let relation = match self.bound {
+            TuneBound::Min => "≥",
+            TuneBound::Max => "≤",
+            TuneBound::Target => "≈",
+        };
+        write!(f, "{} {} {}", self.attribute, relation, self.value)
+    }
+}
@@ -0,0 +1,129 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use log::error;
+
+use crate::fs::cache_path;
+use crate::library::Library;
+use crate::model::playable::Playable;
+use crate::queue::Queue;
+use crate::traits::{ListItem, ViewExt};
+use crate::utils::format_duration;
+
+/// The directory inside the cache directory that snapshots are stored in.
+const SNAPSHOT_DIRECTORY: &str = "queues";
+
+/// A named copy of the queue that is stored on disk, so that it can be
+/// restored later.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct QueueSnapshot {
+    pub name: String,
+    pub items: Vec<Playable>,
+    /// The index of the item that was playing when the snapshot was taken.
+    pub current: Option<usize>,
+    /// The playback position in the current item in milliseconds.
+    pub position: u32,
+}
+
+impl QueueSnapshot {
+    /// The path of the file the snapshot called `name` is stored in.
+    fn path(name: &str) -> Result<PathBuf, String> {
+        if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+            return Err(format!("Invalid queue name: {name}"));
+        }
+        let directory = cache_path(SNAPSHOT_DIRECTORY);
+        fs::create_dir_all(&directory).map_err(|e| e.to_string())?;
+        Ok(directory.join(format!("{name}.json")))
+    }
+
+    /// Read the snapshot called `name` from disk.
+    pub fn load(name: &str) -> Result<Self, String> {
+        let contents = fs::read_to_string(Self::path(name)?)
+            .map_err(|_| format!("No saved queue called \"{name}\""))?;
+        serde_json::from_str(&contents)
+            .map_err(|e| format!("Could not read saved queue \"{name}\": {e}"))
+    }
+
+    /// Write this snapshot to disk, replacing an earlier one with the same
+    /// name.
+    pub fn save(&self) -> Result<(), String> {
+        let contents = serde_json::to_string(self).map_err(|e| e.to_string())?;
+        fs::write(Self::path(&self.name)?, contents)
+            .map_err(|e| format!("Could not save queue \"{}\": {}", self.name, e))
+    }
+
+    /// All the snapshots on disk, sorted by name.
+    pub fn list() -> Vec<Self> {
+        let Ok(entries) = fs::read_dir(cache_path(SNAPSHOT_DIRECTORY)) else {
+            return Vec::new();
+        };
+
+        let mut snapshots: Vec<Self> = entries
+            .filter_map(|entry| fs::read_to_string(entry.ok()?.path()).ok())
+            .filter_map(|contents| {
+                serde_json::from_str(&contents)
+                    .map_err(|e| error!("can't parse saved queue: {}", e))
+                    .ok()
+            })
+            .collect();
+        snapshots.sort_by_key(|s| s.name.to_lowercase());
+        snapshots
+    }
+
+    /// The total duration of the items in this snapshot.
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs(self.items.iter().map(|p| p.duration() as u64 / 1000).sum())
+    }
+}
+
+impl ListItem for QueueSnapshot {
+    fn is_playing(&self, queue: &Queue) -> bool {
+        let uris: Vec<String> = self.items.iter().map(Playable::uri).collect();
+        let playing: Vec<String> = queue
+            .queue
+            .read()
+            .unwrap()
+            .iter()
+            .map(Playable::uri)
+            .collect();
+        !uris.is_empty() && uris == playing
+    }
+
+    fn display_left(&self, _library: &Library) -> String {
+        self.name.clone()
+    }
+
+    fn display_right(&self, _library: &Library) -> String {
+        format!(
+            "{:>4} tracks, {}",
+            self.items.len(),
+            format_duration(&self.duration())
+        )
+    }
+
+    fn play(&mut self, queue: &Queue) {
+        queue.restore_snapshot(self, true);
+    }
+
+    fn play_next(&mut self, queue: &Queue) {
+        queue.append_next(&self.items);
+    }
+
+    fn queue(&mut self, queue: &Queue) {
+        for item in self.items.iter() {
+            queue.append(item.clone());
+        }
+    }
+
+    fn save(&mut self, _library: &Library) {}
+
+    fn open(&self, _queue: Arc<Queue>, _library: Arc<Library>) -> Option<Box<dyn ViewExt>> {
+        None
+    }
+
+    fn as_listitem(&self) -> Box<dyn ListItem> {
+        Box::new(self.clone())
+    }
+}
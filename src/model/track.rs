@@ -10,8 +10,11 @@ use rspotify::model::Id;
 use crate::library::Library;
 use crate::model::album::Album;
 use crate::model::artist::Artist;
+use crate::model::audio_features::AudioFeatures;
 use crate::model::playable::Playable;
+use crate::model::seed::Seed;
 use crate::queue::Queue;
+use crate::spotify_api::RecommendationSeeds;
 use crate::traits::{IntoBoxedViewExt, ListItem, ViewExt};
 use crate::ui::listview::ListView;
 
@@ -182,7 +185,20 @@ impl ListItem for Track {
 
     fn display_right(&self, library: &Library) -> String {
         let saved = if library.is_saved_track(&Playable::Track(self.clone())) { "✓" } else { "" };
-        format!("{} {}", saved, self.duration_str())
+        let mut columns = String::new();
+        let shown = library.audio_feature_columns.read().unwrap().clone();
+        if !shown.is_empty() {
+            let features = library.track_features(self);
+            for column in shown {
+                let cell = features
+                    .as_ref()
+                    .map(|f| f.cell(column))
+                    .unwrap_or_else(|| "-".to_string());
+                let width = AudioFeatures::cell_width(column);
+                columns.push_str(&format!("{cell:>width$}  "));
+            }
+        }
+        format!("{}{} {}", columns, saved, self.duration_str())
     }
 
     fn play(&mut self, queue: &Queue) {
@@ -198,7 +214,13 @@ impl ListItem for Track {
         queue.append(Playable::Track(self.clone()));
     }
 
-    fn save(&mut self, _library: &Library) {}
+    fn save(&mut self, library: &Library) {
+        if library.is_saved_track(&Playable::Track(self.clone())) {
+            library.unsave_track(self);
+        } else {
+            library.save_track(self);
+        }
+    }
 
     fn open(&self, _queue: Arc<Queue>, _library: Arc<Library>) -> Option<Box<dyn ViewExt>> {
         None
@@ -209,17 +231,8 @@ impl ListItem for Track {
         queue: Arc<Queue>,
         library: Arc<Library>,
     ) -> Option<Box<dyn ViewExt>> {
-        let spotify = queue.get_spotify();
-
-        let recommendations: Option<Vec<Self>> = if let Some(id) = &self.id {
-            spotify
-                .api
-                .recommendations(None, None, Some(vec![id]))
-                .map(|r| r.tracks)
-                .map(|tracks| tracks.iter().map(Self::from).collect())
-        } else {
-            None
-        };
+        let seeds = self.recommendation_seeds(&queue)?;
+        let recommendations = queue.get_spotify().api.recommended_tracks(&seeds, &[]);
 
         recommendations.map(|tracks| {
             ListView::new(
@@ -236,6 +249,17 @@ impl ListItem for Track {
         })
     }
 
+    fn recommendation_seeds(&mut self, _queue: &Queue) -> Option<RecommendationSeeds> {
+        Some(RecommendationSeeds {
+            tracks: vec![self.id.clone()?],
+            ..Default::default()
+        })
+    }
+
+    fn seed(&self) -> Option<Seed> {
+        Some(Seed::Track(self.clone()))
+    }
+
     fn album(&self, queue: &Queue) -> Option<Album> {
         let spotify = queue.get_spotify();
 
@@ -259,6 +283,10 @@ impl ListItem for Track {
         Some(self.clone())
     }
 
+    fn playable(&self) -> Option<Playable> {
+        Some(Playable::Track(self.clone()))
+    }
+
     #[inline]
     fn is_saved(&self, library: &Library) -> Option<bool> {
         Some(library.is_saved_track(&Playable::Track(self.clone())))
@@ -0,0 +1,130 @@
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+use cursive::CbSink;
+use log::{debug, error};
+use notify_rust::Notification;
+
+use crate::application::send_command;
+use crate::command::Command;
+use crate::library::Library;
+use crate::model::playable::Playable;
+use crate::spotify::PlayerEvent;
+
+/// Shows a desktop notification whenever another track starts playing. Every
+/// notification replaces the previous one and has buttons to skip the track or
+/// to save it.
+pub struct Notifier {
+    cb_sink: CbSink,
+    library: Arc<Library>,
+    /// The URI of the item the last notification was shown for.
+    track: Option<String>,
+    /// The id of the last notification, which is replaced by the next one.
+    id: Arc<AtomicU32>,
+    /// Increased for every notification. Replaced notifications keep their id,
+    /// so this is used to only act on the buttons of the newest one.
+    generation: Arc<AtomicU64>,
+}
+
+impl Notifier {
+    pub fn new(cb_sink: CbSink, library: Arc<Library>) -> Self {
+        Self {
+            cb_sink,
+            library,
+            track: None,
+            id: Arc::new(AtomicU32::new(0)),
+            generation: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Show a notification if `event` starts another item. `playable` is the
+    /// current item of the queue.
+    pub fn player_event(&mut self, event: &PlayerEvent, playable: Option<&Playable>) {
+        match (event, playable) {
+            (PlayerEvent::Playing(_), Some(playable)) => {
+                let uri = playable.uri();
+                if self.track.as_ref() != Some(&uri) {
+                    self.track = Some(uri);
+                    self.notify(playable);
+                }
+            }
+            (PlayerEvent::Stopped, _) => self.track = None,
+            _ => {}
+        }
+    }
+
+    fn notify(&self, playable: &Playable) {
+        let (summary, body) = match playable {
+            Playable::Track(track) => {
+                let mut body = track.artists.join(", ");
+                if let Some(album) = &track.album {
+                    body.push('\n');
+                    body.push_str(album);
+                }
+                (track.title.clone(), body)
+            }
+            Playable::Episode(episode) => (episode.name.clone(), String::new()),
+        };
+        // episodes can't be saved to the library
+        let unsaved = match playable {
+            Playable::Track(track) if !self.library.is_saved_track(playable) => Some(track.clone()),
+            _ => None,
+        };
+        let library = self.library.clone();
+
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        let newest = self.generation.clone();
+        let id = self.id.clone();
+        let cb_sink = self.cb_sink.clone();
+
+        // Showing the notification and waiting for its buttons blocks, so it
+        // is done on its own thread.
+        thread::spawn(move || {
+            let mut notification = Notification::new();
+            notification
+                .appname("ncspot")
+                .summary(&summary)
+                .body(&body)
+                .action("next", "Next");
+            if unsaved.is_some() {
+                notification.action("save", "Save");
+            }
+            let previous = id.load(Ordering::SeqCst);
+            if previous != 0 {
+                notification.id(previous);
+            }
+
+            let handle = match notification.show() {
+                Ok(handle) => handle,
+                Err(e) => {
+                    error!("could not show notification: {}", e);
+                    return;
+                }
+            };
+            id.store(handle.id(), Ordering::SeqCst);
+
+            handle.wait_for_action(move |action| {
+                if newest.load(Ordering::SeqCst) != generation {
+                    return;
+                }
+                debug!("notification action {}", action);
+                let command = match (action, &unsaved) {
+                    ("next", _) => Command::Next,
+                    // saved directly instead of with the save command, which
+                    // removes tracks that were saved in the meantime
+                    ("save", Some(track)) => {
+                        if !library.is_saved_track(&Playable::Track(track.clone())) {
+                            library.save_track(track);
+                        }
+                        return;
+                    }
+                    _ => return,
+                };
+                cb_sink
+                    .send(Box::new(move |siv| send_command(siv, command)))
+                    .unwrap_or_else(|e| error!("could not send notification action: {}", e));
+            });
+        });
+    }
+}
@@ -0,0 +1,275 @@
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::process::{Child, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{bounded, Receiver, Sender, TrySendError};
+use librespot_playback::audio_backend::{Sink, SinkBuilder, SinkError, SinkResult};
+use librespot_playback::config::AudioFormat;
+use librespot_playback::convert::Converter;
+use librespot_playback::decoder::AudioPacket;
+use librespot_playback::{NUM_CHANNELS, SAMPLE_RATE};
+use log::{error, info, warn};
+
+use crate::hooks::shell;
+
+/// How many packets may wait for the writer. When playing alongside the audio
+/// backend, packets are dropped rather than holding up playback if the reader
+/// can't keep up.
+const QUEUE_LENGTH: usize = 64;
+
+/// How long to wait before opening the output again after it failed.
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// How far the output may run ahead of real time when it replaces the audio
+/// backend, which otherwise paces playback.
+const MAX_AHEAD: Duration = Duration::from_millis(500);
+
+/// The sample formats raw audio can be written in. Samples are little endian
+/// and interleaved, always at 44.1 kHz in stereo.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PcmFormat {
+    #[default]
+    S16,
+    S32,
+    F32,
+}
+
+impl fmt::Display for PcmFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Self::S16 => "S16_LE",
+            Self::S32 => "S32_LE",
+            Self::F32 => "F32_LE",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// The `[pcm]` section of the configuration file. Raw audio is written to a
+/// named pipe if `fifo` is set, or else to the standard input of `command`.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct PcmConfig {
+    pub fifo: Option<String>,
+    pub command: Option<String>,
+    pub format: Option<PcmFormat>,
+    /// Replace the audio backend instead of playing alongside it.
+    #[serde(default)]
+    pub exclusive: bool,
+}
+
+/// Where raw audio is written to.
+#[derive(Clone, Debug)]
+enum Target {
+    Fifo(String),
+    Command(String),
+}
+
+impl PcmConfig {
+    fn target(&self) -> Option<Target> {
+        match (&self.fifo, &self.command) {
+            (Some(fifo), _) => Some(Target::Fifo(fifo.clone())),
+            (None, Some(command)) => Some(Target::Command(command.clone())),
+            (None, None) => None,
+        }
+    }
+}
+
+impl fmt::Display for PcmConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let target = match self.target() {
+            Some(Target::Fifo(path)) => format!("fifo {path}"),
+            Some(Target::Command(command)) => format!("command \"{command}\""),
+            None => return write!(f, "disabled"),
+        };
+        let mode = if self.exclusive {
+            "instead of the audio backend"
+        } else {
+            "alongside the audio backend"
+        };
+        write!(
+            f,
+            "{} {} {} Hz {} channels, {}",
+            target,
+            self.format.unwrap_or_default(),
+            SAMPLE_RATE,
+            NUM_CHANNELS,
+            mode
+        )
+    }
+}
+
+/// The open output, which is opened again when it fails.
+enum Output {
+    Fifo(File),
+    Command(Child),
+}
+
+impl Output {
+    fn open(target: &Target) -> std::io::Result<Self> {
+        match target {
+            // Blocks until the FIFO is opened for reading.
+            Target::Fifo(path) => OpenOptions::new().write(true).open(path).map(Self::Fifo),
+            Target::Command(command) => shell(command)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn()
+                .map(Self::Command),
+        }
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+        match self {
+            Self::Fifo(file) => file.write_all(bytes),
+            Self::Command(child) => match child.stdin.as_mut() {
+                Some(stdin) => stdin.write_all(bytes),
+                None => Err(std::io::ErrorKind::BrokenPipe.into()),
+            },
+        }
+    }
+}
+
+impl Drop for Output {
+    fn drop(&mut self) {
+        if let Self::Command(child) = self {
+            drop(child.stdin.take());
+            if let Err(e) = child.kill().and_then(|_| child.wait()) {
+                warn!("could not stop pcm command: {}", e);
+            }
+        }
+    }
+}
+
+/// Writes the queued audio to `target` until the sink is dropped.
+fn writer(target: Target, packets: Receiver<Vec<u8>>) {
+    let mut output: Option<Output> = None;
+    for bytes in packets {
+        if output.is_none() {
+            match Output::open(&target) {
+                Ok(opened) => {
+                    info!("opened pcm output {:?}", target);
+                    output = Some(opened);
+                }
+                Err(e) => {
+                    error!("could not open pcm output {:?}: {}", target, e);
+                    thread::sleep(RETRY_DELAY);
+                    continue;
+                }
+            }
+        }
+
+        if let Some(Err(e)) = output.as_mut().map(|o| o.write(&bytes)) {
+            warn!("pcm output {:?} closed: {}", target, e);
+            output = None;
+        }
+    }
+}
+
+/// A sink that writes the decoded audio to a named pipe or a command, and
+/// passes it on to the sink of the audio backend unless it replaces it.
+pub struct PcmSink {
+    sink: Option<Box<dyn Sink>>,
+    format: PcmFormat,
+    packets: Sender<Vec<u8>>,
+    /// When playback started and how much audio was written since, to keep
+    /// the output in real time when there's no audio backend.
+    started: Instant,
+    frames: u64,
+}
+
+impl PcmSink {
+    /// Open the sink of the audio `backend`, wrapped in the raw audio output
+    /// if `config` enables it.
+    pub fn open(backend: SinkBuilder, config: Option<&PcmConfig>) -> Box<dyn Sink> {
+        let open_backend = || backend(None, AudioFormat::default());
+        let Some((config, target)) = config.and_then(|c| Some((c, c.target()?))) else {
+            return open_backend();
+        };
+        info!("writing pcm to {}", config);
+
+        let (tx, rx) = bounded(QUEUE_LENGTH);
+        thread::Builder::new()
+            .name("pcm output".into())
+            .spawn(move || writer(target, rx))
+            .expect("could not start pcm output thread");
+
+        Box::new(Self {
+            sink: (!config.exclusive).then(open_backend),
+            format: config.format.unwrap_or_default(),
+            packets: tx,
+            started: Instant::now(),
+            frames: 0,
+        })
+    }
+
+    fn convert(&self, samples: &[f64], converter: &mut Converter) -> Vec<u8> {
+        match self.format {
+            PcmFormat::S16 => converter
+                .f64_to_s16(samples)
+                .iter()
+                .flat_map(|s| s.to_le_bytes())
+                .collect(),
+            PcmFormat::S32 => converter
+                .f64_to_s32(samples)
+                .iter()
+                .flat_map(|s| s.to_le_bytes())
+                .collect(),
+            PcmFormat::F32 => converter
+                .f64_to_f32(samples)
+                .iter()
+                .flat_map(|s| s.to_le_bytes())
+                .collect(),
+        }
+    }
+
+    /// Wait until real time catches up with the audio that was written.
+    fn pace(&mut self, samples: usize) {
+        self.frames += (samples / NUM_CHANNELS as usize) as u64;
+        let written = Duration::from_secs_f64(self.frames as f64 / SAMPLE_RATE as f64);
+        let elapsed = self.started.elapsed();
+        if written > elapsed + MAX_AHEAD {
+            thread::sleep(written - elapsed - MAX_AHEAD);
+        } else if elapsed > written + MAX_AHEAD {
+            // The output was blocked, don't rush to catch up.
+            self.started = Instant::now();
+            self.frames = 0;
+        }
+    }
+}
+
+impl Sink for PcmSink {
+    fn start(&mut self) -> SinkResult<()> {
+        self.started = Instant::now();
+        self.frames = 0;
+        self.sink.as_mut().map_or(Ok(()), |sink| sink.start())
+    }
+
+    fn stop(&mut self) -> SinkResult<()> {
+        self.sink.as_mut().map_or(Ok(()), |sink| sink.stop())
+    }
+
+    fn write(&mut self, packet: AudioPacket, converter: &mut Converter) -> SinkResult<()> {
+        if let Ok(samples) = packet.samples() {
+            let bytes = self.convert(samples, converter);
+            if self.sink.is_some() {
+                if let Err(TrySendError::Disconnected(_)) = self.packets.try_send(bytes) {
+                    return Err(SinkError::NotConnected("pcm output stopped".into()));
+                }
+            } else {
+                self.packets
+                    .send(bytes)
+                    .map_err(|_| SinkError::NotConnected("pcm output stopped".into()))?;
+                self.pace(samples.len());
+            }
+        }
+
+        match self.sink.as_mut() {
+            Some(sink) => sink.write(packet, converter),
+            None => Ok(()),
+        }
+    }
+}
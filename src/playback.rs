@@ -0,0 +1,148 @@
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::time::Duration;
+
+use librespot_playback::config::{Bitrate, NormalisationType, PlayerConfig};
+use librespot_playback::mixer::softmixer::SoftMixer;
+use librespot_playback::mixer::{self, Mixer, MixerConfig};
+use log::{error, info, warn};
+
+/// How the loudness of tracks is normalised.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NormalisationMode {
+    /// Every track plays at the same loudness.
+    Track,
+    /// Albums play at the same loudness, keeping the differences between
+    /// their tracks.
+    Album,
+}
+
+impl fmt::Display for NormalisationMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Track => write!(f, "track"),
+            Self::Album => write!(f, "album"),
+        }
+    }
+}
+
+impl From<NormalisationMode> for NormalisationType {
+    fn from(mode: NormalisationMode) -> Self {
+        match mode {
+            NormalisationMode::Track => Self::Track,
+            NormalisationMode::Album => Self::Album,
+        }
+    }
+}
+
+/// The `[playback]` section of the configuration file.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct PlaybackConfig {
+    /// Normalise the loudness of tracks, off if not set.
+    pub normalisation: Option<NormalisationMode>,
+    /// The gain applied on top of normalisation, in dB.
+    pub normalisation_pregain: Option<f64>,
+    /// The mixer the volume is set with, `softvol` or `alsa`.
+    pub mixer: Option<String>,
+    /// The device and control of the hardware mixer.
+    pub mixer_device: Option<String>,
+    pub mixer_control: Option<String>,
+    pub mixer_index: Option<u32>,
+    /// How long the volume is faded on pause, resume and stop, in
+    /// milliseconds.
+    pub volume_ramp: Option<u64>,
+}
+
+impl PlaybackConfig {
+    pub fn player_config(&self) -> PlayerConfig {
+        let defaults = PlayerConfig::default();
+        PlayerConfig {
+            bitrate: Bitrate::Bitrate320,
+            normalisation: self.normalisation.is_some(),
+            normalisation_type: self
+                .normalisation
+                .map(NormalisationType::from)
+                .unwrap_or(defaults.normalisation_type),
+            normalisation_pregain_db: self
+                .normalisation_pregain
+                .unwrap_or(defaults.normalisation_pregain_db),
+            ..defaults
+        }
+    }
+
+    fn mixer_config(&self) -> MixerConfig {
+        let defaults = MixerConfig::default();
+        MixerConfig {
+            device: self.mixer_device.clone().unwrap_or(defaults.device),
+            control: self.mixer_control.clone().unwrap_or(defaults.control),
+            index: self.mixer_index.unwrap_or(defaults.index),
+            ..defaults
+        }
+    }
+
+    /// Open the configured mixer, falling back to the software mixer if it
+    /// isn't available or can't be opened.
+    pub fn open_mixer(&self) -> Box<dyn Mixer> {
+        let name = self.mixer.as_deref().unwrap_or(SoftMixer::NAME);
+        let softvol = || {
+            let create_mixer =
+                mixer::find(Some(SoftMixer::NAME)).expect("could not create softvol mixer");
+            create_mixer(MixerConfig::default())
+        };
+        if name == SoftMixer::NAME {
+            return softvol();
+        }
+
+        let Some(create_mixer) = mixer::find(Some(name)) else {
+            warn!("mixer {} is not available, using {}", name, SoftMixer::NAME);
+            return softvol();
+        };
+        let config = self.mixer_config();
+        info!("opening mixer {} with control {}", name, config.control);
+        // librespot panics if the device or control can't be found.
+        match panic::catch_unwind(AssertUnwindSafe(|| create_mixer(config))) {
+            Ok(mixer) => mixer,
+            Err(_) => {
+                error!("could not open mixer {}, using {}", name, SoftMixer::NAME);
+                softvol()
+            }
+        }
+    }
+
+    /// How long the volume is faded on pause, resume and stop, if at all.
+    pub fn volume_ramp(&self) -> Option<Duration> {
+        self.volume_ramp
+            .filter(|ms| *ms > 0)
+            .map(Duration::from_millis)
+    }
+}
+
+impl fmt::Display for PlaybackConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.normalisation {
+            Some(mode) => write!(
+                f,
+                "normalisation {} {:+} dB",
+                mode,
+                self.normalisation_pregain.unwrap_or_default()
+            )?,
+            None => write!(f, "normalisation off")?,
+        }
+        match self.mixer.as_deref() {
+            Some(name) if name != SoftMixer::NAME => {
+                let config = self.mixer_config();
+                write!(
+                    f,
+                    ", mixer {} {} {},{}",
+                    name, config.device, config.control, config.index
+                )?;
+            }
+            _ => write!(f, ", mixer {}", SoftMixer::NAME)?,
+        }
+        match self.volume_ramp() {
+            Some(ramp) => write!(f, ", volume ramp {} ms", ramp.as_millis()),
+            None => write!(f, ", no volume ramp"),
+        }
+    }
+}
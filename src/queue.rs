@@ -1,14 +1,46 @@
 use std::cmp::Ordering;
+use std::collections::{HashSet, VecDeque};
 use std::sync::atomic::{AtomicBool,Ordering as AtomicOrdering};
 use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
 
 use log::{debug, info};
 
 use rand::prelude::*;
 
+use crate::application::ASYNC_RUNTIME;
+use crate::events::{Event, EventManager};
 use crate::model::playable::Playable;
+use crate::model::snapshot::QueueSnapshot;
+use crate::model::track::Track;
 use crate::spotify::PlayerEvent;
 use crate::spotify::Spotify;
+use crate::spotify_api::{RecommendationSeeds, MAX_RECOMMENDATION_SEEDS};
+
+/// The amount of played items that are remembered to seed and deduplicate
+/// radio recommendations.
+const HISTORY_SIZE: usize = 50;
+
+/// The amount of recently played tracks that are used as radio seeds. The
+/// remaining seeds are filled with their artists.
+const RADIO_TRACK_SEEDS: usize = 3;
+
+/// The amount of queue states that can be restored with undo.
+const UNDO_SIZE: usize = 50;
+
+/// Changes to the queue that follow each other within this interval are
+/// undone as a whole, e.g. queueing every track of a playlist or dragging an
+/// item across the queue.
+const UNDO_GROUP_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A snapshot of the contents of the [Queue] that can be restored with undo
+/// and redo.
+#[derive(Clone)]
+struct QueueState {
+    items: Vec<Playable>,
+    current: Option<usize>,
+    random_order: Option<Vec<usize>>,
+}
 
 /// Repeat behavior for the [Queue].
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
@@ -19,11 +51,17 @@ pub enum RepeatSetting {
 }
 
 /// Events that are specific to the [Queue].
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug)]
 pub enum QueueEvent {
     /// Request the player to 'preload' a track, basically making sure that
     /// transitions between tracks can be uninterrupted.
     PreloadTrackRequest,
+    /// Radio recommendations were fetched in the background to extend the
+    /// queue.
+    Recommendations(Vec<Track>),
+    /// The recommendations of a newly started radio were fetched in the
+    /// background, they replace the queue.
+    Radio(Vec<Track>),
 }
 
 /// The queue determines the playback order of
@@ -37,19 +75,44 @@ pub struct Queue {
     random_order: RwLock<Option<Vec<usize>>>,
     current_track: RwLock<Option<usize>>,
     spotify: Spotify,
+    events: EventManager,
     shuffle: Arc<AtomicBool>,
     repeat: RwLock<RepeatSetting>,
+    /// Whether recommendations are appended when the end of the queue is
+    /// reached.
+    radio: Arc<AtomicBool>,
+    /// Whether radio recommendations are being fetched.
+    radio_fetching: Arc<AtomicBool>,
+    /// Whether the first of the recommendations being fetched is played once
+    /// they arrive.
+    radio_play_pending: Arc<AtomicBool>,
+    /// The most recently played items, newest last.
+    history: RwLock<VecDeque<Playable>>,
+    /// Previous states of the queue, newest last.
+    undo: RwLock<VecDeque<QueueState>>,
+    /// States that were undone, newest last.
+    redo: RwLock<Vec<QueueState>>,
+    /// When the queue was last changed, to group quick successive changes.
+    last_change: RwLock<Option<Instant>>,
 }
 
 impl Queue {
-    pub fn new(spotify: Spotify) -> Self {
+    pub fn new(spotify: Spotify, events: EventManager) -> Self {
         let queue = Self {
             queue: Arc::new(RwLock::new(Vec::new())),
             spotify: spotify.clone(),
+            events,
             current_track: RwLock::new(None),
             random_order: RwLock::new(None),
             shuffle: Arc::new(AtomicBool::new(false)),
             repeat: RwLock::new(RepeatSetting::None),
+            radio: Arc::new(AtomicBool::new(false)),
+            radio_fetching: Arc::new(AtomicBool::new(false)),
+            radio_play_pending: Arc::new(AtomicBool::new(false)),
+            history: RwLock::new(VecDeque::with_capacity(HISTORY_SIZE)),
+            undo: RwLock::new(VecDeque::with_capacity(UNDO_SIZE)),
+            redo: RwLock::new(Vec::new()),
+            last_change: RwLock::new(None),
         };
 
         queue
@@ -119,6 +182,7 @@ impl Queue {
     /// Insert `track` as the item that should logically follow the currently
     /// playing item, taking into account shuffle status.
     pub fn insert_after_current(&self, track: Playable) {
+        self.save_state();
         if let Some(index) = self.get_current_index() {
             let mut random_order = self.random_order.write().unwrap();
             if let Some(order) = random_order.as_mut() {
@@ -139,8 +203,53 @@ impl Queue {
         }
     }
 
+    /// Insert `tracks` after the last item of the album that is currently
+    /// playing, or at the end of the queue if nothing is playing. Returns the
+    /// index of the first inserted item.
+    pub fn insert_after_current_album(&self, tracks: &[Playable]) -> usize {
+        self.save_state();
+        let mut q = self.queue.write().unwrap();
+
+        let position = match *self.current_track.read().unwrap() {
+            Some(current) => {
+                let album_id = |p: &Playable| match p {
+                    Playable::Track(track) => track.album_id.clone(),
+                    Playable::Episode(_) => None,
+                };
+                let current_album = q.get(current).and_then(album_id);
+                let album_length = q[current..]
+                    .iter()
+                    .take_while(|p| current_album.is_some() && album_id(p) == current_album)
+                    .count();
+                current + album_length.max(1)
+            }
+            None => q.len(),
+        };
+
+        if let Some(order) = self.random_order.write().unwrap().as_mut() {
+            // play the inserted items right after the album, in their order
+            let after = order
+                .iter()
+                .position(|&i| i == position.saturating_sub(1))
+                .map_or(order.len(), |p| p + 1);
+            for item in order.iter_mut() {
+                if *item >= position {
+                    *item += tracks.len();
+                }
+            }
+            order.splice(after..after, position..position + tracks.len());
+        }
+
+        for (offset, track) in tracks.iter().enumerate() {
+            q.insert(position + offset, track.clone());
+        }
+
+        position
+    }
+
     /// Add `track` to the end of the queue.
     pub fn append(&self, track: Playable) {
+        self.save_state();
         let mut random_order = self.random_order.write().unwrap();
         if let Some(order) = random_order.as_mut() {
             let index = order.len().saturating_sub(1);
@@ -154,6 +263,7 @@ impl Queue {
     /// Append `tracks` after the currently playing item, taking into account
     /// shuffle status. Returns the amount of added items.
     pub fn append_next(&self, tracks: &Vec<Playable>) -> usize {
+        self.save_state();
         let mut q = self.queue.write().unwrap();
 
         {
@@ -180,6 +290,7 @@ impl Queue {
     /// Remove the item at `index`. This doesn't take into account shuffle
     /// status, and will literally remove the item at `index` in `self.queue`.
     pub fn remove(&self, index: usize) {
+        self.save_state();
         {
             let mut q = self.queue.write().unwrap();
             if q.len() == 0 {
@@ -230,6 +341,7 @@ impl Queue {
 
     /// Clear all the items from the queue and stop playback.
     pub fn clear(&self) {
+        self.save_state();
         self.stop();
 
         let mut q = self.queue.write().unwrap();
@@ -248,6 +360,7 @@ impl Queue {
 
     /// Shift the item at `from` in `self.queue` to `to`.
     pub fn shift(&self, from: usize, to: usize) {
+        self.save_state();
         let mut queue = self.queue.write().unwrap();
         let item = queue.remove(from);
         queue.insert(to, item);
@@ -258,14 +371,134 @@ impl Queue {
         if let Some(index) = *current {
             if index == from {
                 current.replace(to);
-            } else if index == to && from > index {
-                current.replace(to + 1);
-            } else if index == to && from < index {
-                current.replace(to - 1);
+            } else if from < index && index <= to {
+                current.replace(index - 1);
+            } else if to <= index && index < from {
+                current.replace(index + 1);
             }
         }
     }
 
+    /// The current contents of the queue.
+    fn state(&self) -> QueueState {
+        QueueState {
+            items: self.queue.read().unwrap().clone(),
+            current: self.get_current_index(),
+            random_order: self.get_random_order(),
+        }
+    }
+
+    /// Remember the current state of the queue before it is changed, so that
+    /// the change can be undone. Changes in quick succession are grouped into
+    /// a single undo step.
+    fn save_state(&self) {
+        let now = Instant::now();
+        let last_change = self.last_change.write().unwrap().replace(now);
+        if last_change.is_some_and(|last| now.duration_since(last) < UNDO_GROUP_INTERVAL) {
+            return;
+        }
+
+        let mut undo = self.undo.write().unwrap();
+        if undo.len() == UNDO_SIZE {
+            undo.pop_front();
+        }
+        undo.push_back(self.state());
+        self.redo.write().unwrap().clear();
+    }
+
+    /// Replace the contents of the queue with `state`. The currently playing
+    /// item keeps playing if it is part of `state`.
+    fn restore(&self, state: QueueState) {
+        let playing = self.get_current().map(|p| p.uri());
+        let is_playing = |index: &usize| state.items.get(*index).map(|p| p.uri()) == playing;
+        let current = match playing {
+            Some(_) => state
+                .current
+                .filter(is_playing)
+                .or_else(|| (0..state.items.len()).find(is_playing)),
+            None => None,
+        };
+
+        let random_order = match state.random_order {
+            Some(order) if self.get_shuffle() && order.len() == state.items.len() => Some(order),
+            _ => None,
+        };
+        let reshuffle = self.get_shuffle() && random_order.is_none();
+
+        *self.queue.write().unwrap() = state.items;
+        *self.current_track.write().unwrap() = current;
+        *self.random_order.write().unwrap() = random_order;
+        *self.last_change.write().unwrap() = None;
+
+        if reshuffle {
+            self.generate_random_order();
+        }
+    }
+
+    /// Restore the state of the queue before the last change. Returns whether
+    /// there was a change to undo.
+    pub fn undo(&self) -> bool {
+        let Some(state) = self.undo.write().unwrap().pop_back() else {
+            return false;
+        };
+        self.redo.write().unwrap().push(self.state());
+        self.restore(state);
+        true
+    }
+
+    /// Restore the state of the queue before the last undo. Returns whether
+    /// there was an undo to revert.
+    pub fn redo(&self) -> bool {
+        let Some(state) = self.redo.write().unwrap().pop() else {
+            return false;
+        };
+        self.undo.write().unwrap().push_back(self.state());
+        self.restore(state);
+        true
+    }
+
+    /// A snapshot called `name` of the items in the queue and the playback
+    /// position.
+    pub fn snapshot(&self, name: &str) -> QueueSnapshot {
+        let current = self.get_current_index();
+        QueueSnapshot {
+            name: name.to_string(),
+            items: self.queue.read().unwrap().clone(),
+            current,
+            position: match current {
+                Some(_) => self.spotify.get_current_progress().as_millis() as u32,
+                None => 0,
+            },
+        }
+    }
+
+    /// Replace the items in the queue with those of `snapshot` and continue
+    /// at the position the snapshot was taken at.
+    pub fn restore_snapshot(&self, snapshot: &QueueSnapshot, start_playing: bool) {
+        self.save_state();
+        let current = snapshot.current.filter(|i| *i < snapshot.items.len());
+
+        *self.queue.write().unwrap() = snapshot.items.clone();
+        *self.current_track.write().unwrap() = current;
+        *self.last_change.write().unwrap() = None;
+        if self.get_shuffle() {
+            self.generate_random_order();
+        }
+
+        match current.and_then(|i| snapshot.items.get(i)) {
+            Some(item) => {
+                self.spotify.load(item, start_playing, snapshot.position);
+                self.spotify.update_track();
+                // the player can start a reloaded track from the beginning
+                // regardless of the requested position, so seek explicitly
+                if snapshot.position > 0 {
+                    self.spotify.seek(snapshot.position);
+                }
+            }
+            None => self.stop(),
+        }
+    }
+
     /// Play the item at `index` in `self.queue`.
     ///
     /// `reshuffle`: Reshuffle the current order of the queue.
@@ -284,6 +517,12 @@ impl Queue {
             let mut current = self.current_track.write().unwrap();
             current.replace(index);
             self.spotify.update_track();
+
+            let mut history = self.history.write().unwrap();
+            if history.len() == HISTORY_SIZE {
+                history.pop_front();
+            }
+            history.push_back((*track).clone());
         }
 
         if reshuffle && self.get_shuffle() {
@@ -299,7 +538,9 @@ impl Queue {
                 self.spotify.toggleplayback();
             }
             PlayerEvent::Stopped => match self.next_index() {
-                Some(_) => self.next(false),
+                Some(_) => {
+                    self.next(false);
+                }
                 None => self.play(0, false, false),
             },
             _ => (),
@@ -313,13 +554,14 @@ impl Queue {
         self.spotify.stop();
     }
 
-    /// Play the next song in the queue.
+    /// Play the next song in the queue. Returns whether anything is played,
+    /// playback is stopped when the end of the queue is reached.
     ///
     /// `manual`: If this is true, normal queue logic like repeat will not be
     /// used, and the next track will actually be played. This should be used
     /// when going to the next entry in the queue is the wanted behavior.
-    pub fn next(&self, manual: bool) {
-        let q = self.queue.read().unwrap();
+    pub fn next(&self, manual: bool) -> bool {
+        let queue_length = self.queue.read().unwrap().len();
         let current = *self.current_track.read().unwrap();
         let repeat = self.get_repeat();
 
@@ -332,16 +574,24 @@ impl Queue {
             if repeat == RepeatSetting::RepeatTrack && manual {
                 self.set_repeat(RepeatSetting::RepeatPlaylist);
             }
-        } else if repeat == RepeatSetting::RepeatPlaylist && q.len() > 0 {
-            let random_order = self.random_order.read().unwrap();
-            self.play(
-                random_order.as_ref().map(|o| o[0]).unwrap_or(0),
-                false,
-                false,
-            );
+        } else if repeat == RepeatSetting::RepeatPlaylist && queue_length > 0 {
+            let first = self
+                .random_order
+                .read()
+                .unwrap()
+                .as_ref()
+                .map(|o| o[0])
+                .unwrap_or(0);
+            self.play(first, false, false);
+        } else if self.get_radio() {
+            // playback continues once the recommendations are fetched
+            self.spotify.stop();
+            self.fetch_recommendations(true);
         } else {
             self.spotify.stop();
+            return false;
         }
+        true
     }
 
     /// Play the previous item in the queue.
@@ -386,6 +636,106 @@ impl Queue {
         *repeat = new;
     }
 
+    /// Get whether recommendations are appended at the end of the queue.
+    pub fn get_radio(&self) -> bool {
+        self.radio.load(AtomicOrdering::Relaxed)
+    }
+
+    /// Set whether recommendations are appended at the end of the queue.
+    pub fn set_radio(&self, new: bool) {
+        self.radio.store(new, AtomicOrdering::Relaxed);
+    }
+
+    /// Fetch recommendations based on `seeds` in the background. Once they
+    /// arrive they replace the queue, start playing and the queue keeps being
+    /// extended when it runs out.
+    pub fn start_radio(&self, seeds: RecommendationSeeds) {
+        let spotify = self.spotify.clone();
+        let events = self.events.clone();
+        ASYNC_RUNTIME.get().unwrap().spawn_blocking(move || {
+            let tracks = spotify.api.recommended_tracks(&seeds, &[]);
+            events.send(Event::Queue(QueueEvent::Radio(tracks.unwrap_or_default())));
+        });
+    }
+
+    /// Seeds for the radio, based on the most recently played items.
+    fn history_seeds(&self) -> RecommendationSeeds {
+        let history = self.history.read().unwrap();
+        let recent = history.iter().rev().filter_map(|p| match p {
+            Playable::Track(track) => Some(track),
+            Playable::Episode(_) => None,
+        });
+
+        let mut seeds = RecommendationSeeds::default();
+        for track in recent {
+            if seeds.tracks.len() < RADIO_TRACK_SEEDS {
+                if let Some(id) = track.id.clone() {
+                    seeds.tracks.push(id);
+                }
+            }
+            for artist_id in &track.artist_ids {
+                if seeds.len() >= MAX_RECOMMENDATION_SEEDS {
+                    break;
+                }
+                if !seeds.artists.contains(artist_id) {
+                    seeds.artists.push(artist_id.clone());
+                }
+            }
+            if seeds.len() >= MAX_RECOMMENDATION_SEEDS {
+                break;
+            }
+        }
+        seeds
+    }
+
+    /// Fetch recommendations based on the history in the background, to be
+    /// appended to the queue when they arrive and played if `play` is set.
+    fn fetch_recommendations(&self, play: bool) {
+        if play {
+            self.radio_play_pending.store(true, AtomicOrdering::SeqCst);
+        }
+        if self.radio_fetching.swap(true, AtomicOrdering::SeqCst) {
+            return;
+        }
+        let seeds = self.history_seeds();
+        let spotify = self.spotify.clone();
+        let events = self.events.clone();
+        ASYNC_RUNTIME.get().unwrap().spawn_blocking(move || {
+            let tracks = spotify.api.recommended_tracks(&seeds, &[]);
+            let event = QueueEvent::Recommendations(tracks.unwrap_or_default());
+            events.send(Event::Queue(event));
+        });
+    }
+
+    /// Append the recommended `tracks` to the end of the queue, leaving out
+    /// items that were recently played or are already queued. Returns the
+    /// amount of added items.
+    fn append_recommendations(&self, tracks: Vec<Track>) -> usize {
+        let mut q = self.queue.write().unwrap();
+        let mut known: HashSet<String> = self
+            .history
+            .read()
+            .unwrap()
+            .iter()
+            .chain(q.iter())
+            .filter_map(Playable::id)
+            .collect();
+
+        let first = q.len();
+        for track in tracks {
+            if track.id.as_ref().is_some_and(|id| known.insert(id.clone())) {
+                q.push(Playable::Track(track));
+            }
+        }
+
+        let added = q.len() - first;
+        if let Some(order) = self.random_order.write().unwrap().as_mut() {
+            order.extend(first..q.len());
+        }
+        debug!("radio added {} recommendations to the queue", added);
+        added
+    }
+
     /// Get the current shuffle behavior.
     pub fn get_shuffle(&self) -> bool {
         self.shuffle.load(AtomicOrdering::Relaxed)
@@ -426,16 +776,54 @@ impl Queue {
         }
     }
 
-    /// Handle events that are specific to the queue.
-    pub fn handle_event(&self, event: QueueEvent) {
+    /// Handle events that are specific to the queue. Returns an error that
+    /// should be shown to the user if the event failed.
+    pub fn handle_event(&self, event: QueueEvent) -> Result<(), String> {
         match event {
             QueueEvent::PreloadTrackRequest => {
-                if let Some(next_index) = self.next_index() {
-                    let track = self.queue.read().unwrap()[next_index].clone();
-                    debug!("Preloading track {} as requested by librespot", track);
-                    self.spotify.preload(&track);
+                if self.next_index().is_none()
+                    && self.get_radio()
+                    && self.get_repeat() == RepeatSetting::None
+                {
+                    // the next track is preloaded once the recommendations
+                    // arrive
+                    self.fetch_recommendations(false);
                 }
+                self.preload_next();
             }
+            QueueEvent::Recommendations(tracks) => {
+                self.radio_fetching.store(false, AtomicOrdering::SeqCst);
+                let play = self.radio_play_pending.swap(false, AtomicOrdering::SeqCst);
+                if self.append_recommendations(tracks) == 0 {
+                    return Ok(());
+                }
+                if play {
+                    if let Some(index) = self.next_index() {
+                        self.play(index, false, false);
+                    }
+                } else {
+                    self.preload_next();
+                }
+            }
+            QueueEvent::Radio(tracks) => {
+                if tracks.is_empty() {
+                    return Err("No recommendations found".into());
+                }
+                self.clear();
+                self.set_radio(true);
+                self.append_recommendations(tracks);
+                self.play(0, true, false);
+            }
+        }
+        Ok(())
+    }
+
+    /// Ask the player to preload the next item, if there is one.
+    fn preload_next(&self) {
+        if let Some(next_index) = self.next_index() {
+            let track = self.queue.read().unwrap()[next_index].clone();
+            debug!("Preloading track {} as requested by librespot", track);
+            self.spotify.preload(&track);
         }
     }
 
@@ -444,4 +832,3 @@ impl Queue {
         self.spotify.clone()
     }
 }
-
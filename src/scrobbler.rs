@@ -0,0 +1,329 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use log::{debug, error, info, warn};
+
+use crate::application::ASYNC_RUNTIME;
+use crate::fs::cache_path;
+use crate::model::playable::Playable;
+use crate::model::track::Track;
+use crate::spotify::PlayerEvent;
+
+/// The server listens are submitted to unless another one is configured.
+const DEFAULT_URL: &str = "https://api.listenbrainz.org";
+
+/// The file in the cache directory that keeps the listens that couldn't be
+/// submitted yet.
+const QUEUE_FILE: &str = "listenbrainz_queue.json";
+
+/// The most listens the server accepts in a single submission.
+const MAX_LISTENS_PER_REQUEST: usize = 100;
+
+/// A track counts as listened to after half of its duration, or after this
+/// much if that is shorter.
+const LISTEN_THRESHOLD: Duration = Duration::from_secs(4 * 60);
+
+/// How often listens that couldn't be submitted are tried again.
+const RETRY_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// The `[listenbrainz]` section of the configuration file. Scrobbling is
+/// enabled by setting the user token.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct ListenBrainzConfig {
+    /// The root of the API, for a self-hosted server.
+    pub url: Option<String>,
+    pub token: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct AdditionalInfo {
+    duration_ms: u32,
+    spotify_id: String,
+    media_player: String,
+    submission_client: String,
+    submission_client_version: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct TrackMetadata {
+    artist_name: String,
+    track_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    release_name: Option<String>,
+    additional_info: AdditionalInfo,
+}
+
+/// A track that was listened to, as it is submitted to the server.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct Listen {
+    /// When the track started playing in seconds since the epoch, missing for
+    /// "playing now" submissions.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    listened_at: Option<u64>,
+    track_metadata: TrackMetadata,
+}
+
+impl Listen {
+    fn new(track: &Track, listened_at: Option<u64>) -> Self {
+        Self {
+            listened_at,
+            track_metadata: TrackMetadata {
+                artist_name: track.artists.join(", "),
+                track_name: track.title.clone(),
+                release_name: track.album.clone(),
+                additional_info: AdditionalInfo {
+                    duration_ms: track.duration,
+                    spotify_id: track.url.clone(),
+                    media_player: "ncspot".into(),
+                    submission_client: "ncspot".into(),
+                    submission_client_version: env!("CARGO_PKG_VERSION").into(),
+                },
+            },
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Submission<'a> {
+    listen_type: &'a str,
+    payload: &'a [Listen],
+}
+
+/// The reasons a submission can fail.
+enum SubmitError {
+    /// The server rejected the listens, submitting them again won't help.
+    Rejected(String),
+    /// The server couldn't be reached or had a problem, try again later.
+    Failed(String),
+    /// The token is invalid, nothing can be submitted until it is fixed.
+    Unauthorized,
+}
+
+/// Talks to the server and keeps the listens that still have to be submitted.
+struct Client {
+    url: String,
+    token: String,
+    /// The listens that couldn't be submitted yet, oldest first. They are
+    /// stored on disk whenever they change.
+    queue: Mutex<Vec<Listen>>,
+    /// Set once the server refused the token, after which listens are only
+    /// queued for the next session.
+    unauthorized: AtomicBool,
+}
+
+impl Client {
+    fn submit(&self, listen_type: &str, listens: &[Listen]) -> Result<(), SubmitError> {
+        if self.unauthorized.load(Ordering::Relaxed) {
+            return Err(SubmitError::Unauthorized);
+        }
+        let body = serde_json::to_string(&Submission {
+            listen_type,
+            payload: listens,
+        })
+        .map_err(|e| SubmitError::Rejected(e.to_string()))?;
+
+        let url = format!("{}/1/submit-listens", self.url.trim_end_matches('/'));
+        match ureq::post(&url)
+            .set("Authorization", &format!("Token {}", self.token))
+            .set("Content-Type", "application/json")
+            .send_string(&body)
+        {
+            Ok(_) => Ok(()),
+            Err(ureq::Error::Status(400, response)) => Err(SubmitError::Rejected(
+                response.into_string().unwrap_or_default(),
+            )),
+            Err(ureq::Error::Status(status @ (401 | 403), response)) => {
+                // Only report it the first time, later submissions fail early.
+                if !self.unauthorized.swap(true, Ordering::Relaxed) {
+                    error!(
+                        "ListenBrainz refused the token ({}), check the [listenbrainz] \
+                         section of the configuration: {}",
+                        status,
+                        response.into_string().unwrap_or_default()
+                    );
+                }
+                Err(SubmitError::Unauthorized)
+            }
+            Err(e) => Err(SubmitError::Failed(e.to_string())),
+        }
+    }
+
+    /// Submit `listen` after the ones that are queued, queueing it if that
+    /// fails.
+    fn listen(&self, listen: Listen) {
+        let mut queue = self.queue.lock().unwrap();
+        queue.push(listen);
+        self.flush(&mut queue);
+    }
+
+    /// Submit the queued listens if there are any.
+    fn retry(&self) {
+        let mut queue = self.queue.lock().unwrap();
+        if !queue.is_empty() {
+            self.flush(&mut queue);
+        }
+    }
+
+    /// Submit the queued listens, keeping the ones that fail.
+    fn flush(&self, queue: &mut Vec<Listen>) {
+        while !queue.is_empty() {
+            let count = queue.len().min(MAX_LISTENS_PER_REQUEST);
+            let listen_type = if count == 1 { "single" } else { "import" };
+            match self.submit(listen_type, &queue[..count]) {
+                Ok(()) => info!("submitted {} listens", count),
+                Err(SubmitError::Rejected(e)) => error!("dropping {} listens: {}", count, e),
+                Err(SubmitError::Failed(e)) => {
+                    warn!("could not submit listens, keeping {}: {}", queue.len(), e);
+                    break;
+                }
+                Err(SubmitError::Unauthorized) => {
+                    debug!("not submitting listens, keeping {}", queue.len());
+                    break;
+                }
+            }
+            queue.drain(..count);
+        }
+        save_queue(queue);
+    }
+}
+
+fn load_queue() -> Vec<Listen> {
+    std::fs::read_to_string(cache_path(QUEUE_FILE))
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+fn save_queue(queue: &[Listen]) {
+    let path = cache_path(QUEUE_FILE);
+    let result = if queue.is_empty() {
+        std::fs::remove_file(&path).or(Ok(()))
+    } else {
+        serde_json::to_string(queue)
+            .map_err(std::io::Error::from)
+            .and_then(|contents| std::fs::write(&path, contents))
+    };
+    if let Err(e) = result {
+        error!("could not write {}: {}", path.display(), e);
+    }
+}
+
+/// The track that is playing and whether it was submitted as a listen.
+struct Playing {
+    track: Track,
+    listened_at: u64,
+    submitted: bool,
+}
+
+/// Submits the tracks that are played to a ListenBrainz server: "playing now"
+/// when a track starts and a listen when enough of it has been played.
+pub struct Scrobbler {
+    client: Option<Arc<Client>>,
+    playing: Option<Playing>,
+}
+
+impl Scrobbler {
+    pub fn new(config: Option<ListenBrainzConfig>) -> Self {
+        let client = config.and_then(|config| {
+            Some(Arc::new(Client {
+                url: config.url.unwrap_or_else(|| DEFAULT_URL.into()),
+                token: config.token?,
+                queue: Mutex::new(load_queue()),
+                unauthorized: AtomicBool::new(false),
+            }))
+        });
+
+        // Retry what couldn't be submitted in an earlier session right away,
+        // and then every now and then.
+        if let Some(client) = client.clone() {
+            ASYNC_RUNTIME.get().unwrap().spawn(async move {
+                let mut interval = tokio::time::interval(RETRY_INTERVAL);
+                loop {
+                    interval.tick().await;
+                    let client = client.clone();
+                    let _ = tokio::task::spawn_blocking(move || client.retry()).await;
+                }
+            });
+        }
+
+        Self {
+            client,
+            playing: None,
+        }
+    }
+
+    /// Follow the player event `event`. `playable` is the current item of the
+    /// queue and `position` the playback position in it.
+    pub fn player_event(
+        &mut self,
+        event: &PlayerEvent,
+        playable: Option<&Playable>,
+        position: Duration,
+    ) {
+        let Some(client) = self.client.clone() else {
+            return;
+        };
+
+        match (event, playable) {
+            (PlayerEvent::Playing(_), Some(Playable::Track(track))) => {
+                let is_new = self.playing.as_ref().map(|p| &p.track.uri) != Some(&track.uri);
+                if is_new {
+                    self.start(client, track, position);
+                }
+            }
+            (PlayerEvent::FinishedTrack, _) => {
+                self.update(position);
+                self.playing = None;
+            }
+            (PlayerEvent::Stopped, _) => self.playing = None,
+            _ => {}
+        }
+    }
+
+    fn start(&mut self, client: Arc<Client>, track: &Track, position: Duration) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        self.playing = Some(Playing {
+            track: track.clone(),
+            listened_at: now.saturating_sub(position).as_secs(),
+            submitted: false,
+        });
+
+        let listen = Listen::new(track, None);
+        ASYNC_RUNTIME.get().unwrap().spawn_blocking(move || {
+            match client.submit("playing_now", &[listen]) {
+                Ok(()) => {
+                    debug!("submitted playing now");
+                    // The server is reachable again.
+                    client.retry();
+                }
+                Err(SubmitError::Unauthorized) => {}
+                Err(SubmitError::Rejected(e) | SubmitError::Failed(e)) => {
+                    warn!("could not submit playing now: {}", e)
+                }
+            }
+        });
+    }
+
+    /// Submit the playing track as a listen once `position` is past half of
+    /// it or past four minutes.
+    pub fn update(&mut self, position: Duration) {
+        let (Some(client), Some(playing)) = (self.client.clone(), self.playing.as_mut()) else {
+            return;
+        };
+
+        let half = Duration::from_millis(playing.track.duration as u64 / 2);
+        if playing.submitted || position < half.min(LISTEN_THRESHOLD) {
+            return;
+        }
+        playing.submitted = true;
+
+        let listen = Listen::new(&playing.track, Some(playing.listened_at));
+        ASYNC_RUNTIME
+            .get()
+            .unwrap()
+            .spawn_blocking(move || client.listen(listen));
+    }
+}
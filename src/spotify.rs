@@ -6,13 +6,9 @@ use librespot_core::config::SessionConfig;
 use librespot_core::session::Session;
 use librespot_core::session::SessionError;
 use librespot_playback::audio_backend::SinkBuilder;
-use librespot_playback::config::PlayerConfig;
-use librespot_playback::mixer::softmixer::SoftMixer;
-use librespot_playback::mixer::MixerConfig;
 use log::{debug, error, info};
 
 use librespot_playback::audio_backend;
-use librespot_playback::config::Bitrate;
 use librespot_playback::player::Player;
 
 use futures::channel::oneshot;
@@ -25,13 +21,24 @@ use std::sync::{Arc, RwLock};
 use std::time::{Duration, SystemTime};
 
 use crate::application::ASYNC_RUNTIME;
+use crate::equalizer::Equalizer;
 use crate::events::{Event, EventManager};
-use crate::fs::cache_path;
 use crate::model::playable::Playable;
+use crate::pcm::{PcmConfig, PcmSink};
+use crate::playback::PlaybackConfig;
 use crate::spotify_api::WebApi;
 use crate::spotify_worker::{Worker, WorkerCommand};
+use crate::visualizer::Visualizer;
 
-pub const VOLUME_PERCENT: u16 = ((u16::max_value() as f64) * 1.0 / 100.0) as u16;
+/// The volume at `percent` percent, between 0 and 100.
+fn volume_from_percent(percent: u16) -> u16 {
+    ((u16::MAX as u32 * percent.min(100) as u32 + 50) / 100) as u16
+}
+
+/// The percentage of `volume`, rounded to the nearest percent.
+fn volume_to_percent(volume: u16) -> u16 {
+    ((volume as u32 * 100 + u16::MAX as u32 / 2) / u16::MAX as u32) as u16
+}
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum PlayerEvent {
@@ -54,10 +61,21 @@ pub struct Spotify {
     channel: Arc<RwLock<Option<mpsc::UnboundedSender<WorkerCommand>>>>,
     user: Option<String>,
     volume: Arc<AtomicU16>,
+    pcm: Option<PcmConfig>,
+    playback: PlaybackConfig,
+    cache: Cache,
+    visualizer: Visualizer,
+    equalizer: Equalizer,
 }
 
 impl Spotify {
-    pub fn new(events: EventManager, credentials: Credentials) -> Self {
+    pub fn new(
+        events: EventManager,
+        credentials: Credentials,
+        pcm: Option<PcmConfig>,
+        playback: PlaybackConfig,
+        cache: Cache,
+    ) -> Self {
         let mut spotify = Self {
             events,
             credentials,
@@ -68,6 +86,11 @@ impl Spotify {
             channel: Arc::new(RwLock::new(None)),
             user: None,
             volume: Arc::new(AtomicU16::new(u16::MAX)),
+            pcm,
+            playback,
+            cache,
+            visualizer: Visualizer::new(),
+            equalizer: Equalizer::new(),
         };
 
         let (user_tx, user_rx) = oneshot::channel();
@@ -95,13 +118,23 @@ impl Spotify {
             let events = self.events.clone();
             let volume = self.volume();
             let credentials = self.credentials.clone();
+            let cache = self.cache.clone();
+            let pcm = self.pcm.clone();
+            let playback = self.playback.clone();
+            let visualizer = self.visualizer.clone();
+            let equalizer = self.equalizer.clone();
             ASYNC_RUNTIME.get().unwrap().spawn(Self::worker(
                 worker_channel,
                 events,
                 rx,
                 credentials,
+                cache,
                 user_tx,
                 volume,
+                pcm,
+                playback,
+                visualizer,
+                equalizer,
             ));
         }
     }
@@ -128,14 +161,12 @@ impl Spotify {
             .map(|r| r.0)
     }
 
-    async fn create_session(credentials: Credentials) -> Result<Session, SessionError> {
+    async fn create_session(
+        credentials: Credentials,
+        cache: Cache,
+    ) -> Result<Session, SessionError> {
         debug!("Creating session");
 
-        let librespot_cache_path = cache_path("librespot");
-        let audio_cache_path = librespot_cache_path.join("files");
-        let cache = Cache::new(Some(librespot_cache_path), None, Some(audio_cache_path), None)
-            .expect("Could not create cache");
-
         let session_config = Self::session_config();
         Session::connect(session_config, credentials, Some(cache), true)
             .await
@@ -156,27 +187,28 @@ impl Spotify {
         Some(backend.1)
     }
 
+    #[allow(clippy::too_many_arguments)]
     async fn worker(
         worker_channel: Arc<RwLock<Option<mpsc::UnboundedSender<WorkerCommand>>>>,
         events: EventManager,
         commands: mpsc::UnboundedReceiver<WorkerCommand>,
         credentials: Credentials,
+        cache: Cache,
         user_tx: Option<oneshot::Sender<String>>,
         volume: u16,
+        pcm: Option<PcmConfig>,
+        playback: PlaybackConfig,
+        visualizer: Visualizer,
+        equalizer: Equalizer,
     ) {
-        let player_config = PlayerConfig {
-            bitrate: Bitrate::Bitrate320,
-            ..Default::default()
-        };
+        let player_config = playback.player_config();
 
-        let session = Self::create_session(credentials)
+        let session = Self::create_session(credentials, cache)
             .await
             .expect("Could not create session");
         user_tx.map(|tx| tx.send(session.username()));
 
-        let create_mixer = librespot_playback::mixer::find(Some(SoftMixer::NAME))
-            .expect("could not create softvol mixer");
-        let mixer = create_mixer(MixerConfig::default());
+        let mixer = playback.open_mixer();
         mixer.set_volume(volume);
 
         let backend = Self::init_backend().expect("Could not find an audio playback backend");
@@ -184,7 +216,10 @@ impl Spotify {
             player_config,
             session.clone(),
             mixer.get_soft_volume(),
-            move || (backend)(None, librespot_playback::config::AudioFormat::default()),
+            {
+                let visualizer = visualizer.clone();
+                move || equalizer.wrap(visualizer.tap(PcmSink::open(backend, pcm.as_ref())))
+            },
         );
 
         let mut worker = Worker::new(
@@ -194,6 +229,9 @@ impl Spotify {
             session,
             player,
             mixer,
+            volume,
+            playback.volume_ramp(),
+            visualizer,
         );
         debug!("worker thread ready.");
         worker.run_loop().await;
@@ -339,10 +377,34 @@ impl Spotify {
         self.seek(std::cmp::max(0, new) as u32);
     }
 
+    pub fn visualizer(&self) -> &Visualizer {
+        &self.visualizer
+    }
+
+    pub fn equalizer(&self) -> &Equalizer {
+        &self.equalizer
+    }
+
+    /// The cache of credentials and audio files the sessions use.
+    pub fn cache(&self) -> &Cache {
+        &self.cache
+    }
+
     pub fn volume(&self) -> u16 {
         self.volume.load(Ordering::Relaxed)
     }
 
+    /// The volume in percent.
+    pub fn volume_percent(&self) -> u16 {
+        volume_to_percent(self.volume())
+    }
+
+    /// Change the volume by `delta` percent, keeping it between 0 and 100.
+    pub fn change_volume(&self, delta: i32) {
+        let percent = (self.volume_percent() as i32 + delta).clamp(0, 100);
+        self.set_volume(volume_from_percent(percent as u16));
+    }
+
     pub fn set_volume(&self, new_volume: u16) {
         info!("setting volume to {}", new_volume);
         self.volume.store(new_volume, Ordering::Relaxed);
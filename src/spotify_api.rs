@@ -1,5 +1,6 @@
 use crate::model::album::Album;
 use crate::model::artist::Artist;
+use crate::model::audio_features::AudioFeatures;
 use crate::model::category::Category;
 use crate::model::episode::Episode;
 use crate::model::playable::Playable;
@@ -10,12 +11,12 @@ use crate::ui::pagination::{ApiPage, ApiResult};
 use chrono::{DateTime, Duration as ChronoDuration, Utc};
 use log::{debug, error, info};
 
-use rspotify::http::HttpError;
+use rspotify::http::{HttpError, Query};
 use rspotify::model::{
     AlbumId, AlbumType, ArtistId, CursorBasedPage, EpisodeId, FullAlbum, FullArtist, FullEpisode,
-    FullPlaylist, FullShow, FullTrack, Market, Page, PlaylistId,
-    PrivateUser, Recommendations, SavedAlbum, SavedTrack, SearchResult, SearchType, Show, ShowId,
-    SimplifiedTrack, TrackId,
+    FullPlaylist, FullShow, FullTrack, Market, Page, PlayableId, PlaylistId, PrivateUser,
+    Recommendations, RecommendationsAttribute, SavedAlbum, SavedTrack, SearchResult, SearchType,
+    Show, ShowId, SimplifiedTrack, TrackId, UserId,
 };
 use rspotify::{prelude::*, AuthCodeSpotify, ClientError, ClientResult, Config, Token};
 use std::collections::HashSet;
@@ -25,6 +26,31 @@ use std::thread;
 use std::time::Duration;
 use tokio::sync::mpsc;
 
+/// The maximum amount of seed items the recommendations endpoint accepts.
+pub const MAX_RECOMMENDATION_SEEDS: usize = 5;
+
+/// The maximum amount of tracks audio features can be requested for at once.
+pub const MAX_AUDIO_FEATURES_TRACKS: usize = 100;
+
+/// Tracks, artists and genres to base recommendations on. Tracks and artists
+/// are Spotify IDs, genres are taken from [WebApi::available_genre_seeds].
+#[derive(Clone, Debug, Default)]
+pub struct RecommendationSeeds {
+    pub tracks: Vec<String>,
+    pub artists: Vec<String>,
+    pub genres: Vec<String>,
+}
+
+impl RecommendationSeeds {
+    pub fn len(&self) -> usize {
+        self.tracks.len() + self.artists.len() + self.genres.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
 #[derive(Clone)]
 pub struct WebApi {
     api: AuthCodeSpotify,
@@ -177,6 +203,23 @@ impl WebApi {
         self.api_with_retry(|api| api.track(tid.clone(), Some(Market::FromToken)))
     }
 
+    pub fn track_features(&self, track_id: &str) -> Option<AudioFeatures> {
+        let tid = TrackId::from_id(track_id).ok()?;
+        self.api_with_retry(|api| api.track_features(tid.clone()))
+            .map(|features| (&features).into())
+    }
+
+    /// Fetch the audio features of up to [MAX_AUDIO_FEATURES_TRACKS] tracks.
+    /// Tracks without audio features are left out.
+    pub fn tracks_features(&self, track_ids: &[&str]) -> Option<Vec<AudioFeatures>> {
+        let tids: Vec<TrackId> = track_ids
+            .iter()
+            .filter_map(|id| TrackId::from_id(*id).ok())
+            .collect();
+        self.api_with_retry(|api| api.tracks_features(tids.clone()))?
+            .map(|features| features.iter().map(|f| f.into()).collect())
+    }
+
     pub fn get_show(&self, show_id: &str) -> Option<FullShow> {
         let sid = ShowId::from_id(show_id).ok()?;
         self.api_with_retry(|api| api.get_a_show(sid.clone(), Some(Market::FromToken)))
@@ -192,6 +235,7 @@ impl WebApi {
         seed_artists: Option<Vec<&str>>,
         seed_genres: Option<Vec<&str>>,
         seed_tracks: Option<Vec<&str>>,
+        attributes: &[RecommendationsAttribute],
     ) -> Option<Recommendations> {
         self.api_with_retry(|api| {
             let seed_artistids = seed_artists.as_ref().map(|artistids| {
@@ -207,7 +251,7 @@ impl WebApi {
                     .collect::<Vec<TrackId>>()
             });
             api.recommendations(
-                std::iter::empty(),
+                attributes.iter().copied(),
                 seed_artistids,
                 seed_genres.clone(),
                 seed_trackids,
@@ -217,6 +261,43 @@ impl WebApi {
         })
     }
 
+    /// Fetch tracks that are recommended based on `seeds`, tuned by the
+    /// optional audio feature `attributes`.
+    pub fn recommended_tracks(
+        &self,
+        seeds: &RecommendationSeeds,
+        attributes: &[RecommendationsAttribute],
+    ) -> Option<Vec<Track>> {
+        if seeds.is_empty() {
+            return None;
+        }
+
+        let artists: Vec<&str> = seeds.artists.iter().map(String::as_str).collect();
+        let genres: Vec<&str> = seeds.genres.iter().map(String::as_str).collect();
+        let tracks: Vec<&str> = seeds.tracks.iter().map(String::as_str).collect();
+        self.recommendations(
+            Some(artists).filter(|a| !a.is_empty()),
+            Some(genres).filter(|g| !g.is_empty()),
+            Some(tracks).filter(|t| !t.is_empty()),
+            attributes,
+        )
+        .map(|r| r.tracks.iter().map(Track::from).collect())
+    }
+
+    /// Fetch the genres that can be used as recommendation seeds.
+    pub fn available_genre_seeds(&self) -> Option<Vec<String>> {
+        #[derive(Deserialize)]
+        struct GenreSeeds {
+            genres: Vec<String>,
+        }
+
+        self.api_with_retry(|api| {
+            let result = api.api_get("recommendations/available-genre-seeds", &Query::new())?;
+            let seeds: GenreSeeds = serde_json::from_str(&result)?;
+            Ok(seeds.genres)
+        })
+    }
+
     pub fn search(
         &self,
         searchtype: SearchType,
@@ -351,6 +432,108 @@ impl WebApi {
         ApiResult::new(MAX_SIZE, Arc::new(fetch_page))
     }
 
+    /// Fetch all albums, singles and compilations of an artist.
+    pub fn artist_discography(&self, artist_id: &str) -> Vec<Album> {
+        const MAX_SIZE: u32 = 50;
+        let mut albums = Vec::new();
+        let Ok(aid) = ArtistId::from_id(artist_id) else {
+            return albums;
+        };
+
+        // the same release can be listed several times for different markets,
+        // or as both an album and a single. Albums are fetched first, so their
+        // entry is the one that is kept.
+        let mut seen = HashSet::new();
+        for album_type in [AlbumType::Album, AlbumType::Single, AlbumType::Compilation] {
+            let mut offset = 0;
+            loop {
+                debug!(
+                    "fetching artist {} discography, offset: {}",
+                    artist_id, offset
+                );
+                let page = self.api_with_retry(|api| {
+                    api.artist_albums_manual(
+                        aid.clone(),
+                        Some(album_type),
+                        Some(Market::FromToken),
+                        Some(MAX_SIZE),
+                        Some(offset),
+                    )
+                });
+                match page {
+                    Some(page) => {
+                        albums.extend(page.items.iter().map(Album::from).filter(|album| {
+                            let title = album.title.trim().to_lowercase();
+                            seen.insert((title, album.release_date.clone()))
+                        }));
+                        offset += MAX_SIZE;
+                        if page.next.is_none() {
+                            break;
+                        }
+                    }
+                    None => break,
+                }
+            }
+        }
+
+        albums
+    }
+
+    /// Create a private playlist owned by the user.
+    pub fn user_playlist_create(&self, user_id: &str, name: &str) -> Option<FullPlaylist> {
+        let uid = UserId::from_id(user_id).ok()?;
+        self.api_with_retry(|api| {
+            api.user_playlist_create(uid.clone(), name, Some(false), None, None)
+        })
+    }
+
+    /// Append tracks to a playlist, in batches of the maximum the API accepts.
+    pub fn playlist_add_tracks(&self, playlist_id: &str, track_ids: &[&str]) -> Option<()> {
+        const MAX_SIZE: usize = 100;
+        let pid = PlaylistId::from_id(playlist_id).ok()?;
+        for batch in track_ids.chunks(MAX_SIZE) {
+            let ids: Vec<TrackId> = batch
+                .iter()
+                .filter_map(|id| TrackId::from_id(*id).ok())
+                .collect();
+            self.api_with_retry(|api| {
+                let items = ids.iter().map(|id| PlayableId::Track(id.as_ref()));
+                api.playlist_add_items(pid.clone(), items, None)
+            })?;
+        }
+        Some(())
+    }
+
+    pub fn current_user_saved_tracks_add(&self, track_id: &str) -> Option<()> {
+        let tid = TrackId::from_id(track_id).ok()?;
+        self.api_with_retry(|api| api.current_user_saved_tracks_add([tid.clone()]))
+    }
+
+    pub fn current_user_saved_tracks_delete(&self, track_id: &str) -> Option<()> {
+        let tid = TrackId::from_id(track_id).ok()?;
+        self.api_with_retry(|api| api.current_user_saved_tracks_delete([tid.clone()]))
+    }
+
+    pub fn current_user_saved_albums_add(&self, album_id: &str) -> Option<()> {
+        let aid = AlbumId::from_id(album_id).ok()?;
+        self.api_with_retry(|api| api.current_user_saved_albums_add([aid.clone()]))
+    }
+
+    pub fn current_user_saved_albums_delete(&self, album_id: &str) -> Option<()> {
+        let aid = AlbumId::from_id(album_id).ok()?;
+        self.api_with_retry(|api| api.current_user_saved_albums_delete([aid.clone()]))
+    }
+
+    pub fn user_follow_artist(&self, artist_id: &str) -> Option<()> {
+        let aid = ArtistId::from_id(artist_id).ok()?;
+        self.api_with_retry(|api| api.user_follow_artists([aid.clone()]))
+    }
+
+    pub fn user_unfollow_artist(&self, artist_id: &str) -> Option<()> {
+        let aid = ArtistId::from_id(artist_id).ok()?;
+        self.api_with_retry(|api| api.user_unfollow_artists([aid.clone()]))
+    }
+
     pub fn show_episodes(&self, show_id: &str) -> ApiResult<Episode> {
         const MAX_SIZE: u32 = 50;
         let spotify = self.clone();
@@ -377,6 +560,16 @@ impl WebApi {
         ApiResult::new(MAX_SIZE, Arc::new(fetch_page))
     }
 
+    /// Fetch the `limit` most recently released episodes of a show.
+    pub fn show_latest_episodes(&self, show_id: &str, limit: u32) -> Option<Vec<Episode>> {
+        debug!("fetching latest episodes of show {}", show_id);
+        let sid = ShowId::from_id(show_id).ok()?;
+        self.api_with_retry(|api| {
+            api.get_shows_episodes_manual(sid.clone(), Some(Market::FromToken), Some(limit), None)
+        })
+        .map(|page| page.items.iter().map(|se| se.into()).collect())
+    }
+
     pub fn get_saved_shows(&self, offset: u32) -> Option<Page<Show>> {
         self.api_with_retry(|api| api.get_saved_show_manual(Some(50), Some(offset)))
     }
@@ -461,6 +654,58 @@ impl WebApi {
         ApiResult::new(MAX_LIMIT, Arc::new(fetch_page))
     }
 
+    pub fn new_releases(&self) -> ApiResult<Album> {
+        const MAX_LIMIT: u32 = 50;
+        let spotify = self.clone();
+        let fetch_page = move |offset: u32| {
+            debug!("fetching new releases, offset: {}", offset);
+            spotify.api_with_retry(|api| {
+                match api.new_releases_manual(None, Some(MAX_LIMIT), Some(offset)) {
+                    Ok(page) => Ok(ApiPage {
+                        offset: page.offset,
+                        total: page.total,
+                        items: page.items.iter().map(|sa| sa.into()).collect(),
+                    }),
+                    Err(e) => Err(e),
+                }
+            })
+        };
+        ApiResult::new(MAX_LIMIT, Arc::new(fetch_page))
+    }
+
+    /// Fetch the featured playlists, along with the message Spotify shows
+    /// above them, e.g. "Monday morning music, coming right up".
+    pub fn featured_playlists(&self) -> (ApiResult<Playlist>, Option<String>) {
+        const MAX_LIMIT: u32 = 50;
+        let spotify = self.clone();
+        let message = Arc::new(RwLock::new(None));
+        let page_message = message.clone();
+        let fetch_page = move |offset: u32| {
+            debug!("fetching featured playlists, offset: {}", offset);
+            spotify.api_with_retry(|api| {
+                match api.featured_playlists(None, None, None, Some(MAX_LIMIT), Some(offset)) {
+                    Ok(featured) => {
+                        *page_message.write().unwrap() = Some(featured.message);
+                        Ok(ApiPage {
+                            offset: featured.playlists.offset,
+                            total: featured.playlists.total,
+                            items: featured
+                                .playlists
+                                .items
+                                .iter()
+                                .map(|sp| sp.into())
+                                .collect(),
+                        })
+                    }
+                    Err(e) => Err(e),
+                }
+            })
+        };
+        let result = ApiResult::new(MAX_LIMIT, Arc::new(fetch_page));
+        let message = message.read().unwrap().clone();
+        (result, message)
+    }
+
     pub fn current_user(&self) -> Option<PrivateUser> {
         self.api_with_retry(|api| api.current_user())
     }
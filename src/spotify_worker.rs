@@ -2,6 +2,7 @@ use crate::events::{Event, EventManager};
 use crate::model::playable::Playable;
 use crate::queue::QueueEvent;
 use crate::spotify::PlayerEvent;
+use crate::visualizer::Visualizer;
 use futures::{Future, FutureExt};
 use librespot_core::keymaster::Token;
 use librespot_core::session::Session;
@@ -19,6 +20,16 @@ use tokio_stream::StreamExt;
 
 const CLIENT_ID: &str = "d420a117a32841c2b3474932e49fb54b";
 
+/// How often the screen is redrawn during playback.
+pub const UI_REFRESH: Duration = Duration::from_millis(400);
+
+/// How often the screen is redrawn during playback while the visualizer is
+/// shown, which caps its frame rate.
+pub const VISUALIZER_REFRESH: Duration = Duration::from_millis(50);
+
+/// How often the volume is changed while it is ramped.
+const RAMP_STEP: Duration = Duration::from_millis(10);
+
 #[derive(Debug)]
 pub(crate) enum WorkerCommand {
     Load(Playable, bool, u32),
@@ -41,9 +52,16 @@ pub struct Worker {
     token_task: Pin<Box<dyn Future<Output = ()> + Send>>,
     active: bool,
     mixer: Box<dyn Mixer>,
+    /// The volume that is set, which the mixer is at unless the volume was
+    /// ramped down.
+    volume: u16,
+    ramp: Option<Duration>,
+    ramped_down: bool,
+    visualizer: Visualizer,
 }
 
 impl Worker {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         events: EventManager,
         player_events: mpsc::UnboundedReceiver<LibrespotPlayerEvent>,
@@ -51,6 +69,9 @@ impl Worker {
         session: Session,
         player: Player,
         mixer: Box<dyn Mixer>,
+        volume: u16,
+        ramp: Option<Duration>,
+        visualizer: Visualizer,
     ) -> Self {
         Self {
             events,
@@ -61,6 +82,46 @@ impl Worker {
             token_task: Box::pin(futures::future::pending()),
             active: false,
             mixer,
+            volume,
+            ramp,
+            ramped_down: false,
+            visualizer,
+        }
+    }
+
+    /// Fade the volume of the mixer from `from` to `to` to avoid clicks, or
+    /// set it right away if ramping is disabled.
+    async fn ramp_volume(&mut self, from: u16, to: u16) {
+        if let Some(ramp) = self.ramp {
+            let steps = (ramp.as_millis() / RAMP_STEP.as_millis()).max(1) as i64;
+            for step in 1..steps {
+                let volume = from as i64 + (to as i64 - from as i64) * step / steps;
+                self.mixer.set_volume(volume as u16);
+                time::sleep(RAMP_STEP).await;
+            }
+        }
+        self.mixer.set_volume(to);
+    }
+
+    /// Fade out before playback is paused or stopped. The mixer stays silent
+    /// until playback resumes.
+    async fn ramp_down(&mut self) {
+        if self.ramp.is_some() && self.active && !self.ramped_down {
+            self.ramp_volume(self.volume, 0).await;
+            self.ramped_down = true;
+        }
+    }
+
+    /// Fade in after playback resumes, or restore the volume right away when
+    /// `fade` isn't set.
+    async fn ramp_up(&mut self, fade: bool) {
+        if self.ramped_down {
+            if fade {
+                self.ramp_volume(0, self.volume).await;
+            } else {
+                self.mixer.set_volume(self.volume);
+            }
+            self.ramped_down = false;
         }
     }
 
@@ -87,8 +148,17 @@ impl Worker {
         )
     }
 
+    /// How often the screen should be redrawn during playback.
+    fn refresh_interval(&self) -> Duration {
+        if self.visualizer.is_enabled() {
+            VISUALIZER_REFRESH
+        } else {
+            UI_REFRESH
+        }
+    }
+
     pub async fn run_loop(&mut self) {
-        let mut ui_refresh = time::interval(Duration::from_millis(400));
+        let mut ui_refresh = time::interval(self.refresh_interval());
 
         loop {
             if self.session.is_invalid() {
@@ -107,6 +177,7 @@ impl Worker {
                                     warn!("track is not playable");
                                     self.events.send(Event::Player(PlayerEvent::FinishedTrack));
                                 } else {
+                                    self.ramp_up(false).await;
                                     self.player.load(id, start_playing, position_ms);
                                 }
                             }
@@ -118,18 +189,24 @@ impl Worker {
                     }
                     Some(WorkerCommand::Play) => {
                         self.player.play();
+                        self.ramp_up(true).await;
                     }
                     Some(WorkerCommand::Pause) => {
+                        self.ramp_down().await;
                         self.player.pause();
                     }
                     Some(WorkerCommand::Stop) => {
+                        self.ramp_down().await;
                         self.player.stop();
                     }
                     Some(WorkerCommand::Seek(pos)) => {
                         self.player.seek(pos);
                     }
                     Some(WorkerCommand::SetVolume(volume)) => {
-                        self.mixer.set_volume(volume);
+                        self.volume = volume;
+                        if !self.ramped_down {
+                            self.mixer.set_volume(volume);
+                        }
                     }
                     Some(WorkerCommand::RequestToken(sender)) => {
                         self.token_task = self.get_token(sender);
@@ -191,6 +268,9 @@ impl Worker {
                     if self.active {
                         self.events.trigger();
                     }
+                    if ui_refresh.period() != self.refresh_interval() {
+                        ui_refresh = time::interval(self.refresh_interval());
+                    }
                 },
                 _ = self.token_task.as_mut() => {
                     info!("token updated!");
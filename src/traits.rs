@@ -9,8 +9,11 @@ use crate::commands::CommandResult;
 use crate::library::Library;
 use crate::model::album::Album;
 use crate::model::artist::Artist;
+use crate::model::playable::Playable;
+use crate::model::seed::Seed;
 use crate::model::track::Track;
 use crate::queue::Queue;
+use crate::spotify_api::RecommendationSeeds;
 
 pub trait ListItem: Sync + Send + 'static {
     fn is_playing(&self, queue: &Queue) -> bool;
@@ -32,6 +35,16 @@ pub trait ListItem: Sync + Send + 'static {
         None
     }
 
+    /// The tracks and artists recommendations for this item are based on.
+    fn recommendation_seeds(&mut self, _queue: &Queue) -> Option<RecommendationSeeds> {
+        None
+    }
+
+    /// This item as a seed for the recommendations builder.
+    fn seed(&self) -> Option<Seed> {
+        None
+    }
+
     fn album(&self, _queue: &Queue) -> Option<Album> {
         None
     }
@@ -44,6 +57,16 @@ pub trait ListItem: Sync + Send + 'static {
         None
     }
 
+    fn playable(&self) -> Option<Playable> {
+        None
+    }
+
+    /// The playable items this item consists of when it's exported, e.g. the
+    /// tracks of an album.
+    fn exported(&self) -> Vec<Playable> {
+        self.playable().into_iter().collect()
+    }
+
     #[allow(unused_variables)]
     #[inline]
     fn is_saved(&self, library: &Library) -> Option<bool> {
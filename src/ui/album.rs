@@ -1,20 +1,42 @@
 use std::sync::{Arc, RwLock};
 
-use cursive::view::ViewWrapper;
-use cursive::Cursive;
+use cursive::direction::Direction;
+use cursive::event::{AnyCb, Callback, Event, EventResult, MouseButton, MouseEvent};
+use cursive::theme::ColorStyle;
+use cursive::view::{CannotFocus, Selector, View, ViewNotFound};
+use cursive::{Cursive, Printer, Rect, Vec2};
+use unicode_width::UnicodeWidthStr;
 
-use crate::command::Command;
+use crate::command::{AlbumAction, Command};
 use crate::commands::CommandResult;
+use crate::export::{export, expand_path};
+use crate::ext_traits::CursiveExt;
 use crate::library::Library;
 use crate::model::album::Album;
+use crate::model::playable::Playable;
 use crate::queue::Queue;
-use crate::traits::{ListItem,ViewExt};
+use crate::traits::{IntoBoxedViewExt, ListItem, ViewExt};
+use crate::ui::artist::ArtistView;
+use crate::ui::cover::{self, CoverView, COVER_SIZE};
 use crate::ui::listview::ListView;
+use crate::ui::selectview::select_artist;
 use crate::ui::tabbedview::TabbedView;
 
+/// The clickable actions in the header of an [AlbumView].
+enum HeaderAction {
+    Save,
+    Queue,
+    Artist(usize),
+}
+
 pub struct AlbumView {
     album: Album,
     tabs: TabbedView,
+    queue: Arc<Queue>,
+    library: Arc<Library>,
+    cover: Option<CoverView>,
+    /// Whether there's enough room to show the cover in the header.
+    cover_visible: bool,
 }
 
 impl AlbumView {
@@ -28,30 +50,248 @@ impl AlbumView {
         } else {
             Vec::new()
         };
+        let multi_disc = tracks.iter().any(|t| t.disc_number > 1);
 
         // Note: currently cannot return None
         let artists = album.artists().unwrap();
 
+        let mut tracks_view = ListView::new(
+            Arc::new(RwLock::new(tracks)),
+            queue.clone(),
+            library.clone(),
+        );
+        if multi_disc {
+            tracks_view = tracks_view.with_separators(|t| format!("Disc {}", t.disc_number));
+        }
+
         let mut tabs = TabbedView::new();
+        tabs.add_tab("Tracks", tracks_view);
         tabs.add_tab(
-            "Tracks",
+            "Artists",
             ListView::new(
-                Arc::new(RwLock::new(tracks)),
+                Arc::new(RwLock::new(artists)),
                 queue.clone(),
                 library.clone(),
             ),
         );
-        tabs.add_tab(
-            "Artists",
-            ListView::new(Arc::new(RwLock::new(artists)), queue, library),
-        );
 
-        Self { album, tabs }
+        let cover = album
+            .cover_url
+            .as_deref()
+            .filter(|_| cover::enabled())
+            .map(CoverView::new);
+
+        Self {
+            album,
+            tabs,
+            queue,
+            library,
+            cover,
+            cover_visible: false,
+        }
+    }
+
+    /// The informational lines of the header.
+    fn header_lines(&self) -> Vec<String> {
+        let mut details = Vec::new();
+        if let Some(label) = &self.album.label {
+            details.push(label.clone());
+        }
+        if let Some(release_date) = &self.album.release_date {
+            details.push(format!("Released {release_date}"));
+        }
+        if let Some(total_tracks) = self.album.total_tracks {
+            details.push(format!("{total_tracks} tracks"));
+        }
+        if let Some(popularity) = self.album.popularity {
+            details.push(format!("Popularity {popularity}/100"));
+        }
+
+        let mut lines = Vec::new();
+        if !details.is_empty() {
+            lines.push(details.join(" · "));
+        }
+        if !self.album.copyrights.is_empty() {
+            lines.push(self.album.copyrights.join(" "));
+        }
+        lines
+    }
+
+    /// The clickable actions of the header, in the order they're drawn.
+    fn header_actions(&self) -> Vec<(String, HeaderAction)> {
+        let save = if self.library.is_saved_album(&self.album) {
+            "Saved ✓"
+        } else {
+            "Save"
+        };
+        let mut actions = vec![
+            (save.to_string(), HeaderAction::Save),
+            ("Queue after current album".to_string(), HeaderAction::Queue),
+        ];
+        for (index, artist) in self.album.artists.iter().enumerate() {
+            actions.push((artist.clone(), HeaderAction::Artist(index)));
+        }
+        actions
+    }
+
+    /// The amount of rows taken up by the header, including the actions.
+    fn header_height(&self) -> usize {
+        let height = self.header_lines().len() + 1;
+        if self.cover_visible {
+            height.max(COVER_SIZE.y)
+        } else {
+            height
+        }
+    }
+
+    /// The column the text of the header starts at, next to the cover.
+    fn header_indent(&self) -> usize {
+        if self.cover_visible {
+            COVER_SIZE.x + 2
+        } else {
+            1
+        }
+    }
+
+    /// The action drawn at column `x` of the actions row.
+    fn header_action_at(&self, x: usize) -> Option<HeaderAction> {
+        let mut offset = self.header_indent();
+        for (label, action) in self.header_actions() {
+            let width = label.width() + 2;
+            if (offset..offset + width).contains(&x) {
+                return Some(action);
+            }
+            offset += width + 1;
+        }
+        None
+    }
+
+    fn toggle_save(&mut self) -> String {
+        self.album.save(&self.library);
+        if self.library.is_saved_album(&self.album) {
+            format!("Saved \"{}\"", self.album.title)
+        } else {
+            format!("Removed \"{}\" from the library", self.album.title)
+        }
+    }
+
+    fn queue_after_current_album(&self) -> String {
+        let tracks: Vec<Playable> = self
+            .album
+            .tracks
+            .iter()
+            .flatten()
+            .map(|t| Playable::Track(t.clone()))
+            .collect();
+        self.queue.insert_after_current_album(&tracks);
+        format!("Queued {} tracks after the current album", tracks.len())
+    }
+
+    fn artist_view(&self, index: usize) -> Option<Box<dyn ViewExt>> {
+        let artist = self.album.artists()?.into_iter().nth(index)?;
+        Some(
+            ArtistView::new(self.queue.clone(), self.library.clone(), &artist)
+                .into_boxed_view_ext(),
+        )
     }
 }
 
-impl ViewWrapper for AlbumView {
-    wrap_impl!(self.tabs: TabbedView);
+impl View for AlbumView {
+    fn draw(&self, printer: &Printer<'_, '_>) {
+        if let Some(cover) = self.cover.as_ref().filter(|_| self.cover_visible) {
+            cover.draw(&printer.offset((1, 0)).cropped(COVER_SIZE));
+        }
+
+        let indent = self.header_indent();
+        let lines = self.header_lines();
+        printer.with_color(ColorStyle::secondary(), |printer| {
+            for (row, line) in lines.iter().enumerate() {
+                printer.print((indent, row), line);
+            }
+        });
+
+        let mut offset = indent;
+        for (label, _) in self.header_actions() {
+            let button = format!("[{label}]");
+            printer.with_color(ColorStyle::title_secondary(), |printer| {
+                printer.print((offset, lines.len()), &button);
+            });
+            offset += button.width() + 1;
+        }
+
+        self.tabs.draw(&printer.offset((0, self.header_height())));
+    }
+
+    fn layout(&mut self, size: Vec2) {
+        // Only show the cover if it leaves enough room for the tracks.
+        self.cover_visible =
+            self.cover.is_some() && size.x >= COVER_SIZE.x * 3 && size.y >= COVER_SIZE.y * 3;
+
+        let header_height = self.header_height();
+        self.tabs.layout(size.saturating_sub((0, header_height)));
+    }
+
+    fn required_size(&mut self, constraint: Vec2) -> Vec2 {
+        constraint
+    }
+
+    fn on_event(&mut self, event: Event) -> EventResult {
+        let header_height = self.header_height();
+
+        if let Event::Mouse {
+            event: MouseEvent::Press(MouseButton::Left),
+            position,
+            offset,
+        } = event
+        {
+            if let Some(position) = position.checked_sub(offset) {
+                if position.y == self.header_lines().len() {
+                    match self.header_action_at(position.x) {
+                        Some(HeaderAction::Save) => {
+                            self.toggle_save();
+                        }
+                        Some(HeaderAction::Queue) => {
+                            self.queue_after_current_album();
+                        }
+                        Some(HeaderAction::Artist(index)) => {
+                            if let Some(view) = self.artist_view(index) {
+                                return EventResult::Consumed(Some(Callback::from_fn_once(
+                                    move |s| {
+                                        s.on_layout(|_, mut l| l.push_view(view));
+                                    },
+                                )));
+                            }
+                        }
+                        None => (),
+                    }
+                }
+                if position.y < header_height {
+                    return EventResult::Consumed(None);
+                }
+            }
+        }
+
+        self.tabs.on_event(event.relativized((0, header_height)))
+    }
+
+    fn call_on_any(&mut self, selector: &Selector, callback: AnyCb) {
+        self.tabs.call_on_any(selector, callback);
+    }
+
+    fn focus_view(&mut self, selector: &Selector) -> Result<EventResult, ViewNotFound> {
+        self.tabs.focus_view(selector)
+    }
+
+    fn take_focus(&mut self, source: Direction) -> Result<EventResult, CannotFocus> {
+        self.tabs.take_focus(source)
+    }
+
+    fn important_area(&self, view_size: Vec2) -> Rect {
+        let header_height = self.header_height();
+        self.tabs
+            .important_area(view_size.saturating_sub((0, header_height)))
+            + (0, header_height)
+    }
 }
 
 impl ViewExt for AlbumView {
@@ -71,6 +311,46 @@ impl ViewExt for AlbumView {
     }
 
     fn on_command(&mut self, s: &mut Cursive, cmd: &Command) -> Result<CommandResult, String> {
-        self.tabs.on_command(s, cmd)
+        match cmd {
+            Command::Album(AlbumAction::Save) => {
+                Ok(CommandResult::Consumed(Some(self.toggle_save())))
+            }
+            Command::Album(AlbumAction::Queue) => Ok(CommandResult::Consumed(Some(
+                self.queue_after_current_album(),
+            ))),
+            Command::Album(AlbumAction::Artists) => {
+                // Note: currently cannot return None
+                let artists = self.album.artists().unwrap();
+                match artists.len() {
+                    0 => Ok(CommandResult::Consumed(None)),
+                    1 => Ok(self
+                        .artist_view(0)
+                        .map(CommandResult::View)
+                        .unwrap_or(CommandResult::Consumed(None))),
+                    _ => {
+                        let modal =
+                            select_artist(self.queue.clone(), self.library.clone(), artists);
+                        Ok(CommandResult::Modal(Box::new(modal)))
+                    }
+                }
+            }
+            Command::Export(path, format) => {
+                let tracks: Vec<Playable> = self
+                    .album
+                    .tracks
+                    .iter()
+                    .flatten()
+                    .map(|t| Playable::Track(t.clone()))
+                    .collect();
+                let path = expand_path(path);
+                export(&path, *format, &tracks)?;
+                Ok(CommandResult::Consumed(Some(format!(
+                    "Exported {} tracks to {}",
+                    tracks.len(),
+                    path.display()
+                ))))
+            }
+            _ => self.tabs.on_command(s, cmd),
+        }
     }
 }
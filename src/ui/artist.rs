@@ -17,7 +17,8 @@ use crate::ui::listview::ListView;
 use crate::ui::tabbedview::TabbedView;
 
 pub struct ArtistView {
-    artist: Artist,
+    artist: Arc<RwLock<Artist>>,
+    library: Arc<Library>,
     tabs: TabbedView,
 }
 
@@ -29,12 +30,55 @@ impl ArtistView {
             Self::albums_view(artist, AlbumType::Album, queue.clone(), library.clone());
         let singles_view =
             Self::albums_view(artist, AlbumType::Single, queue.clone(), library.clone());
+        let compilations_view = Self::albums_view(
+            artist,
+            AlbumType::Compilation,
+            queue.clone(),
+            library.clone(),
+        );
+        let appears_on_view =
+            Self::albums_view(artist, AlbumType::AppearsOn, queue.clone(), library.clone());
+
+        let mut artist = artist.clone();
+        artist.is_followed = library.is_followed_artist(&artist);
+        let artist = Arc::new(RwLock::new(artist));
+        {
+            let artist = artist.clone();
+            let spotify = spotify.clone();
+            let library = library.clone();
+            thread::spawn(move || {
+                let id = artist.read().unwrap().id.clone();
+                if let Some(full) = id.and_then(|id| spotify.api.artist(&id)) {
+                    let mut artist = artist.write().unwrap();
+                    artist.followers = Some(full.followers.total);
+                    artist.genres = full.genres;
+                    library.trigger_redraw();
+                }
+            });
+        }
+
+        let discography: Arc<RwLock<Vec<Album>>> = Arc::new(RwLock::new(Vec::new()));
+        {
+            let discography = discography.clone();
+            let spotify = spotify.clone();
+            let id = artist.read().unwrap().id.clone();
+            let library = library.clone();
+            thread::spawn(move || {
+                if let Some(id) = id {
+                    let mut albums = spotify.api.artist_discography(&id);
+                    albums.sort_by(|a, b| a.release_date.cmp(&b.release_date));
+
+                    discography.write().unwrap().extend(albums);
+                    library.trigger_redraw();
+                }
+            });
+        }
 
         let top_tracks: Arc<RwLock<Vec<Track>>> = Arc::new(RwLock::new(Vec::new()));
         {
             let top_tracks = top_tracks.clone();
             let spotify = spotify.clone();
-            let id = artist.id.clone();
+            let id = artist.read().unwrap().id.clone();
             let library = library.clone();
             thread::spawn(move || {
                 if let Some(id) = id {
@@ -49,7 +93,7 @@ impl ArtistView {
         let related: Arc<RwLock<Vec<Artist>>> = Arc::new(RwLock::new(Vec::new()));
         {
             let related = related.clone();
-            let id = artist.id.clone();
+            let id = artist.read().unwrap().id.clone();
             let library = library.clone();
             thread::spawn(move || {
                 if let Some(id) = id {
@@ -63,7 +107,7 @@ impl ArtistView {
 
         let mut tabs = TabbedView::new();
 
-        if let Some(tracks) = artist.tracks.as_ref() {
+        if let Some(tracks) = artist.read().unwrap().tracks.as_ref() {
             let tracks = tracks.clone();
 
             tabs.add_tab(
@@ -79,16 +123,43 @@ impl ArtistView {
             "Top 10",
             ListView::new(top_tracks, queue.clone(), library.clone()),
         );
+        tabs.add_tab(
+            "Discography",
+            ListView::new(discography, queue.clone(), library.clone()),
+        );
         tabs.add_tab("Albums", albums_view);
         tabs.add_tab("Singles", singles_view);
-        tabs.add_tab("Related Artists", ListView::new(related, queue, library));
+        tabs.add_tab("Compilations", compilations_view);
+        tabs.add_tab("Appears on", appears_on_view);
+        tabs.add_tab(
+            "Related Artists",
+            ListView::new(related, queue, library.clone()),
+        );
 
         Self {
-            artist: artist.clone(),
+            artist,
+            library,
             tabs,
         }
     }
 
+    /// Follow the artist, or unfollow it if it's already followed. The
+    /// request is made in the background.
+    fn toggle_follow(&mut self) {
+        let artist = self.artist.read().unwrap().clone();
+        let shared = self.artist.clone();
+        let library = self.library.clone();
+        thread::spawn(move || {
+            if artist.is_followed {
+                library.unfollow_artist(&artist);
+            } else {
+                library.follow_artist(&artist);
+            }
+            shared.write().unwrap().is_followed = library.is_followed_artist(&artist);
+            library.trigger_redraw();
+        });
+    }
+
     fn albums_view(
         artist: &Artist,
         album_type: AlbumType,
@@ -114,10 +185,30 @@ impl ViewWrapper for ArtistView {
 
 impl ViewExt for ArtistView {
     fn title(&self) -> String {
-        self.artist.name.clone()
+        self.artist.read().unwrap().name.clone()
+    }
+
+    fn title_sub(&self) -> String {
+        let artist = self.artist.read().unwrap();
+        let mut header = Vec::new();
+        if artist.is_followed {
+            header.push("✓ Following".to_string());
+        }
+        if let Some(followers) = artist.followers {
+            header.push(format!("{followers} followers"));
+        }
+        if !artist.genres.is_empty() {
+            header.push(artist.genres.join(", "));
+        }
+        header.join(" · ")
     }
 
     fn on_command(&mut self, s: &mut Cursive, cmd: &Command) -> Result<CommandResult, String> {
+        if let Command::Follow = cmd {
+            self.toggle_follow();
+            return Ok(CommandResult::Consumed(None));
+        }
+
         self.tabs.on_command(s, cmd)
     }
 }
@@ -6,28 +6,47 @@ use cursive::Cursive;
 use crate::command::Command;
 use crate::commands::CommandResult;
 use crate::library::Library;
-use crate::model::category::Category;
+use crate::model::playlist::Playlist;
 use crate::queue::Queue;
 use crate::traits::ViewExt;
 
 use crate::ui::listview::ListView;
+use crate::ui::tabbedview::TabbedView;
 
 pub struct BrowseView {
-    list: ListView<Category>,
+    tabs: TabbedView,
 }
 
 impl BrowseView {
     pub fn new(queue: Arc<Queue>, library: Arc<Library>) -> Self {
-        let categories = queue.get_spotify().api.categories();
-        let list = ListView::new(categories.items.clone(), queue, library);
-        categories.apply_pagination(list.get_pagination());
+        let spotify = queue.get_spotify();
 
-        Self { list }
+        let categories = spotify.api.categories();
+        let categories_list = ListView::new(categories.items.clone(), queue.clone(), library.clone());
+        categories.apply_pagination(categories_list.get_pagination());
+
+        let releases = spotify.api.new_releases();
+        let releases_list = ListView::new(releases.items.clone(), queue.clone(), library.clone());
+        releases.apply_pagination(releases_list.get_pagination());
+
+        let (featured, message) = spotify.api.featured_playlists();
+        let featured_list = FeaturedPlaylistsView {
+            list: ListView::new(featured.items.clone(), queue, library),
+            message: message.unwrap_or_default(),
+        };
+        featured.apply_pagination(featured_list.list.get_pagination());
+
+        let mut tabs = TabbedView::new();
+        tabs.add_tab("Categories", categories_list);
+        tabs.add_tab("New releases", releases_list);
+        tabs.add_tab("Featured playlists", featured_list);
+
+        Self { tabs }
     }
 }
 
 impl ViewWrapper for BrowseView {
-    wrap_impl!(self.list: ListView<Category>);
+    wrap_impl!(self.tabs: TabbedView);
 }
 
 impl ViewExt for BrowseView {
@@ -35,6 +54,30 @@ impl ViewExt for BrowseView {
         "Browse".to_string()
     }
 
+    fn title_sub(&self) -> String {
+        self.tabs.title_sub()
+    }
+
+    fn on_command(&mut self, s: &mut Cursive, cmd: &Command) -> Result<CommandResult, String> {
+        self.tabs.on_command(s, cmd)
+    }
+}
+
+/// The featured playlists, which come with a message from Spotify.
+struct FeaturedPlaylistsView {
+    list: ListView<Playlist>,
+    message: String,
+}
+
+impl ViewWrapper for FeaturedPlaylistsView {
+    wrap_impl!(self.list: ListView<Playlist>);
+}
+
+impl ViewExt for FeaturedPlaylistsView {
+    fn title_sub(&self) -> String {
+        self.message.clone()
+    }
+
     fn on_command(&mut self, s: &mut Cursive, cmd: &Command) -> Result<CommandResult, String> {
         self.list.on_command(s, cmd)
     }
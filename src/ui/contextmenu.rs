@@ -7,10 +7,12 @@ use cursive::Cursive;
 use crate::commands::CommandResult;
 use crate::ext_traits::SelectViewExt;
 use crate::library::Library;
+use crate::model::playable::Playable;
 use crate::model::track::Track;
 use crate::queue::Queue;
 use crate::spotify::PlayerEvent;
 use crate::traits::{ListItem, ViewExt};
+use crate::ui::info::info_dialog;
 use crate::ui::layout::Layout;
 use crate::ui::modal::Modal;
 use crate::command::Command;
@@ -24,6 +26,7 @@ pub struct ContextMenu {
 enum ContextMenuAction {
     ShowItem(Box<dyn ListItem>),
     ShowRecommendations(Box<Track>),
+    ShowInfo(Box<Playable>),
     Play(Box<dyn ListItem>),
     PlayNext(Box<dyn ListItem>),
     TogglePlayback,
@@ -79,6 +82,10 @@ impl ContextMenu {
             )
         }
 
+        if let Some(p) = item.playable() {
+            content.add_item("Show info", ContextMenuAction::ShowInfo(Box::new(p)))
+        }
+
         // open detail view of artist/album
         {
             let library = library.clone();
@@ -98,6 +105,9 @@ impl ContextMenu {
                             s.call_on_name("main", move |v: &mut Layout| v.push_view(view));
                         }
                     }
+                    ContextMenuAction::ShowInfo(item) => {
+                        s.add_layer(info_dialog(&queue.get_spotify().api, item));
+                    }
                     ContextMenuAction::Play(item) => item.as_listitem().play(&queue),
                     ContextMenuAction::PlayNext(item) => item.as_listitem().play_next(&queue),
                     ContextMenuAction::TogglePlayback => queue.toggleplayback(),
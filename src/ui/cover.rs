@@ -0,0 +1,361 @@
+use std::cell::RefCell;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use cursive::theme::{Color, ColorStyle};
+use cursive::view::View;
+use cursive::{Cursive, CursiveRunner, Printer, Vec2};
+use image::imageops::FilterType;
+use image::RgbImage;
+use log::{debug, error};
+
+use crate::cover::Covers;
+
+/// The size of a cover in cells. Cells are about twice as high as they are
+/// wide, so this is roughly square.
+pub const COVER_SIZE: Vec2 = Vec2 { x: 16, y: 8 };
+
+/// The size of a cell in pixels if the terminal doesn't tell.
+const DEFAULT_CELL_SIZE: Vec2 = Vec2 { x: 8, y: 16 };
+
+/// How covers are drawn in the terminal.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Protocol {
+    /// Pick the best protocol the terminal is known to support.
+    #[default]
+    Auto,
+    /// Coloured Unicode half blocks, which work in any terminal with colours.
+    HalfBlocks,
+    /// The sixel graphics protocol.
+    Sixel,
+    /// The kitty graphics protocol.
+    Kitty,
+    /// Don't show covers.
+    Off,
+}
+
+impl Protocol {
+    /// The protocol to use for `Auto`, from the environment of the terminal.
+    fn detect() -> Self {
+        let var = |name| std::env::var(name).unwrap_or_default();
+        let term = var("TERM");
+        let program = var("TERM_PROGRAM");
+
+        if std::env::var_os("KITTY_WINDOW_ID").is_some()
+            || term == "xterm-kitty"
+            || term == "xterm-ghostty"
+            || program == "WezTerm"
+            || program == "ghostty"
+        {
+            Self::Kitty
+        } else if term.contains("sixel")
+            || term.starts_with("foot")
+            || term.starts_with("mlterm")
+            || term.starts_with("yaft")
+        {
+            Self::Sixel
+        } else {
+            Self::HalfBlocks
+        }
+    }
+}
+
+/// The `[cover]` section of the configuration file.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct CoverConfig {
+    pub protocol: Option<Protocol>,
+}
+
+/// A cover drawn with a graphics protocol, in screen cells.
+#[derive(Clone, PartialEq, Eq)]
+struct Placement {
+    url: String,
+    position: Vec2,
+    size: Vec2,
+}
+
+/// Covers drawn with a graphics protocol are written to the terminal after
+/// cursive is done drawing, as it only knows about text. This keeps the
+/// covers of the frame that is being drawn and the ones that are on screen.
+struct Graphics {
+    protocol: Protocol,
+    frame: Option<Vec<Placement>>,
+    shown: Vec<Placement>,
+    /// The size of the screen the shown covers were drawn on. The terminal
+    /// may drop them when it is resized.
+    screen_size: Vec2,
+}
+
+static GRAPHICS: Mutex<Graphics> = Mutex::new(Graphics {
+    protocol: Protocol::Off,
+    frame: None,
+    shown: Vec::new(),
+    screen_size: Vec2 { x: 0, y: 0 },
+});
+
+/// Set up how covers are drawn.
+pub fn init(config: Option<CoverConfig>) {
+    let protocol = match config.and_then(|c| c.protocol).unwrap_or_default() {
+        Protocol::Auto => Protocol::detect(),
+        protocol => protocol,
+    };
+    debug!("drawing covers with {:?}", protocol);
+    GRAPHICS.lock().unwrap().protocol = protocol;
+}
+
+/// Whether covers are drawn at all.
+pub fn enabled() -> bool {
+    GRAPHICS.lock().unwrap().protocol != Protocol::Off
+}
+
+/// Start a new frame. Called before the views are drawn.
+pub fn begin_frame() {
+    GRAPHICS.lock().unwrap().frame = Some(Vec::new());
+}
+
+/// Write the covers of the frame that was just drawn to the terminal, if they
+/// changed. Covers are hidden while a dialog is shown, as they would be drawn
+/// over it.
+pub fn flush(cursive: &mut CursiveRunner<Cursive>) {
+    let screen_size = cursive.screen_size();
+    let (protocol, frame, shown, resized) = {
+        let mut graphics = GRAPHICS.lock().unwrap();
+        let Some(frame) = graphics.frame.take() else {
+            return;
+        };
+        let resized = graphics.screen_size != screen_size;
+        (graphics.protocol, frame, graphics.shown.clone(), resized)
+    };
+    let frame = if cursive.screen().len() > 1 {
+        Vec::new()
+    } else {
+        frame
+    };
+    if frame == shown && !resized {
+        return;
+    }
+
+    let mut output = Vec::new();
+    if !shown.is_empty() {
+        match protocol {
+            Protocol::Kitty => output.extend_from_slice(b"\x1b_Ga=d,d=A,q=2\x1b\\"),
+            _ => {
+                // Sixel images stay until the cells under them are written, so
+                // the whole screen has to be drawn again.
+                cursive.clear();
+                cursive.refresh();
+                GRAPHICS.lock().unwrap().frame = None;
+            }
+        }
+    }
+
+    for placement in &frame {
+        let Some(image) = Covers::get(&placement.url) else {
+            continue;
+        };
+        // Save the cursor and restore it afterwards, so the terminal backend
+        // doesn't lose track of it.
+        write!(
+            output,
+            "\x1b7\x1b[{};{}H",
+            placement.position.y + 1,
+            placement.position.x + 1
+        )
+        .unwrap();
+        match protocol {
+            Protocol::Kitty => kitty(&mut output, &image, placement.size),
+            _ => sixel(&mut output, &image, placement.size),
+        }
+        output.extend_from_slice(b"\x1b8");
+    }
+
+    let mut stdout = std::io::stdout().lock();
+    if let Err(e) = stdout.write_all(&output).and_then(|_| stdout.flush()) {
+        error!("could not draw cover: {}", e);
+    }
+    let mut graphics = GRAPHICS.lock().unwrap();
+    graphics.shown = frame;
+    graphics.screen_size = screen_size;
+}
+
+/// Write `image` in the kitty graphics protocol, scaled to `size` cells.
+fn kitty(output: &mut Vec<u8>, image: &RgbImage, size: Vec2) {
+    let data = STANDARD.encode(image.as_raw());
+    let chunks: Vec<&[u8]> = data.as_bytes().chunks(4096).collect();
+    for (index, chunk) in chunks.iter().enumerate() {
+        let more = u8::from(index + 1 < chunks.len());
+        if index == 0 {
+            write!(
+                output,
+                "\x1b_Ga=T,f=24,s={},v={},c={},r={},C=1,q=2,m={};",
+                image.width(),
+                image.height(),
+                size.x,
+                size.y,
+                more
+            )
+            .unwrap();
+        } else {
+            write!(output, "\x1b_Gm={};", more).unwrap();
+        }
+        output.extend_from_slice(chunk);
+        output.extend_from_slice(b"\x1b\\");
+    }
+}
+
+/// Write `image` as sixels, scaled to `size` cells. The colours are reduced to
+/// a palette of 6 levels of red, green and blue.
+fn sixel(output: &mut Vec<u8>, image: &RgbImage, size: Vec2) {
+    let cell = cell_size();
+    let image = image::imageops::resize(
+        image,
+        (size.x * cell.x) as u32,
+        (size.y * cell.y) as u32,
+        FilterType::Triangle,
+    );
+    let (width, height) = (image.width() as usize, image.height() as usize);
+    let level = |value: u8| (value as usize * 5 + 127) / 255;
+
+    write!(output, "\x1bP0;1;0q\"1;1;{};{}", width, height).unwrap();
+    for color in 0..216 {
+        let (r, g, b) = (color / 36, color / 6 % 6, color % 6);
+        write!(output, "#{};2;{};{};{}", color, r * 20, g * 20, b * 20).unwrap();
+    }
+
+    for top in (0..height).step_by(6) {
+        // The bits of every column for each colour used in this band.
+        let mut colors: Vec<Option<Vec<u8>>> = vec![None; 216];
+        for y in top..(top + 6).min(height) {
+            for x in 0..width {
+                let pixel = image.get_pixel(x as u32, y as u32);
+                let color = level(pixel[0]) * 36 + level(pixel[1]) * 6 + level(pixel[2]);
+                colors[color].get_or_insert_with(|| vec![0; width])[x] |= 1 << (y - top);
+            }
+        }
+
+        let mut first = true;
+        for (color, bits) in colors.iter().enumerate() {
+            let Some(bits) = bits else {
+                continue;
+            };
+            if !first {
+                output.push(b'$');
+            }
+            first = false;
+            write!(output, "#{}", color).unwrap();
+
+            let mut x = 0;
+            while x < width {
+                let run = bits[x..].iter().take_while(|b| **b == bits[x]).count();
+                let sixel = b'?' + bits[x];
+                if run > 3 {
+                    write!(output, "!{}", run).unwrap();
+                    output.push(sixel);
+                } else {
+                    output.extend(std::iter::repeat_n(sixel, run));
+                }
+                x += run;
+            }
+        }
+        output.push(b'-');
+    }
+    output.extend_from_slice(b"\x1b\\");
+}
+
+/// The size of a cell in pixels.
+#[cfg(unix)]
+fn cell_size() -> Vec2 {
+    let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+    let result = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) };
+    if result != 0 || size.ws_col == 0 || size.ws_row == 0 || size.ws_xpixel == 0 {
+        return DEFAULT_CELL_SIZE;
+    }
+    Vec2::new(
+        (size.ws_xpixel / size.ws_col) as usize,
+        (size.ws_ypixel / size.ws_row) as usize,
+    )
+}
+
+#[cfg(not(unix))]
+fn cell_size() -> Vec2 {
+    DEFAULT_CELL_SIZE
+}
+
+/// Draws the cover at a URL. It is drawn with half blocks, or left empty for
+/// a graphics protocol to draw it after the frame.
+pub struct CoverView {
+    url: String,
+    /// The cover scaled for the last size it was drawn at.
+    scaled: RefCell<Option<(Vec2, RgbImage)>>,
+}
+
+impl CoverView {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            scaled: RefCell::new(None),
+        }
+    }
+
+    fn draw_half_blocks(&self, printer: &Printer<'_, '_>, image: &Arc<RgbImage>) {
+        let size = printer.size;
+        let mut scaled = self.scaled.borrow_mut();
+        if scaled.as_ref().map(|(s, _)| *s) != Some(size) {
+            let image = image::imageops::resize(
+                image.as_ref(),
+                size.x as u32,
+                size.y as u32 * 2,
+                FilterType::Triangle,
+            );
+            *scaled = Some((size, image));
+        }
+        let Some((_, image)) = scaled.as_ref() else {
+            return;
+        };
+
+        let rgb = |x: usize, y: usize| {
+            let pixel = image.get_pixel(x as u32, y as u32);
+            Color::Rgb(pixel[0], pixel[1], pixel[2])
+        };
+        for y in 0..size.y {
+            for x in 0..size.x {
+                let style = ColorStyle::new(rgb(x, y * 2), rgb(x, y * 2 + 1));
+                printer.with_color(style, |printer| printer.print((x, y), "▀"));
+            }
+        }
+    }
+}
+
+impl View for CoverView {
+    fn draw(&self, printer: &Printer<'_, '_>) {
+        let Some(image) = Covers::get(&self.url) else {
+            return;
+        };
+
+        let mut graphics = GRAPHICS.lock().unwrap();
+        // Graphics can't be cropped, so covers that are partly hidden fall
+        // back to half blocks.
+        let visible = printer.output_size == printer.size && printer.content_offset == Vec2::zero();
+        match (graphics.protocol, graphics.frame.as_mut()) {
+            (Protocol::Sixel | Protocol::Kitty, Some(frame)) if visible => {
+                frame.push(Placement {
+                    url: self.url.clone(),
+                    position: printer.offset,
+                    size: printer.size,
+                });
+            }
+            (Protocol::Off, _) => {}
+            _ => {
+                drop(graphics);
+                self.draw_half_blocks(printer, &image);
+            }
+        }
+    }
+
+    fn required_size(&mut self, _constraint: Vec2) -> Vec2 {
+        COVER_SIZE
+    }
+}
@@ -0,0 +1,157 @@
+use cursive::direction::Direction;
+use cursive::event::EventResult;
+use cursive::theme::ColorStyle;
+use cursive::view::{CannotFocus, View};
+use cursive::{Cursive, Printer, Vec2};
+
+use crate::command::{Command, MoveAmount, MoveMode};
+use crate::commands::CommandResult;
+use crate::equalizer::{Equalizer, EqualizerSettings, MAX_GAIN};
+use crate::traits::ViewExt;
+
+/// The width of the labels in front of the sliders.
+const LABEL_WIDTH: usize = 8;
+
+/// The width of the values behind the sliders.
+const VALUE_WIDTH: usize = 10;
+
+/// The gain in dB a single step changes a slider by.
+const STEP: f32 = 0.5;
+
+/// A readable label for `frequency` in Hz.
+pub fn frequency_label(frequency: f32) -> String {
+    if frequency >= 1000.0 {
+        format!("{} kHz", frequency / 1000.0)
+    } else {
+        format!("{frequency} Hz")
+    }
+}
+
+/// Shows the equalizer settings with a slider for the preamp and every band.
+/// The selected slider is moved left and right with the move commands.
+pub struct EqualizerView {
+    equalizer: Equalizer,
+    /// The selected slider, the preamp first and then the bands.
+    selected: usize,
+}
+
+impl EqualizerView {
+    pub fn new(equalizer: Equalizer) -> Self {
+        Self {
+            equalizer,
+            selected: 0,
+        }
+    }
+
+    /// The labels and gains of the sliders.
+    fn sliders(settings: &EqualizerSettings) -> Vec<(String, f32)> {
+        let mut sliders = vec![("Preamp".to_string(), settings.preamp)];
+        for band in &settings.bands {
+            sliders.push((frequency_label(band.frequency), band.gain));
+        }
+        sliders
+    }
+
+    fn status(settings: &EqualizerSettings) -> String {
+        let state = if settings.bypass { "bypassed" } else { "on" };
+        let preset = settings.preset.as_deref().unwrap_or("custom");
+        let limiter = if settings.limiter { "on" } else { "off" };
+        format!("Equalizer {state} · preset {preset} · limiter {limiter}")
+    }
+
+    /// Change the gain of the selected slider by `steps`.
+    fn change_gain(&self, steps: f32) -> Result<(), String> {
+        let selected = self.selected;
+        self.equalizer.update(|settings| {
+            if selected == 0 {
+                settings.set_preamp(settings.preamp + steps * STEP);
+                Ok(())
+            } else {
+                let gain = settings.bands[selected - 1].gain;
+                settings.set_gain(selected - 1, gain + steps * STEP)
+            }
+        })
+    }
+}
+
+impl View for EqualizerView {
+    fn draw(&self, printer: &Printer<'_, '_>) {
+        let settings = self.equalizer.settings();
+        printer.print((1, 0), &Self::status(&settings));
+
+        let width = printer.size.x.saturating_sub(LABEL_WIDTH + VALUE_WIDTH + 2);
+        let center = width / 2;
+        for (index, (label, gain)) in Self::sliders(&settings).into_iter().enumerate() {
+            let row = index + 2;
+            let style = if index == self.selected && printer.focused {
+                ColorStyle::highlight()
+            } else {
+                ColorStyle::primary()
+            };
+            printer.with_color(style, |printer| {
+                printer.print((1, row), &format!("{label:<LABEL_WIDTH$}"));
+            });
+
+            // The slider fills from the center towards the gain.
+            let filled = ((gain.abs() / MAX_GAIN) * center as f32).round() as usize;
+            let (start, end) = if gain < 0.0 {
+                (center - filled, center)
+            } else {
+                (center, center + filled)
+            };
+            let x = LABEL_WIDTH + 1;
+            printer.with_color(ColorStyle::secondary(), |printer| {
+                printer.print_hline((x, row), width, "─");
+                printer.print((x + center, row), "┼");
+            });
+            printer.with_color(ColorStyle::title_primary(), |printer| {
+                printer.print_hline((x + start, row), end - start, "━");
+            });
+            printer.print((x + width + 1, row), &format!("{gain:+.1} dB"));
+        }
+
+        let help = "←/→ change the gain · :eq preset, :eq bypass, :eq limiter";
+        printer.with_color(ColorStyle::secondary(), |printer| {
+            printer.print((1, settings.bands.len() + 4), help);
+        });
+    }
+
+    fn required_size(&mut self, constraint: Vec2) -> Vec2 {
+        constraint
+    }
+
+    fn take_focus(&mut self, _source: Direction) -> Result<EventResult, CannotFocus> {
+        Ok(EventResult::Consumed(None))
+    }
+}
+
+impl ViewExt for EqualizerView {
+    fn title(&self) -> String {
+        "Equalizer".to_string()
+    }
+
+    fn on_command(&mut self, _s: &mut Cursive, cmd: &Command) -> Result<CommandResult, String> {
+        let Command::Move(mode, amount) = cmd else {
+            return Ok(CommandResult::Ignored);
+        };
+        let sliders = self.equalizer.settings().bands.len() + 1;
+        let steps = match amount {
+            MoveAmount::Integer(steps) => *steps,
+            MoveAmount::Float(steps) => steps.round() as i32,
+            MoveAmount::Extreme => i32::MAX,
+        };
+
+        match mode {
+            MoveMode::Up => {
+                self.selected = self.selected.saturating_sub(steps.max(0) as usize);
+            }
+            MoveMode::Down => {
+                self.selected = (self.selected + steps.max(0) as usize).min(sliders - 1);
+            }
+            MoveMode::Left => self.change_gain(-(steps as f32))?,
+            MoveMode::Right => self.change_gain(steps as f32)?,
+            MoveMode::Playing => return Ok(CommandResult::Ignored),
+        }
+        Ok(CommandResult::Consumed(None))
+    }
+}
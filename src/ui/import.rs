@@ -0,0 +1,209 @@
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+use std::thread;
+
+use cursive::view::{Margins, ViewWrapper};
+use cursive::views::{Dialog, ScrollView, SelectView};
+use cursive::Cursive;
+
+use crate::command::{Command, ImportAction, TargetMode};
+use crate::commands::CommandResult;
+use crate::export::expand_path;
+use crate::import::{candidates, parse, ImportMatch};
+use crate::library::Library;
+use crate::model::playable::Playable;
+use crate::model::track::Track;
+use crate::queue::Queue;
+use crate::traits::ViewExt;
+use crate::ui::listview::ListView;
+use crate::ui::modal::Modal;
+
+/// Reviews the tracks matched for the lines of an imported file. Matching
+/// happens in the background, the list fills up as lines are matched.
+pub struct ImportView {
+    name: String,
+    total: usize,
+    matches: Arc<RwLock<Vec<ImportMatch>>>,
+    list: ListView<ImportMatch>,
+    queue: Arc<Queue>,
+    library: Arc<Library>,
+}
+
+impl ImportView {
+    pub fn new(queue: Arc<Queue>, library: Arc<Library>, path: &str) -> Result<Self, String> {
+        let path = expand_path(path);
+        let lines = parse(&path)?;
+        if lines.is_empty() {
+            return Err(format!("No tracks found in {}", path.display()));
+        }
+
+        let total = lines.len();
+        let matches = Arc::new(RwLock::new(Vec::new()));
+        {
+            let matches = matches.clone();
+            let library = library.clone();
+            let api = queue.get_spotify().api;
+            thread::spawn(move || {
+                for line in lines {
+                    let candidates = candidates(&api, &line);
+                    matches
+                        .write()
+                        .unwrap()
+                        .push(ImportMatch::new(line, candidates));
+                    library.trigger_redraw();
+                }
+            });
+        }
+
+        Ok(Self {
+            name: Path::new(&path)
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            total,
+            list: ListView::new(matches.clone(), queue.clone(), library.clone()),
+            matches,
+            queue,
+            library,
+        })
+    }
+
+    fn is_done(&self) -> bool {
+        self.matches.read().unwrap().len() == self.total
+    }
+
+    /// The chosen tracks, in the order of the file.
+    fn tracks(&self) -> Vec<Track> {
+        self.matches
+            .read()
+            .unwrap()
+            .iter()
+            .filter_map(|m| m.candidate().map(|c| c.track.clone()))
+            .collect()
+    }
+
+    /// Report the outcome of an import, listing the lines without a track.
+    fn report(&self, outcome: String) -> CommandResult {
+        let unmatched: Vec<String> = self
+            .matches
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|m| m.candidate().is_none())
+            .map(|m| m.line.text.clone())
+            .collect();
+        if unmatched.is_empty() {
+            return CommandResult::Consumed(Some(outcome));
+        }
+
+        let text = format!(
+            "{}\n\nThese {} lines weren't imported:\n{}",
+            outcome,
+            unmatched.len(),
+            unmatched.join("\n")
+        );
+        let dialog = Dialog::text(text).title("Import").dismiss_button("Close");
+        CommandResult::Modal(Box::new(Modal::new(dialog)))
+    }
+
+    /// A dialog to choose another candidate for the selected line, or to skip
+    /// it.
+    fn alternatives(&self) -> Option<Modal<Dialog>> {
+        let index = self.list.get_selected_index();
+        let selected = self.matches.read().unwrap().get(index)?.clone();
+
+        let mut select = SelectView::<Option<usize>>::new();
+        for (i, candidate) in selected.candidates.iter().enumerate() {
+            let track = &candidate.track;
+            select.add_item(
+                format!(
+                    "{:>3.0}%  {} - {} ({}) {}",
+                    candidate.score * 100.0,
+                    track.artists.join(", "),
+                    track.title,
+                    track.album.clone().unwrap_or_default(),
+                    track.duration_str()
+                ),
+                Some(i),
+            );
+        }
+        select.add_item("Skip this line", None);
+        let _ = select.set_selection(selected.selected.unwrap_or(selected.candidates.len()));
+
+        let matches = self.matches.clone();
+        select.set_on_submit(move |s: &mut Cursive, choice: &Option<usize>| {
+            if let Some(m) = matches.write().unwrap().get_mut(index) {
+                m.selected = *choice;
+            }
+            s.pop_layer();
+        });
+
+        let dialog = Dialog::new()
+            .title(format!("Matches for \"{}\"", selected.line.text))
+            .dismiss_button("Close")
+            .padding(Margins::lrtb(1, 1, 1, 0))
+            .content(ScrollView::new(select));
+        Some(Modal::new(dialog))
+    }
+}
+
+impl ViewWrapper for ImportView {
+    wrap_impl!(self.list: ListView<ImportMatch>);
+}
+
+impl ViewExt for ImportView {
+    fn title(&self) -> String {
+        format!("Import {}", self.name)
+    }
+
+    fn title_sub(&self) -> String {
+        let matches = self.matches.read().unwrap();
+        if matches.len() < self.total {
+            return format!("matching {}/{} lines", matches.len(), self.total);
+        }
+
+        let unmatched = matches.iter().filter(|m| m.candidate().is_none()).count();
+        let uncertain = matches.iter().filter(|m| m.is_uncertain()).count();
+        format!(
+            "{} matched, {} uncertain, {} unmatched",
+            self.total - unmatched,
+            uncertain,
+            unmatched
+        )
+    }
+
+    fn on_command(&mut self, s: &mut Cursive, cmd: &Command) -> Result<CommandResult, String> {
+        match cmd {
+            Command::Open(TargetMode::Selected) => {
+                return Ok(match self.alternatives() {
+                    Some(dialog) => CommandResult::Modal(Box::new(dialog)),
+                    None => CommandResult::Consumed(None),
+                });
+            }
+            Command::Import(ImportAction::Queue | ImportAction::Playlist(_)) if !self.is_done() => {
+                return Err("Still matching, wait for all lines to be matched".into());
+            }
+            Command::Import(ImportAction::Queue) => {
+                let tracks = self.tracks();
+                for track in tracks.iter() {
+                    self.queue.append(Playable::Track(track.clone()));
+                }
+                return Ok(self.report(format!("Queued {} tracks", tracks.len())));
+            }
+            Command::Import(ImportAction::Playlist(name)) => {
+                let tracks = self.tracks();
+                self.library
+                    .create_playlist(name, &tracks)
+                    .ok_or("Could not create the playlist")?;
+                return Ok(self.report(format!(
+                    "Created playlist \"{}\" with {} tracks",
+                    name,
+                    tracks.len()
+                )));
+            }
+            _ => {}
+        }
+
+        self.list.on_command(s, cmd)
+    }
+}
@@ -0,0 +1,61 @@
+use std::sync::Arc;
+
+use cursive::view::ViewWrapper;
+use cursive::Cursive;
+
+use crate::command::{Command, InboxAction};
+use crate::commands::CommandResult;
+use crate::library::Library;
+use crate::model::episode::Episode;
+use crate::queue::Queue;
+use crate::traits::ViewExt;
+use crate::ui::listview::ListView;
+
+/// Lists the episodes of saved shows that were released since the inbox was
+/// last checked.
+pub struct InboxView {
+    list: ListView<Episode>,
+    library: Arc<Library>,
+}
+
+impl InboxView {
+    pub fn new(queue: Arc<Queue>, library: Arc<Library>) -> Self {
+        Self {
+            list: ListView::new(library.inbox.clone(), queue, library.clone()),
+            library,
+        }
+    }
+}
+
+impl ViewWrapper for InboxView {
+    wrap_impl!(self.list: ListView<Episode>);
+}
+
+impl ViewExt for InboxView {
+    fn title(&self) -> String {
+        "Inbox".to_string()
+    }
+
+    fn title_sub(&self) -> String {
+        let count = self.library.inbox.read().unwrap().len();
+        format!("{count} new episodes")
+    }
+
+    fn on_command(&mut self, s: &mut Cursive, cmd: &Command) -> Result<CommandResult, String> {
+        if let Command::Inbox(InboxAction::Dismiss) = cmd {
+            let selected = self
+                .library
+                .inbox
+                .read()
+                .unwrap()
+                .get(self.list.get_selected_index())
+                .cloned();
+            if let Some(episode) = selected {
+                self.library.dismiss_from_inbox(&episode);
+            }
+            return Ok(CommandResult::Consumed(None));
+        }
+
+        self.list.on_command(s, cmd)
+    }
+}
@@ -0,0 +1,116 @@
+use cursive::traits::Resizable;
+use cursive::view::{Margins, Nameable};
+use cursive::views::{Dialog, NamedView, ScrollView, TextView};
+use rspotify::model::{EpisodeId, Id};
+
+use crate::model::episode::Episode;
+use crate::model::playable::Playable;
+use crate::model::track::Track;
+use crate::spotify_api::WebApi;
+use crate::ui::modal::Modal;
+use crate::utils::strip_html;
+
+fn yes_no(value: bool) -> String {
+    if value { "yes" } else { "no" }.to_string()
+}
+
+fn track_rows(api: &WebApi, track: &Track) -> Vec<(&'static str, String)> {
+    let mut rows = vec![
+        ("Title", track.title.clone()),
+        ("Artists", track.artists.join(", ")),
+    ];
+    if let Some(album) = &track.album {
+        rows.push(("Album", album.clone()));
+    }
+    if !track.album_artists.is_empty() {
+        rows.push(("Album artists", track.album_artists.join(", ")));
+    }
+    rows.push((
+        "Track",
+        format!("{} (disc {})", track.track_number, track.disc_number),
+    ));
+    rows.push(("Duration", track.duration_str()));
+    if let Some(added_at) = track.added_at {
+        rows.push(("Added", added_at.format("%Y-%m-%d %H:%M").to_string()));
+    }
+    rows.push(("URI", track.uri.clone()));
+    rows.push(("URL", track.url.clone()));
+    rows.push(("Local", yes_no(track.is_local)));
+    if let Some(is_playable) = track.is_playable {
+        rows.push(("Playable", yes_no(is_playable)));
+    }
+
+    // local tracks are unknown to the Web API
+    let Some(id) = &track.id else {
+        return rows;
+    };
+
+    if let Some(full_track) = api.track(id) {
+        if let Some(isrc) = full_track.external_ids.get("isrc") {
+            rows.push(("ISRC", isrc.clone()));
+        }
+        rows.push(("Popularity", format!("{}/100", full_track.popularity)));
+        rows.push(("Explicit", yes_no(full_track.explicit)));
+    }
+
+    if let Some(features) = api.track_features(id) {
+        rows.push(("Tempo", format!("{:.0} BPM", features.tempo)));
+        if let Some(key) = features.key_name() {
+            rows.push(("Key", key));
+        }
+        rows.push(("Energy", format!("{:.2}", features.energy)));
+        rows.push(("Danceability", format!("{:.2}", features.danceability)));
+        rows.push(("Valence", format!("{:.2}", features.valence)));
+    }
+
+    rows
+}
+
+fn episode_rows(episode: &Episode) -> Vec<(&'static str, String)> {
+    let mut rows = vec![
+        ("Title", episode.name.clone()),
+        ("Released", episode.release_date.clone()),
+        ("Duration", episode.duration_str()),
+    ];
+    if let Some(added_at) = episode.added_at {
+        rows.push(("Added", added_at.format("%Y-%m-%d %H:%M").to_string()));
+    }
+    rows.push(("URI", episode.uri.clone()));
+    if let Ok(id) = EpisodeId::from_id(&episode.id) {
+        rows.push(("URL", id.url()));
+    }
+    rows
+}
+
+/// A dialog listing everything that is known about `playable`. Details that
+/// aren't part of the model, like the popularity and audio features of
+/// tracks, are fetched from the Web API.
+pub fn info_dialog(api: &WebApi, playable: &Playable) -> NamedView<Modal<Dialog>> {
+    let (title, rows, description) = match playable {
+        Playable::Track(track) => (track.title.clone(), track_rows(api, track), None),
+        Playable::Episode(episode) => (
+            episode.name.clone(),
+            episode_rows(episode),
+            Some(strip_html(&episode.description)),
+        ),
+    };
+
+    let label_width = rows.iter().map(|(label, _)| label.len()).max().unwrap_or(0);
+    let mut text = rows
+        .iter()
+        .map(|(label, value)| format!("{label:<label_width$}  {value}"))
+        .collect::<Vec<String>>()
+        .join("\n");
+    if let Some(description) = description.filter(|d| !d.is_empty()) {
+        text.push_str("\n\n");
+        text.push_str(&description);
+    }
+
+    let dialog = Dialog::new()
+        .title(title)
+        .dismiss_button("Close")
+        .padding(Margins::lrtb(1, 1, 1, 0))
+        .content(ScrollView::new(TextView::new(text)).max_width(100));
+
+    Modal::new(dialog).with_name("info")
+}
@@ -0,0 +1,59 @@
+use cursive::view::ViewWrapper;
+use cursive::views::{ScrollView, SelectView};
+use cursive::Cursive;
+
+use crate::command::{aliases, Command};
+use crate::commands::CommandResult;
+use crate::ext_traits::{CursiveExt, SelectViewExt};
+use crate::keymap::escape_command;
+use crate::traits::ViewExt;
+
+/// Lists the key bindings and aliases. Selecting one puts the command that
+/// changes it on the command line.
+pub struct KeymapView {
+    view: ScrollView<SelectView<String>>,
+}
+
+impl KeymapView {
+    pub fn new(bindings: Vec<(String, String)>) -> Self {
+        let mut select = SelectView::new();
+
+        let width = bindings.iter().map(|(key, _)| key.len()).max().unwrap_or(0);
+        for (key, command) in bindings {
+            select.add_item(
+                format!("{key:<width$}   {command}"),
+                format!(":bind {} {}", key, escape_command(&command)),
+            );
+        }
+
+        for (name, command) in aliases() {
+            select.add_item(
+                format!("alias {name} = {command}"),
+                format!(":alias {} {}", name, escape_command(&command)),
+            );
+        }
+
+        select.set_on_submit(|s: &mut Cursive, command: &String| {
+            let command = command.clone();
+            s.on_layout(|_, mut layout| layout.edit_cmdline(&command));
+        });
+
+        Self {
+            view: ScrollView::new(select),
+        }
+    }
+}
+
+impl ViewWrapper for KeymapView {
+    wrap_impl!(self.view: ScrollView<SelectView<String>>);
+}
+
+impl ViewExt for KeymapView {
+    fn title(&self) -> String {
+        "Keymap".to_string()
+    }
+
+    fn on_command(&mut self, _s: &mut Cursive, cmd: &Command) -> Result<CommandResult, String> {
+        self.view.get_inner_mut().handle_command(cmd)
+    }
+}
@@ -17,7 +17,15 @@ use crate::command::{self, Command, JumpMode};
 use crate::commands::CommandResult;
 use crate::events;
 use crate::ext_traits::CursiveExt;
+use crate::history::History;
 use crate::traits::{IntoBoxedViewExt, ViewExt};
+use crate::ui::cover;
+
+/// The cache file the history of entered commands is stored in.
+const COMMAND_HISTORY: &str = "command_history";
+
+/// The cache file the history of `/` queries is stored in.
+const SEARCH_HISTORY: &str = "search_history";
 
 pub struct Layout {
     screens: HashMap<String, Box<dyn ViewExt>>,
@@ -26,6 +34,10 @@ pub struct Layout {
     focus: Option<String>,
     cmdline: EditView,
     cmdline_focus: bool,
+    /// Completions or the arguments that are missing, shown after the command line.
+    cmdline_hint: Option<String>,
+    command_history: History,
+    search_history: History,
     result: Result<Option<String>, String>,
     result_time: Option<SystemTime>,
     last_size: Vec2,
@@ -48,8 +60,11 @@ impl Layout {
         let event_manager = ev.clone();
         // 1. When a search was submitted on the commandline...
         command_line_input.set_on_submit(move |s, cmd| {
-            // 2. Clear the commandline on Layout...
-            s.on_layout(|_, mut layout| layout.clear_cmdline());
+            // 2. Remember it in the history and clear the commandline on Layout...
+            s.on_layout(|_, mut layout| {
+                layout.add_to_history(cmd);
+                layout.clear_cmdline();
+            });
 
             // 3. Get the actual command without the prefix (like `:` or `/`)...
             let mut command_characters = cmd.chars();
@@ -88,6 +103,9 @@ impl Layout {
             focus: None,
             cmdline: command_line_input,
             cmdline_focus: false,
+            cmdline_hint: None,
+            command_history: History::load(COMMAND_HISTORY),
+            search_history: History::load(SEARCH_HISTORY),
             result: Ok(None),
             result_time: None,
             last_size: Vec2::new(0, 0),
@@ -103,6 +121,14 @@ impl Layout {
         }
     }
 
+    /// Focus the command line with `content` on it, so that it can be edited
+    /// before it is submitted.
+    pub fn edit_cmdline(&mut self, content: &str) {
+        self.cmdline.set_content(content);
+        self.cmdline_focus = true;
+        self.cmdline_hint = None;
+    }
+
     pub fn enable_jump(&mut self) {
         if !self.cmdline_focus {
             self.cmdline.set_content("/");
@@ -147,6 +173,9 @@ impl Layout {
     pub fn clear_cmdline(&mut self) {
         self.cmdline.set_content("");
         self.cmdline_focus = false;
+        self.cmdline_hint = None;
+        self.command_history.reset();
+        self.search_history.reset();
         self.result = Ok(None);
         self.result_time = None;
     }
@@ -233,13 +262,96 @@ impl Layout {
         }
     }
 
+    /// Add the submitted command line `content` to the history of its prefix.
+    fn add_to_history(&mut self, content: &str) {
+        if let Some(query) = content.strip_prefix('/') {
+            self.search_history.push(query);
+        } else if let Some(command) = content.strip_prefix(':') {
+            self.command_history.push(command);
+        }
+    }
+
+    /// Replace the command line with the previous (`older`) or next entry from the history of
+    /// its prefix.
+    fn browse_history(&mut self, older: bool) {
+        let content = self.cmdline.get_content();
+        let (prefix, current) = content.split_at(1);
+        let history = match prefix {
+            "/" => &mut self.search_history,
+            _ => &mut self.command_history,
+        };
+        let entry = if older {
+            history.previous(current)
+        } else {
+            history.next()
+        };
+
+        if let Some(entry) = entry.map(|entry| format!("{prefix}{entry}")) {
+            self.cmdline.set_content(entry);
+        }
+    }
+
+    /// Complete the word before the cursor on the command line. If there are several
+    /// completions, their common prefix is completed and the completions are shown as a hint.
+    fn complete_cmdline(&mut self) {
+        let content = self.cmdline.get_content();
+        let Some(input) = content.strip_prefix(':') else {
+            return;
+        };
+
+        let (start, completions) = command::complete(input);
+        let completed = match completions.as_slice() {
+            [] => return,
+            [completion] => format!("{completion} "),
+            [first, rest @ ..] => {
+                let common = rest.iter().fold(first.len(), |len, completion| {
+                    first
+                        .char_indices()
+                        .zip(completion.chars())
+                        .take_while(|((i, a), b)| *i < len && a == b)
+                        .map(|((i, a), _)| i + a.len_utf8())
+                        .last()
+                        .unwrap_or(0)
+                });
+                self.cmdline_hint = Some(completions.join(" "));
+                first[..common].to_string()
+            }
+        };
+
+        let completed = format!("{}{}", &input[..start], completed);
+        if completions.len() == 1 {
+            self.cmdline_hint = command::hint(&completed);
+        }
+        self.cmdline.set_content(format!(":{completed}"));
+    }
+
     /// Propagate the given event to the command line.
     fn command_line_handle_event(&mut self, event: Event) -> EventResult {
         let is_left_right_event = matches!(event, Event::Key(Key::Left) | Event::Key(Key::Right));
-        let result = self.cmdline.on_event(event);
+        let result = match event {
+            Event::Key(Key::Up) => {
+                self.browse_history(true);
+                EventResult::consumed()
+            }
+            Event::Key(Key::Down) => {
+                self.browse_history(false);
+                EventResult::consumed()
+            }
+            Event::Key(Key::Tab) => {
+                self.complete_cmdline();
+                return EventResult::consumed();
+            }
+            _ => self.cmdline.on_event(event),
+        };
 
         if self.cmdline.get_content().is_empty() {
             self.clear_cmdline();
+        } else {
+            self.cmdline_hint = self
+                .cmdline
+                .get_content()
+                .strip_prefix(':')
+                .and_then(command::hint);
         }
 
         if is_left_right_event {
@@ -252,6 +364,7 @@ impl Layout {
 
 impl View for Layout {
     fn draw(&self, printer: &Printer<'_, '_>) {
+        cover::begin_frame();
         let result = self.get_result();
 
         let cmdline_visible = self.cmdline.get_content().len() > 0;
@@ -313,6 +426,15 @@ impl View for Layout {
         if cmdline_visible {
             let printer = &printer.offset((0, printer.size.y - 1));
             self.cmdline.draw(printer);
+
+            if let Some(hint) = &self.cmdline_hint {
+                let offset = self.cmdline.get_content().width() + 2;
+                if offset + hint.width() <= printer.size.x {
+                    printer.with_color(ColorStyle::secondary(), |printer| {
+                        printer.print((offset, 0), hint);
+                    });
+                }
+            }
         }
     }
 
@@ -9,6 +9,7 @@ use crate::library::Library;
 use crate::queue::Queue;
 use crate::traits::ViewExt;
 use crate::ui::browse::BrowseView;
+use crate::ui::inbox::InboxView;
 use crate::ui::listview::ListView;
 use crate::ui::playlists::PlaylistsView;
 use crate::ui::tabbedview::TabbedView;
@@ -26,6 +27,7 @@ impl LibraryView {
         tabview.add_tab("Artists", ListView::new(library.artists.clone(), queue.clone(), library.clone()));
         tabview.add_tab("Playlists", PlaylistsView::new(queue.clone(), library.clone()));
         tabview.add_tab("Podcasts", ListView::new(library.shows.clone(), queue.clone(), library.clone()));
+        tabview.add_tab("Inbox", InboxView::new(queue.clone(), library.clone()));
         tabview.add_tab("Browse", BrowseView::new(queue.clone(), library.clone()));
 
         Self {
@@ -48,6 +50,10 @@ impl ViewExt for LibraryView {
         }
     }
 
+    fn title_sub(&self) -> String {
+        self.tabs.title_sub()
+    }
+
     fn on_command(&mut self, s: &mut Cursive, cmd: &Command) -> Result<CommandResult, String> {
         self.tabs.on_command(s, cmd)
     }
@@ -2,6 +2,7 @@ use cursive::view::scroll::Scroller;
 use log::info;
 use std::cmp::{max, min, Ordering};
 use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
 
 use cursive::align::HAlign;
 use cursive::event::{Callback, Event, EventResult, MouseButton, MouseEvent};
@@ -12,19 +13,26 @@ use cursive::{Cursive, Printer, Rect, Vec2};
 use unicode_width::UnicodeWidthStr;
 
 use crate::command::{Command, GotoMode, JumpMode, MoveAmount, MoveMode, TargetMode};
+use crate::application::send_command;
 use crate::commands::CommandResult;
+use crate::export::{export, expand_path};
 use crate::ext_traits::CursiveExt;
 use crate::library::Library;
 use crate::model::playable::Playable;
 use crate::model::track::Track;
 use crate::queue::Queue;
+use crate::spotify_api::MAX_RECOMMENDATION_SEEDS;
 use crate::traits::{IntoBoxedViewExt, ListItem, ViewExt};
 use crate::ui::album::AlbumView;
 use crate::ui::artist::ArtistView;
 use crate::ui::contextmenu::ContextMenu;
+use crate::ui::info::info_dialog;
 use crate::ui::pagination::Pagination;
 use crate::ui::selectview::select_artist;
 
+/// The maximum time between two clicks on the same item for them to count as a double click.
+const DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(500);
+
 pub struct ListView<I: ListItem> {
     content: Arc<RwLock<Vec<I>>>,
     last_content_len: usize,
@@ -38,6 +46,11 @@ pub struct ListView<I: ListItem> {
     library: Arc<Library>,
     pagination: Pagination<I>,
     title: String,
+    /// Labels items so that a separator is drawn above each group of
+    /// consecutive items with the same label.
+    separator: Option<fn(&I) -> String>,
+    /// The index of the last clicked item and when it was clicked, to detect double clicks.
+    last_click: Option<(usize, Instant)>,
 }
 
 impl<I: ListItem> Scroller for ListView<I> {
@@ -65,6 +78,8 @@ impl<I: ListItem + Clone> ListView<I> {
             library,
             pagination: Pagination::default(),
             title: "".to_string(),
+            separator: None,
+            last_click: None,
         };
         result.try_paginate();
         result
@@ -75,6 +90,67 @@ impl<I: ListItem + Clone> ListView<I> {
         self
     }
 
+    pub fn with_separators(mut self, label: fn(&I) -> String) -> Self {
+        self.separator = Some(label);
+        self
+    }
+
+    /// The separators to draw, as the index of the item they're drawn above
+    /// and their label.
+    fn separators(&self) -> Vec<(usize, String)> {
+        let mut separators: Vec<(usize, String)> = Vec::new();
+        if let Some(label) = self.separator {
+            for (index, item) in self.content.read().unwrap().iter().enumerate() {
+                let label = label(item);
+                if separators.last().map(|(_, l)| l) != Some(&label) {
+                    separators.push((index, label));
+                }
+            }
+        }
+        separators
+    }
+
+    /// The index of the item drawn at `row`, or the label of the separator
+    /// drawn there.
+    fn row_content(row: usize, separators: &[(usize, String)]) -> Result<usize, &str> {
+        let mut offset = 0;
+        for (index, label) in separators {
+            match row.cmp(&(index + offset)) {
+                Ordering::Equal => return Err(label),
+                Ordering::Less => break,
+                Ordering::Greater => offset += 1,
+            }
+        }
+        Ok(row - offset)
+    }
+
+    /// The row the item at `index` is drawn at.
+    fn row_of(&self, index: usize) -> usize {
+        index
+            + self
+                .separators()
+                .iter()
+                .filter(|(i, _)| *i <= index)
+                .count()
+    }
+
+    /// The index of the item drawn at `row`, if it isn't a separator.
+    fn index_at(&self, row: usize) -> Option<usize> {
+        Self::row_content(row, &self.separators()).ok()
+    }
+
+    /// The index of the item drawn at `position`, relative to the top left corner of this view.
+    pub fn index_at_position(&self, position: Vec2) -> Option<usize> {
+        let viewport = self.scroller.content_viewport().top_left();
+        self.index_at(position.y + viewport.y)
+            .filter(|index| *index < self.content_len(false))
+    }
+
+    /// Return whether the scrollbar is currently being dragged.
+    pub fn is_dragging_scrollbar(&self) -> bool {
+        self.scroller.is_scrolling().any()
+    }
+
     pub fn get_pagination(&self) -> &Pagination<I> {
         &self.pagination
     }
@@ -144,7 +220,7 @@ impl<I: ListItem + Clone> ListView<I> {
     pub fn move_focus_to(&mut self, target: usize) {
         let len = self.content_len(false).saturating_sub(1);
         self.selected = min(target, len);
-        self.scroller.scroll_to_y(self.selected);
+        self.scroller.scroll_to_y(self.row_of(self.selected));
     }
 
     pub fn move_focus(&mut self, delta: i32) {
@@ -184,9 +260,21 @@ impl<I: ListItem + Clone> ListView<I> {
 
 impl<I: ListItem + Clone> View for ListView<I> {
     fn draw(&self, printer: &Printer<'_, '_>) {
+        let separators = self.separators();
         let content = self.content.read().unwrap();
 
-        scroll::draw_lines(self, printer, |_, printer, i| {
+        scroll::draw_lines(self, printer, |_, printer, row| {
+            let i = match Self::row_content(row, &separators) {
+                Ok(i) => i,
+                Err(label) => {
+                    printer.with_color(ColorStyle::secondary(), |printer| {
+                        printer.print_hline((0, 0), printer.size.x, "─");
+                        printer.print((1, 0), &format!(" {label} "));
+                    });
+                    return;
+                }
+            };
+
             // draw paginator after content
             if i == content.len() && self.can_paginate() {
                 let style = ColorStyle::secondary();
@@ -311,7 +399,7 @@ impl<I: ListItem + Clone> View for ListView<I> {
             size,
             relayout_scroller,
             |_, _| {},
-            |s, c| Vec2::new(c.x, s.content_len(true)),
+            |s, c| Vec2::new(c.x, s.content_len(true) + s.separators().len()),
         );
     }
 
@@ -353,50 +441,41 @@ impl<I: ListItem + Clone> View for ListView<I> {
 
                 if drag_started {
                     log::debug!("grabbing scroller");
-                } else {
-                    let viewport = self.scroller.content_viewport().top_left();
-                    let selected_row = position.checked_sub(offset).map(|p| p.y + viewport.y);
-                    if let Some(clicked_row_index) =
-                        selected_row.filter(|row| *row < self.content_len(false))
+                } else if let Some(clicked_row_index) =
+                    self.index_at_position(view_coordinates_click_position)
+                {
+                    let double_click = matches!(
+                        self.last_click,
+                        Some((index, time))
+                            if index == clicked_row_index && time.elapsed() < DOUBLE_CLICK_INTERVAL
+                    );
+                    self.move_focus_to(clicked_row_index);
+
+                    if !double_click {
+                        self.last_click = Some((clicked_row_index, Instant::now()));
+                        return EventResult::consumed();
+                    }
+                    self.last_click = None;
+
+                    let content = self.content.read().unwrap();
+                    let clicked_list_item = content.get(self.selected).map(ListItem::as_listitem);
+                    let is_individual = clicked_list_item
+                        .as_ref()
+                        .map(|item| item.track().is_some())
+                        .unwrap_or(false);
+
+                    if is_individual {
+                        // Play the item through the command so that wrapping views like the
+                        // queue can handle it the same way as pressing Enter.
+                        return EventResult::Consumed(Some(Callback::from_fn_once(|s| {
+                            send_command(s, Command::Play);
+                        })));
+                    } else if let Some(view) = clicked_list_item
+                        .and_then(|target| target.open(self.queue.clone(), self.library.clone()))
                     {
-                        let currently_selected_listitem = self
-                            .content
-                            .read()
-                            .unwrap()
-                            .get(clicked_row_index)
-                            .map(ListItem::as_listitem);
-                        let currently_selected_is_individual = currently_selected_listitem
-                            .filter(|item| item.track().is_some())
-                            .is_some();
-                        if self.selected == clicked_row_index && currently_selected_is_individual {
-                            // The selected position was already focused. Play the item at the
-                            // position as if Enter was pressed. This sort of emulates double
-                            // clicking, which isn't supported by Cursive.
-                            self.queue.clear();
-
-                            if !self.attempt_play_all_tracks() {
-                                self.play_current_item();
-                            }
-                        } else {
-                            // The clicked position wasn't focused yet or the item is a collection
-                            // that can be opened.
-                            self.move_focus_to(clicked_row_index);
-                            let content = self.content.read().unwrap();
-                            let clicked_list_item =
-                                content.get(self.selected).map(ListItem::as_listitem);
-
-                            if let Some(target) = clicked_list_item {
-                                if let Some(view) =
-                                    target.open(self.queue.clone(), self.library.clone())
-                                {
-                                    return EventResult::Consumed(Some(Callback::from_fn_once(
-                                        move |s| {
-                                            s.on_layout(|_, mut l| l.push_view(view));
-                                        },
-                                    )));
-                                }
-                            }
-                        }
+                        return EventResult::Consumed(Some(Callback::from_fn_once(move |s| {
+                            s.on_layout(|_, mut l| l.push_view(view));
+                        })));
                     }
                 }
             }
@@ -405,9 +484,10 @@ impl<I: ListItem + Clone> View for ListView<I> {
                 position,
                 offset,
             } => {
-                let viewport = self.scroller.content_viewport().top_left();
-                let selected_row = position.checked_sub(offset).map(|p| p.y + viewport.y);
-                if let Some(y) = selected_row.filter(|row| row < &self.content_len(false)) {
+                if let Some(y) = position
+                    .checked_sub(offset)
+                    .and_then(|p| self.index_at_position(p))
+                {
                     self.move_focus_to(y);
 
                     let queue = self.queue.clone();
@@ -449,7 +529,7 @@ impl<I: ListItem + Clone> View for ListView<I> {
 
     fn important_area(&self, view_size: Vec2) -> Rect {
         if self.content_len(false) > 0 {
-            Rect::from_point((view_size.x, self.selected))
+            Rect::from_point((view_size.x, self.row_of(self.selected)))
         } else {
             Rect::from_point((0, 0))
         }
@@ -489,6 +569,18 @@ impl<I: ListItem + Clone> ViewExt for ListView<I> {
 
                 return Ok(CommandResult::Consumed(None));
             }
+            Command::Save(TargetMode::Selected) => {
+                // release the lock first, the content might be a list of the library
+                let item = {
+                    let content = self.content.read().unwrap();
+                    content.get(self.selected).map(ListItem::as_listitem)
+                };
+                if let Some(mut item) = item {
+                    item.save(&self.library);
+                }
+
+                return Ok(CommandResult::Consumed(None));
+            }
             Command::Jump(mode) => match mode {
                 JumpMode::Query(query) => {
                     self.search_query = query.to_lowercase();
@@ -640,6 +732,65 @@ impl<I: ListItem + Clone> ViewExt for ListView<I> {
                     };
                 }
             }
+            Command::Export(path, format) => {
+                let items: Vec<Playable> = {
+                    let content = self.content.read().unwrap();
+                    content.iter().flat_map(|i| i.exported()).collect()
+                };
+                if items.is_empty() {
+                    return Err("Nothing to export in this view".into());
+                }
+
+                let path = expand_path(path);
+                export(&path, *format, &items)?;
+                return Ok(CommandResult::Consumed(Some(format!(
+                    "Exported {} items to {}",
+                    items.len(),
+                    path.display()
+                ))));
+            }
+            Command::Info(mode) => {
+                let target = match mode {
+                    TargetMode::Current => self.queue.get_current(),
+                    TargetMode::Selected => {
+                        let content = self.content.read().unwrap();
+                        content.get(self.selected).and_then(|t| t.playable())
+                    }
+                };
+
+                if let Some(target) = target {
+                    let dialog = info_dialog(&self.queue.get_spotify().api, &target);
+                    return Ok(CommandResult::Modal(Box::new(dialog)));
+                }
+            }
+            Command::Seed => {
+                let seed = {
+                    let content = self.content.read().unwrap();
+                    content.get(self.selected).and_then(|t| t.seed())
+                };
+
+                if let Some(seed) = seed {
+                    let count = self.library.toggle_recommendation_seed(seed)?;
+                    return Ok(CommandResult::Consumed(Some(format!(
+                        "{count}/{MAX_RECOMMENDATION_SEEDS} recommendation seeds"
+                    ))));
+                }
+            }
+            Command::Radio => {
+                // release the content lock first, the queue may be the content
+                let target: Option<Box<dyn ListItem>> = {
+                    let content = self.content.read().unwrap();
+                    content.get(self.selected).map(|t| t.as_listitem())
+                };
+
+                if let Some(mut target) = target {
+                    let seeds = target
+                        .recommendation_seeds(&self.queue)
+                        .ok_or("No recommendations found")?;
+                    self.queue.start_radio(seeds);
+                    return Ok(CommandResult::Consumed(None));
+                }
+            }
             _ => {}
         };
 
@@ -4,7 +4,13 @@ pub mod album;
 pub mod artist;
 pub mod browse;
 pub mod contextmenu;
+pub mod cover;
+pub mod equalizer;
 pub mod help;
+pub mod import;
+pub mod inbox;
+pub mod info;
+pub mod keymap;
 pub mod layout;
 pub mod library;
 pub mod listview;
@@ -13,10 +19,12 @@ pub mod pagination;
 pub mod playlist;
 pub mod playlists;
 pub mod queue;
+pub mod recommendations;
 pub mod search;
 pub mod search_results;
 pub mod selectview;
 pub mod show;
+pub mod snapshots;
 pub mod statusbar;
 pub mod tabbedview;
 
@@ -3,18 +3,21 @@ use std::sync::{Arc, RwLock};
 use cursive::view::ViewWrapper;
 use cursive::Cursive;
 
-use crate::command::Command;
+use crate::command::{Command, FeaturesAction, SortKey};
 use crate::commands::CommandResult;
 use crate::library::Library;
 use crate::model::playable::Playable;
 use crate::model::playlist::Playlist;
+use crate::model::track::Track;
 use crate::queue::Queue;
 
-use crate::traits::ViewExt;
+use crate::traits::{ListItem, ViewExt};
 use crate::ui::listview::ListView;
 
 pub struct PlaylistView {
     playlist: Playlist,
+    /// The tracks shown by `list`, in their sorted order.
+    content: Arc<RwLock<Vec<Playable>>>,
     list: ListView<Playable>,
     library: Arc<Library>,
     queue: Arc<Queue>,
@@ -31,18 +34,37 @@ impl PlaylistView {
             Vec::new()
         };
 
-        let list = ListView::new(
-            Arc::new(RwLock::new(tracks)),
-            queue.clone(),
-            library.clone(),
-        );
+        let show_features = !library.audio_feature_columns.read().unwrap().is_empty();
+        let content = Arc::new(RwLock::new(tracks));
+        let list = ListView::new(content.clone(), queue.clone(), library.clone());
 
-        Self {
+        let view = Self {
             playlist,
+            content,
             list,
             library,
             queue,
+        };
+        if show_features {
+            view.fetch_audio_features();
         }
+        view
+    }
+
+    fn tracks(&self) -> Vec<Track> {
+        self.playlist
+            .tracks
+            .iter()
+            .flatten()
+            .filter_map(|p| p.track())
+            .collect()
+    }
+
+    /// Fetch the audio features of the tracks in the background.
+    fn fetch_audio_features(&self) {
+        let library = self.library.clone();
+        let tracks = self.tracks();
+        std::thread::spawn(move || library.fetch_audio_features(&tracks));
     }
 }
 
@@ -71,16 +93,44 @@ impl ViewExt for PlaylistView {
 
     fn on_command(&mut self, s: &mut Cursive, cmd: &Command) -> Result<CommandResult, String> {
         if let Command::Sort(key, direction) = cmd {
-            self.playlist.sort(key, direction);
+            self.playlist.sort(key, direction, &self.library);
             let tracks = self.playlist.tracks.as_ref().unwrap_or(&Vec::new()).clone();
+            self.content = Arc::new(RwLock::new(tracks));
             self.list = ListView::new(
-                Arc::new(RwLock::new(tracks)),
+                self.content.clone(),
                 self.queue.clone(),
                 self.library.clone(),
             );
+
+            if matches!(
+                key,
+                SortKey::Tempo | SortKey::Key | SortKey::Energy | SortKey::Danceability
+            ) {
+                // sort again once the missing features are fetched, unless the
+                // list was replaced by then
+                let mut playlist = self.playlist.clone();
+                let content = self.content.clone();
+                let library = self.library.clone();
+                let (key, direction) = (key.clone(), direction.clone());
+                let tracks = self.tracks();
+                std::thread::spawn(move || {
+                    if library.fetch_audio_features(&tracks) {
+                        playlist.sort(&key, &direction, &library);
+                        *content.write().unwrap() = playlist.tracks.unwrap_or_default();
+                        library.trigger_redraw();
+                    }
+                });
+            }
             return Ok(CommandResult::Consumed(None));
         }
 
+        if let Command::AudioFeatures(action) = cmd {
+            // the columns themselves are changed by the default handler
+            if !matches!(action, FeaturesAction::Switch(Some(false))) {
+                self.fetch_audio_features();
+            }
+        }
+
         self.list.on_command(s, cmd)
     }
 }
@@ -1,5 +1,6 @@
+use cursive::event::{Event, EventResult, MouseButton, MouseEvent};
 use cursive::view::ViewWrapper;
-use cursive::Cursive;
+use cursive::{Cursive, View};
 
 use std::cmp::min;
 use std::sync::Arc;
@@ -15,6 +16,8 @@ use crate::ui::listview::ListView;
 pub struct QueueView {
     list: ListView<Playable>,
     queue: Arc<Queue>,
+    /// The index of the item that is being dragged with the mouse.
+    dragging: Option<usize>,
 }
 
 impl QueueView {
@@ -24,12 +27,58 @@ impl QueueView {
         Self {
             list,
             queue,
+            dragging: None,
         }
     }
 }
 
 impl ViewWrapper for QueueView {
     wrap_impl!(self.list: ListView<Playable>);
+
+    fn wrap_on_event(&mut self, event: Event) -> EventResult {
+        match event {
+            Event::Mouse {
+                event: MouseEvent::Press(MouseButton::Left),
+                position,
+                offset,
+            } => {
+                let result = self.list.on_event(event);
+                self.dragging = if self.list.is_dragging_scrollbar() {
+                    None
+                } else {
+                    position
+                        .checked_sub(offset)
+                        .and_then(|p| self.list.index_at_position(p))
+                };
+                result
+            }
+            Event::Mouse {
+                event: MouseEvent::Hold(MouseButton::Left),
+                position,
+                offset,
+            } if self.dragging.is_some() => {
+                let target = position
+                    .checked_sub(offset)
+                    .and_then(|p| self.list.index_at_position(p));
+                if let (Some(from), Some(to)) = (self.dragging, target) {
+                    if from != to {
+                        self.queue.shift(from, to);
+                        self.list.move_focus_to(to);
+                        self.dragging = Some(to);
+                    }
+                }
+                EventResult::consumed()
+            }
+            Event::Mouse {
+                event: MouseEvent::Release(MouseButton::Left),
+                ..
+            } => {
+                self.dragging = None;
+                self.list.on_event(event)
+            }
+            _ => self.list.on_event(event),
+        }
+    }
 }
 
 impl ViewExt for QueueView {
@@ -0,0 +1,158 @@
+use std::sync::{Arc, RwLock};
+use std::thread;
+
+use cursive::view::ViewWrapper;
+use cursive::Cursive;
+use log::error;
+
+use crate::command::{Command, RecommendMode};
+use crate::commands::CommandResult;
+use crate::library::Library;
+use crate::model::playable::Playable;
+use crate::model::seed::Seed;
+use crate::model::track::Track;
+use crate::queue::Queue;
+use crate::spotify_api::MAX_RECOMMENDATION_SEEDS;
+use crate::traits::ViewExt;
+use crate::ui::listview::ListView;
+use crate::ui::tabbedview::TabbedView;
+
+const RESULTS_TAB: usize = 2;
+
+/// Builds recommendations from the seeds and tuning stored in the [Library].
+/// Seeds are added with the `seed` command on any item, including the genres
+/// listed here.
+pub struct RecommendationsView {
+    tabs: TabbedView,
+    results: Arc<RwLock<Vec<Track>>>,
+    queue: Arc<Queue>,
+    library: Arc<Library>,
+}
+
+impl RecommendationsView {
+    pub fn new(queue: Arc<Queue>, library: Arc<Library>) -> Self {
+        let genres: Arc<RwLock<Vec<Seed>>> = Arc::new(RwLock::new(Vec::new()));
+        {
+            let genres = genres.clone();
+            let spotify = queue.get_spotify();
+            let library = library.clone();
+            thread::spawn(move || {
+                if let Some(names) = spotify.api.available_genre_seeds() {
+                    genres
+                        .write()
+                        .unwrap()
+                        .extend(names.into_iter().map(Seed::Genre));
+                    library.trigger_redraw();
+                }
+            });
+        }
+        let results = Arc::new(RwLock::new(Vec::new()));
+
+        let mut tabs = TabbedView::new();
+        tabs.add_tab(
+            "Seeds",
+            ListView::new(
+                library.recommendation_seeds.clone(),
+                queue.clone(),
+                library.clone(),
+            ),
+        );
+        tabs.add_tab(
+            "Genres",
+            ListView::new(genres, queue.clone(), library.clone()),
+        );
+        tabs.add_tab(
+            "Results",
+            ListView::new(results.clone(), queue.clone(), library.clone()),
+        );
+
+        let mut view = Self {
+            tabs,
+            results,
+            queue,
+            library,
+        };
+        if !view.library.recommendation_seeds.read().unwrap().is_empty() {
+            // errors will show up once the user refreshes themselves
+            let _ = view.refresh(false);
+        }
+        view
+    }
+
+    /// Replace the results with new recommendations in the background and
+    /// show them, queueing them as well if `queue` is set.
+    fn refresh(&mut self, queue: bool) -> Result<(), String> {
+        if self.library.recommendation_seeds.read().unwrap().is_empty() {
+            return Err("Add seeds with the \"seed\" command first".into());
+        }
+
+        let results = self.results.clone();
+        let library = self.library.clone();
+        let player_queue = self.queue.clone();
+        thread::spawn(move || {
+            let Some(tracks) = library.recommendations() else {
+                error!("could not fetch recommendations");
+                return;
+            };
+            if queue {
+                for track in tracks.iter() {
+                    player_queue.append(Playable::Track(track.clone()));
+                }
+            }
+            *results.write().unwrap() = tracks;
+            library.trigger_redraw();
+        });
+        self.tabs.set_selected(RESULTS_TAB);
+        Ok(())
+    }
+}
+
+impl ViewWrapper for RecommendationsView {
+    wrap_impl!(self.tabs: TabbedView);
+}
+
+impl ViewExt for RecommendationsView {
+    fn title(&self) -> String {
+        "Recommendations".to_string()
+    }
+
+    fn title_sub(&self) -> String {
+        let seeds = self.library.recommendation_seeds.read().unwrap().len();
+        let mut sub = format!("{seeds}/{MAX_RECOMMENDATION_SEEDS} seeds");
+        for tuning in self.library.recommendation_tuning.read().unwrap().iter() {
+            sub.push_str(&format!(", {tuning}"));
+        }
+        sub
+    }
+
+    fn on_command(&mut self, s: &mut Cursive, cmd: &Command) -> Result<CommandResult, String> {
+        match cmd {
+            Command::Recommend(RecommendMode::Show) => {
+                self.refresh(false)?;
+                Ok(CommandResult::Consumed(None))
+            }
+            Command::Tune(attribute, tuning) => {
+                self.library.tune_recommendations(*attribute, *tuning)?;
+                if !self.library.recommendation_seeds.read().unwrap().is_empty() {
+                    self.refresh(false)?;
+                }
+                Ok(CommandResult::Consumed(None))
+            }
+            Command::Recommend(RecommendMode::Queue) => {
+                if self.results.read().unwrap().is_empty() {
+                    self.refresh(true)?;
+                    return Ok(CommandResult::Consumed(None));
+                }
+                let tracks = self.results.read().unwrap().clone();
+                for track in tracks.iter() {
+                    self.queue.append(Playable::Track(track.clone()));
+                }
+                Ok(CommandResult::Consumed(Some(format!(
+                    "Queued {} tracks",
+                    tracks.len()
+                ))))
+            }
+            _ => self.tabs.on_command(s, cmd),
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use std::sync::{Arc, RwLock};
+
+use cursive::view::ViewWrapper;
+use cursive::Cursive;
+
+use crate::command::Command;
+use crate::commands::CommandResult;
+use crate::library::Library;
+use crate::model::snapshot::QueueSnapshot;
+use crate::queue::Queue;
+use crate::traits::ViewExt;
+use crate::ui::listview::ListView;
+
+/// Lists the queues that were saved with `:queue save`.
+pub struct SnapshotsView {
+    snapshots: Arc<RwLock<Vec<QueueSnapshot>>>,
+    list: ListView<QueueSnapshot>,
+}
+
+impl SnapshotsView {
+    pub fn new(queue: Arc<Queue>, library: Arc<Library>) -> Self {
+        let snapshots = Arc::new(RwLock::new(QueueSnapshot::list()));
+        Self {
+            list: ListView::new(snapshots.clone(), queue, library),
+            snapshots,
+        }
+    }
+}
+
+impl ViewWrapper for SnapshotsView {
+    wrap_impl!(self.list: ListView<QueueSnapshot>);
+}
+
+impl ViewExt for SnapshotsView {
+    fn title(&self) -> String {
+        "Saved queues".to_string()
+    }
+
+    fn title_sub(&self) -> String {
+        format!("{} queues", self.snapshots.read().unwrap().len())
+    }
+
+    fn on_command(&mut self, s: &mut Cursive, cmd: &Command) -> Result<CommandResult, String> {
+        self.list.on_command(s, cmd)
+    }
+}
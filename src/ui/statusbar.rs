@@ -13,6 +13,10 @@ use crate::model::playable::Playable;
 use crate::queue::{Queue, RepeatSetting};
 use crate::spotify::{PlayerEvent, Spotify};
 use crate::utils::ms_to_hms;
+use crate::visualizer::BANDS;
+
+/// The blocks the levels of the visualizer are drawn with, from low to high.
+const LEVELS: [&str; 8] = ["▁", "▂", "▃", "▄", "▅", "▆", "▇", "█"];
 
 pub struct StatusBar {
     queue: Arc<Queue>,
@@ -43,10 +47,7 @@ impl StatusBar {
     }
 
     fn volume_display(&self) -> String {
-        format!(
-            " [{}%]",
-            (self.spotify.volume() as f64 / 65535_f64 * 100.0).round() as u16
-        )
+        format!(" [{}%]", self.spotify.volume_percent())
     }
 
     fn format_track(&self, t: &Playable) -> String {
@@ -104,6 +105,8 @@ impl View for StatusBar {
 
         let shuffle = if self.queue.get_shuffle() { "[Z] " } else { "" };
 
+        let autoplay = if self.queue.get_radio() { "[A] " } else { "" };
+
         let volume = self.volume_display();
 
         printer.with_color(style_bar_bg, |printer| {
@@ -123,6 +126,7 @@ impl View for StatusBar {
         let right = updating.to_string()
             + repeat
             + shuffle
+            + autoplay
             // + saved
             + &playback_duration_status
             + &volume;
@@ -136,11 +140,26 @@ impl View for StatusBar {
         });
 
         if let Some(t) = self.queue.get_current() {
-            printer.with_color(style_bar, |printer| {
-                let duration_width =
-                    (((printer.size.x as u32) * elapsed_ms) / t.duration()) as usize;
-                printer.print((0, 0), &"━".repeat(duration_width + 1));
-            });
+            let duration_width = (((printer.size.x as u32) * elapsed_ms) / t.duration()) as usize;
+            match self.spotify.visualizer().bands() {
+                // The spectrum takes the place of the progress bar, coloured
+                // up to the playback position.
+                Some(bands) => {
+                    for x in 0..printer.size.x {
+                        let level = bands[x * BANDS / printer.size.x];
+                        let block = LEVELS[((level * LEVELS.len() as f32) as usize).min(7)];
+                        let style = if x <= duration_width {
+                            style_bar
+                        } else {
+                            style_bar_bg
+                        };
+                        printer.with_color(style, |printer| printer.print((x, 0), block));
+                    }
+                }
+                None => printer.with_color(style_bar, |printer| {
+                    printer.print((0, 0), &"━".repeat(duration_width + 1));
+                }),
+            }
         }
     }
 
@@ -180,21 +199,11 @@ impl View for StatusBar {
                 }
             } else if self.last_size.x - position.x < volume_len {
                 if event == MouseEvent::WheelUp {
-                    let volume = self
-                        .spotify
-                        .volume()
-                        .saturating_add(crate::spotify::VOLUME_PERCENT);
-
-                    self.spotify.set_volume(volume);
+                    self.spotify.change_volume(1);
                 }
 
                 if event == MouseEvent::WheelDown {
-                    let volume = self
-                        .spotify
-                        .volume()
-                        .saturating_sub(crate::spotify::VOLUME_PERCENT);
-
-                    self.spotify.set_volume(volume);
+                    self.spotify.change_volume(-1);
                 }
             } else if event == MouseEvent::Press(MouseButton::Left) {
                 self.queue.toggleplayback();
@@ -187,6 +187,13 @@ impl View for TabbedView {
 }
 
 impl ViewExt for TabbedView {
+    fn title_sub(&self) -> String {
+        self.tabs
+            .get(self.selected)
+            .map(|tab| tab.title_sub())
+            .unwrap_or_default()
+    }
+
     fn on_command(&mut self, s: &mut Cursive, cmd: &Command) -> Result<CommandResult, String> {
         match cmd {
             Command::Move(mode, amount) if matches!(mode, MoveMode::Left | MoveMode::Right) => {
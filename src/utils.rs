@@ -40,3 +40,21 @@ pub fn ms_to_hms(duration: u32) -> String {
 
     formated_time
 }
+
+/// Returns `html` as plain text, with tags removed and common entities decoded.
+/// Line breaks and paragraphs are kept as newlines.
+pub fn strip_html(html: &str) -> String {
+    let breaks = regex::Regex::new(r"(?i)<br\s*/?>|</p>").unwrap();
+    let tags = regex::Regex::new(r"<[^>]*>").unwrap();
+
+    let text = breaks.replace_all(html, "\n");
+    let text = tags.replace_all(&text, "");
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+        .trim()
+        .to_string()
+}
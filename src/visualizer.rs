@@ -0,0 +1,213 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Instant;
+
+use crossbeam_channel::{bounded, Receiver, RecvTimeoutError, Sender};
+use librespot_playback::audio_backend::{Sink, SinkResult};
+use librespot_playback::convert::Converter;
+use librespot_playback::decoder::AudioPacket;
+use librespot_playback::{NUM_CHANNELS, SAMPLE_RATE};
+use log::error;
+use rustfft::num_complex::Complex;
+use rustfft::FftPlanner;
+
+use crate::spotify_worker::{UI_REFRESH, VISUALIZER_REFRESH};
+
+/// The amount of samples the spectrum is computed from.
+const FFT_SIZE: usize = 2048;
+
+/// The amount of frequency bands in the spectrum.
+pub const BANDS: usize = 64;
+
+/// The range of frequencies shown, in Hz.
+const MIN_FREQUENCY: f32 = 40.0;
+const MAX_FREQUENCY: f32 = 16000.0;
+
+/// The quietest level shown, in dB.
+const FLOOR_DB: f32 = -70.0;
+
+/// How much of its height a band keeps each frame when the level drops.
+const DECAY: f32 = 0.75;
+
+/// How many packets may wait for the analysis. Packets are dropped rather
+/// than holding up playback when it falls behind.
+const QUEUE_LENGTH: usize = 16;
+
+/// The last spectrum and when it was computed.
+#[derive(Default)]
+struct Spectrum {
+    bands: Vec<f32>,
+    updated: Option<Instant>,
+}
+
+/// A spectrum analyser of the audio that is played. Audio is tapped from the
+/// sink and analysed on a background thread, no more often than the screen is
+/// redrawn.
+#[derive(Clone)]
+pub struct Visualizer {
+    enabled: Arc<AtomicBool>,
+    spectrum: Arc<Mutex<Spectrum>>,
+    samples: Sender<Vec<f32>>,
+}
+
+impl Visualizer {
+    pub fn new() -> Self {
+        let (tx, rx) = bounded(QUEUE_LENGTH);
+        let visualizer = Self {
+            enabled: Arc::new(AtomicBool::new(false)),
+            spectrum: Arc::new(Mutex::new(Spectrum::default())),
+            samples: tx,
+        };
+
+        let spectrum = visualizer.spectrum.clone();
+        thread::Builder::new()
+            .name("visualizer".into())
+            .spawn(move || analyse(rx, spectrum))
+            .expect("could not start visualizer thread");
+        visualizer
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+        if !enabled {
+            *self.spectrum.lock().unwrap() = Spectrum::default();
+        }
+    }
+
+    /// The levels of the frequency bands from low to high between 0 and 1, or
+    /// `None` if nothing was played recently.
+    pub fn bands(&self) -> Option<Vec<f32>> {
+        let spectrum = self.spectrum.lock().unwrap();
+        let updated = spectrum.updated?;
+        (self.is_enabled() && updated.elapsed() < UI_REFRESH).then(|| spectrum.bands.clone())
+    }
+
+    /// Wrap `sink` so the audio written to it is analysed while the
+    /// visualiser is enabled.
+    pub fn tap(&self, sink: Box<dyn Sink>) -> Box<dyn Sink> {
+        Box::new(TapSink {
+            sink,
+            visualizer: self.clone(),
+        })
+    }
+}
+
+impl Default for Visualizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Passes the audio on to the sink it wraps, sending a copy to the analysis.
+struct TapSink {
+    sink: Box<dyn Sink>,
+    visualizer: Visualizer,
+}
+
+impl Sink for TapSink {
+    fn start(&mut self) -> SinkResult<()> {
+        self.sink.start()
+    }
+
+    fn stop(&mut self) -> SinkResult<()> {
+        self.sink.stop()
+    }
+
+    fn write(&mut self, packet: AudioPacket, converter: &mut Converter) -> SinkResult<()> {
+        if self.visualizer.is_enabled() {
+            if let Ok(samples) = packet.samples() {
+                let mono = samples
+                    .chunks(NUM_CHANNELS as usize)
+                    .map(|frame| (frame.iter().sum::<f64>() / frame.len() as f64) as f32)
+                    .collect();
+                let _ = self.visualizer.samples.try_send(mono);
+            }
+        }
+        self.sink.write(packet, converter)
+    }
+}
+
+/// The range of FFT bins of every band, spaced logarithmically.
+fn band_bins() -> Vec<(usize, usize)> {
+    let bin_width = SAMPLE_RATE as f32 / FFT_SIZE as f32;
+    let ratio = (MAX_FREQUENCY / MIN_FREQUENCY).powf(1.0 / BANDS as f32);
+    (0..BANDS)
+        .map(|band| {
+            let low = MIN_FREQUENCY * ratio.powi(band as i32);
+            let high = low * ratio;
+            let start = ((low / bin_width) as usize).max(1);
+            let end = ((high / bin_width) as usize).max(start + 1);
+            (start, end.min(FFT_SIZE / 2))
+        })
+        .collect()
+}
+
+/// Compute the spectrum of the latest samples from `samples` until all
+/// visualisers are dropped.
+fn analyse(samples: Receiver<Vec<f32>>, spectrum: Arc<Mutex<Spectrum>>) {
+    let fft = FftPlanner::<f32>::new().plan_fft_forward(FFT_SIZE);
+    let window: Vec<f32> = (0..FFT_SIZE)
+        .map(|i| {
+            let phase = 2.0 * std::f32::consts::PI * i as f32 / FFT_SIZE as f32;
+            0.5 - 0.5 * phase.cos()
+        })
+        .collect();
+    let bins = band_bins();
+
+    let mut latest = vec![0.0; FFT_SIZE];
+    let mut buffer = vec![Complex::default(); FFT_SIZE];
+    let mut levels = vec![0.0; BANDS];
+    let mut computed = Instant::now();
+
+    loop {
+        match samples.recv_timeout(UI_REFRESH) {
+            Ok(chunk) => {
+                let keep = FFT_SIZE.saturating_sub(chunk.len());
+                latest.drain(..FFT_SIZE - keep);
+                latest.extend(&chunk[chunk.len().saturating_sub(FFT_SIZE)..]);
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                levels.fill(0.0);
+                continue;
+            }
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+
+        // There's no use in computing frames that are never drawn.
+        if computed.elapsed() < VISUALIZER_REFRESH {
+            continue;
+        }
+        computed = Instant::now();
+
+        for ((value, sample), weight) in buffer.iter_mut().zip(&latest).zip(&window) {
+            *value = Complex::new(sample * weight, 0.0);
+        }
+        fft.process(&mut buffer);
+
+        for (level, (start, end)) in levels.iter_mut().zip(&bins) {
+            let magnitude = buffer[*start..*end]
+                .iter()
+                .map(|bin| bin.norm())
+                .fold(0.0, f32::max);
+            let db = 20.0 * (magnitude * 4.0 / FFT_SIZE as f32).max(1e-9).log10();
+            let new = ((db - FLOOR_DB) / -FLOOR_DB).clamp(0.0, 1.0);
+            *level = new.max(*level * DECAY);
+        }
+
+        match spectrum.lock() {
+            Ok(mut spectrum) => {
+                spectrum.bands = levels.clone();
+                spectrum.updated = Some(computed);
+            }
+            Err(e) => {
+                error!("visualizer spectrum is poisoned: {}", e);
+                break;
+            }
+        }
+    }
+}